@@ -2,16 +2,22 @@
 
 use memchr::{memchr as find_char, memrchr as find_char_reverse};
 use serde::Serialize;
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashSet};
+use std::net::IpAddr;
 use std::ops::DerefMut;
 use std::sync::OnceLock;
 
+use crate::filters::fb_network::FlatNetworkFilter;
 use crate::filters::fb_network_builder::NetworkFilterListId;
 use crate::filters::filter_data_context::FilterDataContextRef;
-use crate::filters::network::NetworkFilterMaskHelper;
-use crate::network_filter_list::NetworkFilterList;
+use crate::filters::network::{
+    DnsRewrite, HeaderMatcher, HeaderValue, NetworkFilterMaskHelper, RemoveparamMatcher,
+    RemoveparamValue, ReplaceValue, parse_permissions_directives,
+};
+use crate::network_filter_list::{CheckResult, NetworkFilterList};
 use crate::regex_manager::{RegexManager, RegexManagerDiscardPolicy};
-use crate::request::Request;
+use crate::request::{Request, RequestError};
 use crate::resources::ResourceStorage;
 use crate::sourcemap::FilterRuleDebugInfo;
 
@@ -53,10 +59,37 @@ pub struct BlockerResult {
     /// should be blocked. The `redirect-rule` option can produce a redirection
     /// that's only applied if another blocking filter matches a request.
     pub redirect: Option<String>,
-    /// `removeparam` may remove URL parameters. If the original request URL was
-    /// modified at all, the new version will be here. This should be used
-    /// as long as the request is not blocked.
+    /// `removeparam` may remove URL parameters, either by name, by regex
+    /// (tested against `name=value`), all parameters except those matching a
+    /// negated (`~`) value, or all parameters at once. `urltransform` may then
+    /// rewrite the resulting URL with a regex replacement. If the original
+    /// request URL was modified at all, the new version will be here. This
+    /// should be used as long as the request is not blocked.
     pub rewritten_url: Option<String>,
+    /// `removeparam` filters that removed at least one parameter to produce
+    /// `rewritten_url`.
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub removeparam_filters: Vec<FilterRuleDebugInfo>,
+    /// `removeparam` filters that matched the request, but were disabled by a
+    /// `removeparam` exception rule, e.g. `@@||example.com^$removeparam=q` or
+    /// `@@||example.com^$removeparam`.
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub excepted_removeparam_filters: Vec<FilterRuleDebugInfo>,
+}
+
+/// Outcome of applying the `removeparam` filters matching a request.
+#[derive(Default)]
+struct RemoveparamResult {
+    rewritten_url: Option<String>,
+    applied: Vec<CheckResult>,
+    excepted: Vec<CheckResult>,
+    /// Filters that weren't excepted, but didn't match any parameter.
+    unused: Vec<CheckResult>,
+    exceptions: Vec<CheckResult>,
 }
 
 impl BlockerResult {
@@ -66,6 +99,248 @@ impl BlockerResult {
     }
 }
 
+/// Lists every filter that matched a network request, as returned by [`Blocker::explain`].
+#[derive(Debug, Serialize)]
+pub struct RequestExplanation {
+    /// How the request should be handled, as returned by [`Blocker::check`].
+    pub result: BlockerResult,
+    /// Every matching filter, in the order its [`FilterBucket`] is checked.
+    pub matches: Vec<FilterMatch>,
+}
+
+/// A single filter that matched a network request.
+#[derive(Debug, Serialize)]
+pub struct FilterMatch {
+    /// The kind of filter that matched.
+    pub bucket: FilterBucket,
+    /// Whether the filter is an exception (`@@`) rule.
+    pub exception: bool,
+    /// The original filter rule and its [`crate::sourcemap::SourceLocation`].
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule info will be limited.
+    pub filter: FilterRuleDebugInfo,
+    /// Whether the filter was applied, or why it wasn't.
+    pub outcome: MatchOutcome,
+}
+
+impl FilterMatch {
+    fn new(bucket: FilterBucket, filter: CheckResult, outcome: MatchOutcome) -> Self {
+        Self {
+            bucket,
+            exception: filter.filter_mask.is_exception(),
+            filter: filter.debug_data.unwrap_or_default(),
+            outcome,
+        }
+    }
+}
+
+/// The groups of filters considered by [`Blocker::explain`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum FilterBucket {
+    /// Blocking filters with the `important` option.
+    Important,
+    /// Blocking filters with a `tag` option, which only apply while their tag is enabled.
+    Tagged,
+    /// All other blocking filters.
+    Regular,
+    /// Exceptions to blocking filters.
+    Exception,
+    /// Filters and exceptions with a `redirect` or `redirect-rule` option.
+    Redirect,
+    /// Filters and exceptions with a `removeparam` option.
+    Removeparam,
+    /// Filters and exceptions with a `csp` option.
+    Csp,
+}
+
+/// Describes the effect a matching filter had on the result for a network request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum MatchOutcome {
+    /// The filter was applied.
+    Applied,
+    /// Another matching filter from the same bucket was applied instead, with the same or a
+    /// higher priority effect.
+    Superseded,
+    /// A matching `important` filter blocked the request, so the filter had no effect.
+    OverriddenByImportant,
+    /// The filter was disabled by a matching exception.
+    Excepted,
+    /// The filter is generic, and the request came from a page with a `$genericblock` exception.
+    GenericBlock,
+    /// The filter matched, but had nothing to apply to, e.g. an exception without a matching
+    /// filter, a `removeparam` filter without a matching parameter, or a `csp` filter on a
+    /// request that isn't for a document.
+    NoEffect,
+    /// The filter was chosen to redirect the request, but its resource isn't available, so the
+    /// request isn't redirected.
+    RedirectResourceMissing {
+        /// Name of the missing resource.
+        resource: String,
+    },
+}
+
+/// A rewrite of a response body from a `replace` filter, e.g.
+/// `||example.com/player.js$replace=/autoplay:!0/autoplay:!1/g`.
+#[derive(Debug)]
+pub struct BodyReplacement {
+    /// Pattern to search for in the response body.
+    pub regex: regex::bytes::Regex,
+    /// Replacement for each match, in the syntax of [`regex::bytes::Regex::replace`]. References
+    /// to capture groups from the original filter (`$1`, `$<name>`, `$&`) have been translated
+    /// accordingly.
+    pub replacement: String,
+    /// If `true`, every match should be replaced. Otherwise, only the first one.
+    pub global: bool,
+    /// The `replace` filter this came from.
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub filter: FilterRuleDebugInfo,
+}
+
+impl BodyReplacement {
+    /// Applies this replacement to a response body.
+    pub fn apply<'b>(&self, body: &'b [u8]) -> Cow<'b, [u8]> {
+        let limit = if self.global { 0 } else { 1 };
+        self.regex
+            .replacen(body, limit, self.replacement.as_bytes())
+    }
+}
+
+/// The client that sent a DNS query, which is checked against `$client` options.
+#[derive(Debug, Clone, Default)]
+pub struct DnsClient {
+    /// The name the client is known by to the DNS resolver, e.g. `Frank's laptop`.
+    pub name: Option<String>,
+    /// The address the query was sent from.
+    pub ip_address: Option<IpAddr>,
+}
+
+/// Describes how a DNS query should be answered, as returned by [`Blocker::check_hostname`].
+#[derive(Debug, Serialize, Default)]
+pub struct DnsResult {
+    /// Whether the query should be blocked, rewritten, or answered normally.
+    pub action: DnsAction,
+    /// Represents any matched blocking rule, as in [`BlockerResult::filter`].
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub filter: Option<FilterRuleDebugInfo>,
+    /// Represents any matched exception rule, as in [`BlockerResult::exception`].
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub exception: Option<FilterRuleDebugInfo>,
+    /// Whether `filter` has the `important` option, in which case exceptions don't apply.
+    pub important: bool,
+    /// `dnsrewrite` filters that produced the rewrites of [`DnsAction::Rewrite`].
+    ///
+    /// If debugging was _not_ enabled (see [`crate::FilterSet::new`]), rule
+    /// info will be limited.
+    pub rewrite_filters: Vec<FilterRuleDebugInfo>,
+}
+
+/// How a DNS query should be answered.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub enum DnsAction {
+    /// Answer the query normally, since no blocking filter matched, or it was disabled by an
+    /// exception.
+    #[default]
+    Allow,
+    /// Block the query, e.g. by answering with `0.0.0.0` or `NXDOMAIN`, as configured in the DNS
+    /// resolver.
+    Block,
+    /// Answer the query with each of the given rewrites, sorted and without duplicates.
+    Rewrite(Vec<DnsRewrite>),
+}
+
+/// Translates a replacement from the syntax of JavaScript's `String.prototype.replace` into the
+/// syntax of [`regex::bytes::Regex::replace`]. Any `$` that doesn't form a valid reference to a
+/// group in `regex` is kept literally, as in JavaScript. `` $` `` and `$'` have no equivalent, so
+/// they're kept literally too.
+fn translate_replacement(replacement: &str, regex: &regex::bytes::Regex) -> String {
+    let is_group = |n: usize| n >= 1 && n < regex.captures_len();
+    let mut result = String::with_capacity(replacement.len());
+    let mut rest = replacement;
+    while let Some(i) = find_char(b'$', rest.as_bytes()) {
+        result.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        let digit = |i: usize| {
+            rest.as_bytes()
+                .get(i)
+                .filter(|b| b.is_ascii_digit())
+                .map(|b| usize::from(b - b'0'))
+        };
+        let name = rest
+            .strip_prefix('<')
+            .and_then(|r| r.split_once('>'))
+            .map(|(name, _)| name)
+            .filter(|name| regex.capture_names().any(|n| n == Some(*name)));
+
+        if rest.starts_with('$') {
+            result.push_str("$$");
+            rest = &rest[1..];
+        } else if rest.starts_with('&') {
+            result.push_str("${0}");
+            rest = &rest[1..];
+        } else if let Some(name) = name {
+            result.push_str("${");
+            result.push_str(name);
+            result.push('}');
+            rest = &rest[name.len() + 2..];
+        } else if let (Some(d1), Some(d2)) = (digit(0), digit(1))
+            && is_group(d1 * 10 + d2)
+        {
+            result.push_str(&format!("${{{}}}", d1 * 10 + d2));
+            rest = &rest[2..];
+        } else if let Some(d1) = digit(0)
+            && is_group(d1)
+        {
+            result.push_str(&format!("${{{d1}}}"));
+            rest = &rest[1..];
+        } else {
+            result.push_str("$$");
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Splits the value of a `redirect` option into the name of its resource and its priority, e.g.
+/// `noopjs:10`. The priority is 0 if it's missing or invalid.
+fn parse_redirect_priority(redirect: &str) -> (&str, i32) {
+    if let Some(idx) = find_char_reverse(b':', redirect.as_bytes())
+        && let Ok(priority) = redirect[idx + 1..].parse::<i32>()
+    {
+        (&redirect[..idx], priority)
+    } else {
+        (redirect, 0)
+    }
+}
+
+/// Returns the index of the highest priority filter from `redirect_filters` that isn't disabled
+/// by a redirect exception, along with the name of its resource.
+fn select_redirect(redirect_filters: &[CheckResult]) -> Option<(usize, &str)> {
+    let exceptions: Vec<&String> = redirect_filters
+        .iter()
+        .filter(|f| f.filter_mask.is_exception())
+        .filter_map(|f| f.modifier_option.as_ref())
+        .collect();
+    let mut selected: Option<(usize, &str, i32)> = None;
+    for (i, redirect_filter) in redirect_filters.iter().enumerate() {
+        if !redirect_filter.filter_mask.is_exception()
+            && let Some(redirect) = redirect_filter.modifier_option.as_ref()
+            && !exceptions.contains(&redirect)
+        {
+            let (resource, priority) = parse_redirect_priority(redirect);
+            if selected.is_none_or(|(_, _, p)| priority > p) {
+                selected = Some((i, resource, priority));
+            }
+        }
+    }
+    selected.map(|(i, resource, _)| (i, resource))
+}
+
 // only check for tags in tagged and exception rule buckets,
 // pass empty set for the rest
 fn get_no_tags() -> &'static HashSet<String> {
@@ -99,6 +374,47 @@ impl Blocker {
         self.check_parameterised(request, resources, false, false)
     }
 
+    /// Like [`Blocker::check`], but also checks `request` with its hostname replaced by each of
+    /// `cname_aliases` (see [`Request::with_hostname_alias`]), in order to block trackers hidden
+    /// behind first-party hostnames through DNS CNAME records.
+    ///
+    /// Aliases are only checked if no blocking filter matched the original request. An aliased
+    /// request is never blocked if it matches a `$cname` exception, e.g.
+    /// `@@||cdn.example.net^$cname`. Otherwise, the first aliased request that should be blocked
+    /// determines the result.
+    pub fn check_cname_aliases(
+        &self,
+        request: &Request,
+        cname_aliases: impl IntoIterator<Item = impl AsRef<str>>,
+        resources: &ResourceStorage,
+    ) -> BlockerResult {
+        let result = self.check(request, resources);
+        if result.filter.is_some() || !request.is_supported {
+            return result;
+        }
+
+        for alias in cname_aliases {
+            let Ok(aliased_request) = request.with_hostname_alias(alias.as_ref()) else {
+                continue;
+            };
+            if self.check_cname_exception(&aliased_request) {
+                continue;
+            }
+            let aliased_result = self.check(&aliased_request, resources);
+            if aliased_result.should_block() {
+                // URL rewrites only apply to the original URL.
+                return BlockerResult {
+                    rewritten_url: result.rewritten_url,
+                    removeparam_filters: result.removeparam_filters,
+                    excepted_removeparam_filters: result.excepted_removeparam_filters,
+                    ..aliased_result
+                };
+            }
+        }
+
+        result
+    }
+
     pub(crate) fn get_list(&self, id: NetworkFilterListId) -> NetworkFilterList<'_> {
         NetworkFilterList {
             list: self
@@ -115,6 +431,10 @@ impl Blocker {
         self.get_list(NetworkFilterListId::Csp)
     }
 
+    pub(crate) fn permissions(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Permissions)
+    }
+
     pub(crate) fn exceptions(&self) -> NetworkFilterList<'_> {
         self.get_list(NetworkFilterListId::Exceptions)
     }
@@ -131,6 +451,26 @@ impl Blocker {
         self.get_list(NetworkFilterListId::RemoveParam)
     }
 
+    pub(crate) fn replace(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Replace)
+    }
+
+    pub(crate) fn header(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Header)
+    }
+
+    pub(crate) fn popup(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Popup)
+    }
+
+    pub(crate) fn popunder(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Popunder)
+    }
+
+    pub(crate) fn urltransform(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::UrlTransform)
+    }
+
     pub(crate) fn filters(&self) -> NetworkFilterList<'_> {
         self.get_list(NetworkFilterListId::Filters)
     }
@@ -139,6 +479,30 @@ impl Blocker {
         self.get_list(NetworkFilterListId::GenericHide)
     }
 
+    pub(crate) fn elem_hide(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::ElemHide)
+    }
+
+    pub(crate) fn specific_hide(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::SpecificHide)
+    }
+
+    pub(crate) fn generic_block(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::GenericBlock)
+    }
+
+    pub(crate) fn cname(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Cname)
+    }
+
+    pub(crate) fn dns(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::Dns)
+    }
+
+    pub(crate) fn dns_rewrite(&self) -> NetworkFilterList<'_> {
+        self.get_list(NetworkFilterListId::DnsRewrite)
+    }
+
     pub(crate) fn tagged_filters_all(&self) -> NetworkFilterList<'_> {
         self.get_list(NetworkFilterListId::TaggedFiltersAll)
     }
@@ -165,6 +529,55 @@ impl Blocker {
             .is_some()
     }
 
+    /// Returns `true` if an `$elemhide` exception disables all cosmetic filtering on the page.
+    pub fn check_elem_hide(&self, hostname_request: &Request) -> bool {
+        let mut regex_manager = self.borrow_regex_manager();
+        self.elem_hide()
+            .check(hostname_request, &HashSet::new(), &mut regex_manager)
+            .is_some()
+    }
+
+    /// Returns `true` if a `$specifichide` exception disables hostname-specific cosmetic filtering
+    /// on the page.
+    pub fn check_specific_hide(&self, hostname_request: &Request) -> bool {
+        let mut regex_manager = self.borrow_regex_manager();
+        self.specific_hide()
+            .check(hostname_request, &HashSet::new(), &mut regex_manager)
+            .is_some()
+    }
+
+    /// Returns `true` if a `$genericblock` exception disables generic blocking filters for
+    /// requests from the page that sent `request`. Only the page's hostname is known at this
+    /// point, so these exceptions are matched against `https://<source hostname>/`.
+    fn check_generic_block(&self, request: &Request, regex_manager: &mut RegexManager) -> bool {
+        let generic_block = self.generic_block();
+        if generic_block.is_empty() || request.source_hostname.is_empty() {
+            return false;
+        }
+        let hostname = request.source_hostname.as_str();
+        // Only the source hostname is matched, not the page's path, so exceptions with a path like
+        // `@@||example.com/page$genericblock` never apply.
+        let page_request = Request::preparsed(
+            &format!("https://{hostname}/"),
+            hostname,
+            hostname,
+            "document",
+            false,
+            "get",
+        );
+        generic_block
+            .check(&page_request, &HashSet::new(), regex_manager)
+            .is_some()
+    }
+
+    /// Returns `true` if a `$cname` exception prevents `aliased_request` from being blocked.
+    fn check_cname_exception(&self, aliased_request: &Request) -> bool {
+        let mut regex_manager = self.borrow_regex_manager();
+        self.cname()
+            .check(aliased_request, &HashSet::new(), &mut regex_manager)
+            .is_some()
+    }
+
     #[cfg(test)]
     pub(crate) fn check_exceptions(&self, request: &Request) -> bool {
         let mut regex_manager = self.borrow_regex_manager();
@@ -191,19 +604,29 @@ impl Blocker {
         // 3. normal filters - if no match by then
         // 4. exceptions - if any non-important match of forced
 
+        // Generic filters don't apply to requests from pages with a `$genericblock` exception.
+        let generic_block = self.check_generic_block(request, &mut regex_manager);
+        let check_blocking = |list: NetworkFilterList<'_>,
+                              active_tags: &HashSet<String>,
+                              regex_manager: &mut RegexManager| {
+            if generic_block {
+                list.check_specific(request, active_tags, regex_manager)
+            } else {
+                list.check(request, active_tags, regex_manager)
+            }
+        };
+
         // Always check important filters
-        let important_filter = self
-            .importants()
-            .check(request, get_no_tags(), &mut regex_manager);
+        let important_filter = check_blocking(self.importants(), get_no_tags(), &mut regex_manager);
 
         // only check the rest of the rules if not previously matched
         let filter = if important_filter.is_none() && !matched_rule {
-            self.tagged_filters_all()
-                .check(request, &self.tags_enabled, &mut regex_manager)
-                .or_else(|| {
-                    self.filters()
-                        .check(request, get_no_tags(), &mut regex_manager)
-                })
+            check_blocking(
+                self.tagged_filters_all(),
+                &self.tags_enabled,
+                &mut regex_manager,
+            )
+            .or_else(|| check_blocking(self.filters(), get_no_tags(), &mut regex_manager))
         } else {
             important_filter
         };
@@ -229,50 +652,12 @@ impl Blocker {
         // Extract the highest priority redirect directive.
         // 1. Exceptions - can bail immediately if found
         // 2. Find highest priority non-exception redirect
-        let redirect_resource = {
-            let mut exceptions = vec![];
-            for redirect_filter in redirect_filters.iter() {
-                if redirect_filter.filter_mask.is_exception()
-                    && let Some(redirect) = redirect_filter.modifier_option.as_ref()
-                {
-                    exceptions.push(redirect);
-                }
-            }
-            let mut resource_and_priority = None;
-            for redirect_filter in redirect_filters.iter() {
-                if !redirect_filter.filter_mask.is_exception()
-                    && let Some(redirect) = redirect_filter.modifier_option.as_ref()
-                    && !exceptions.contains(&redirect)
-                {
-                    // parse redirect + priority
-                    let (resource, priority) =
-                        if let Some(idx) = find_char_reverse(b':', redirect.as_bytes()) {
-                            let priority_str = &redirect[idx + 1..];
-                            let resource = &redirect[..idx];
-                            if let Ok(priority) = priority_str.parse::<i32>() {
-                                (resource, priority)
-                            } else {
-                                (&redirect[..], 0)
-                            }
-                        } else {
-                            (&redirect[..], 0)
-                        };
-                    if let Some((_, p1)) = resource_and_priority {
-                        if priority > p1 {
-                            resource_and_priority = Some((resource, priority));
-                        }
-                    } else {
-                        resource_and_priority = Some((resource, priority));
-                    }
-                }
-            }
-            resource_and_priority.map(|(r, _)| r)
-        };
+        let redirect_resource = select_redirect(&redirect_filters).map(|(_, resource)| resource);
 
         let redirect: Option<String> = redirect_resource.and_then(|resource_name| {
             resources.get_redirect_resource(resource_name).or({
                 // It's acceptable to pass no redirection if no matching resource is loaded.
-                // TODO - it may be useful to return a status flag to indicate that this occurred.
+                // `Blocker::explain` reports when this occurs.
                 #[cfg(test)]
                 eprintln!("Matched rule with redirect option but did not find corresponding resource to send");
                 None
@@ -285,10 +670,23 @@ impl Blocker {
                 .map(|f| f.filter_mask.is_important())
                 .unwrap_or_else(|| false);
 
+        let removeparam_result = if important {
+            RemoveparamResult::default()
+        } else {
+            Self::apply_removeparam(&self.removeparam(), request, regex_manager.deref_mut())
+        };
+
+        // `urltransform` rewrites are applied on top of any removed parameters.
         let rewritten_url = if important {
             None
         } else {
-            Self::apply_removeparam(&self.removeparam(), request, regex_manager.deref_mut())
+            Self::apply_urltransform(
+                &self.urltransform(),
+                request,
+                removeparam_result.rewritten_url.as_deref(),
+                regex_manager.deref_mut(),
+            )
+            .or(removeparam_result.rewritten_url)
         };
 
         // If something has already matched before but we don't know what, still return a match
@@ -306,14 +704,243 @@ impl Blocker {
             important,
             redirect,
             rewritten_url,
+            removeparam_filters: removeparam_result
+                .applied
+                .into_iter()
+                .map(|f| f.debug_data.unwrap_or_default())
+                .collect(),
+            excepted_removeparam_filters: removeparam_result
+                .excepted
+                .into_iter()
+                .map(|f| f.debug_data.unwrap_or_default())
+                .collect(),
+        }
+    }
+
+    /// Lists every filter that matches `request`, along with whether it was applied or why it
+    /// had no effect. This is intended for diagnosing breakage; use [`Blocker::check`] to decide
+    /// how a request should be handled.
+    ///
+    /// Blocking filters, exceptions, redirects, `removeparam` and `csp` filters are included.
+    pub fn explain(&self, request: &Request, resources: &ResourceStorage) -> RequestExplanation {
+        let result = self.check(request, resources);
+        let mut matches = vec![];
+        if !request.is_supported {
+            return RequestExplanation { result, matches };
+        }
+
+        let mut regex_manager = self.borrow_regex_manager();
+        let generic_block = self.check_generic_block(request, &mut regex_manager);
+
+        // Blocking filters, in the order they're checked by `check_parameterised`. The first one
+        // that isn't ignored due to `$genericblock` determines the result.
+        let mut blocking = vec![];
+        for (bucket, list, active_tags) in [
+            (FilterBucket::Important, self.importants(), get_no_tags()),
+            (
+                FilterBucket::Tagged,
+                self.tagged_filters_all(),
+                &self.tags_enabled,
+            ),
+            (FilterBucket::Regular, self.filters(), get_no_tags()),
+        ] {
+            for filter in list.check_all(request, active_tags, &mut regex_manager) {
+                blocking.push((bucket, filter));
+            }
+        }
+        let ignored = |f: &CheckResult| generic_block && f.is_generic;
+        let winner = blocking.iter().position(|(_, f)| !ignored(f));
+        let important = winner.is_some_and(|i| blocking[i].1.filter_mask.is_important());
+        let exceptions =
+            self.exceptions()
+                .check_all(request, &self.tags_enabled, &mut regex_manager);
+        let excepted = winner.is_some() && !important && !exceptions.is_empty();
+
+        for (i, (bucket, filter)) in blocking.into_iter().enumerate() {
+            let outcome = if ignored(&filter) {
+                MatchOutcome::GenericBlock
+            } else if winner == Some(i) && !excepted {
+                MatchOutcome::Applied
+            } else if excepted {
+                MatchOutcome::Excepted
+            } else if important && !filter.filter_mask.is_important() {
+                MatchOutcome::OverriddenByImportant
+            } else {
+                MatchOutcome::Superseded
+            };
+            matches.push(FilterMatch::new(bucket, filter, outcome));
+        }
+        for (i, exception) in exceptions.into_iter().enumerate() {
+            let outcome = match winner {
+                None => MatchOutcome::NoEffect,
+                Some(_) if important => MatchOutcome::OverriddenByImportant,
+                Some(_) if i == 0 => MatchOutcome::Applied,
+                Some(_) => MatchOutcome::Superseded,
+            };
+            matches.push(FilterMatch::new(
+                FilterBucket::Exception,
+                exception,
+                outcome,
+            ));
+        }
+
+        // Redirect exceptions disable redirect filters with the same value.
+        let redirect_filters =
+            self.redirects()
+                .check_all(request, get_no_tags(), &mut regex_manager);
+        let has_redirect = |redirect: &String, exception: bool| {
+            redirect_filters.iter().any(|f| {
+                f.filter_mask.is_exception() == exception
+                    && f.modifier_option.as_ref() == Some(redirect)
+            })
+        };
+        let selected = select_redirect(&redirect_filters);
+        let redirect_outcomes: Vec<_> = redirect_filters
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let redirect = f.modifier_option.as_ref();
+                if f.filter_mask.is_exception() {
+                    if redirect.is_some_and(|r| has_redirect(r, false)) {
+                        MatchOutcome::Applied
+                    } else {
+                        MatchOutcome::NoEffect
+                    }
+                } else if redirect.is_some_and(|r| has_redirect(r, true)) {
+                    MatchOutcome::Excepted
+                } else {
+                    match selected {
+                        Some((j, resource)) if i == j => {
+                            if resources.get_redirect_resource(resource).is_some() {
+                                MatchOutcome::Applied
+                            } else {
+                                MatchOutcome::RedirectResourceMissing {
+                                    resource: resource.to_string(),
+                                }
+                            }
+                        }
+                        Some(_) => MatchOutcome::Superseded,
+                        None => MatchOutcome::NoEffect,
+                    }
+                }
+            })
+            .collect();
+        for (filter, outcome) in redirect_filters.into_iter().zip(redirect_outcomes) {
+            matches.push(FilterMatch::new(FilterBucket::Redirect, filter, outcome));
         }
+
+        if important {
+            for filter in self
+                .removeparam()
+                .check_all(request, get_no_tags(), &mut regex_manager)
+            {
+                let outcome = if filter.filter_mask.is_exception() {
+                    MatchOutcome::NoEffect
+                } else {
+                    MatchOutcome::OverriddenByImportant
+                };
+                matches.push(FilterMatch::new(FilterBucket::Removeparam, filter, outcome));
+            }
+        } else {
+            let removeparam =
+                Self::apply_removeparam(&self.removeparam(), request, &mut regex_manager);
+            let exception_outcomes: Vec<_> = removeparam
+                .exceptions
+                .iter()
+                .map(|exception| {
+                    let disables_any = removeparam.excepted.iter().any(|f| {
+                        exception.modifier_option.is_none()
+                            || f.modifier_option == exception.modifier_option
+                    });
+                    if disables_any {
+                        MatchOutcome::Applied
+                    } else {
+                        MatchOutcome::NoEffect
+                    }
+                })
+                .collect();
+            let outcomes = [
+                (removeparam.applied, MatchOutcome::Applied),
+                (removeparam.excepted, MatchOutcome::Excepted),
+                (removeparam.unused, MatchOutcome::NoEffect),
+            ];
+            for (filters, outcome) in outcomes {
+                for filter in filters {
+                    matches.push(FilterMatch::new(
+                        FilterBucket::Removeparam,
+                        filter,
+                        outcome.clone(),
+                    ));
+                }
+            }
+            for (exception, outcome) in removeparam.exceptions.into_iter().zip(exception_outcomes) {
+                matches.push(FilterMatch::new(
+                    FilterBucket::Removeparam,
+                    exception,
+                    outcome,
+                ));
+            }
+        }
+
+        // Mirrors `get_csp_directives`: `csp` filters only apply to documents, and an exception
+        // without a value disables all of them.
+        let csp_filters = self
+            .csp()
+            .check_all(request, &self.tags_enabled, &mut regex_manager);
+        let is_document = matches!(
+            request.request_type,
+            crate::request::RequestType::Document | crate::request::RequestType::Subdocument
+        );
+        let csp_outcomes: Vec<_> = {
+            let except_all = csp_filters
+                .iter()
+                .any(|f| f.filter_mask.is_exception() && f.modifier_option.is_none());
+            let disabled: HashSet<&String> = csp_filters
+                .iter()
+                .filter(|f| f.filter_mask.is_exception())
+                .filter_map(|f| f.modifier_option.as_ref())
+                .collect();
+            let mut enabled = HashSet::new();
+            csp_filters
+                .iter()
+                .map(|f| match f.modifier_option.as_ref() {
+                    _ if !is_document => MatchOutcome::NoEffect,
+                    None if f.filter_mask.is_exception() => MatchOutcome::Applied,
+                    Some(_) if f.filter_mask.is_exception() && except_all => {
+                        MatchOutcome::Superseded
+                    }
+                    Some(directive) if f.filter_mask.is_exception() => {
+                        let disables_any = csp_filters.iter().any(|other| {
+                            !other.filter_mask.is_exception()
+                                && other.modifier_option.as_ref() == Some(directive)
+                        });
+                        if disables_any {
+                            MatchOutcome::Applied
+                        } else {
+                            MatchOutcome::NoEffect
+                        }
+                    }
+                    None => MatchOutcome::NoEffect,
+                    Some(directive) if except_all || disabled.contains(directive) => {
+                        MatchOutcome::Excepted
+                    }
+                    Some(directive) if enabled.insert(directive) => MatchOutcome::Applied,
+                    Some(_) => MatchOutcome::Superseded,
+                })
+                .collect()
+        };
+        for (filter, outcome) in csp_filters.into_iter().zip(csp_outcomes) {
+            matches.push(FilterMatch::new(FilterBucket::Csp, filter, outcome));
+        }
+
+        RequestExplanation { result, matches }
     }
 
     fn apply_removeparam(
         removeparam_filters: &NetworkFilterList,
         request: &Request,
         regex_manager: &mut RegexManager,
-    ) -> Option<String> {
+    ) -> RemoveparamResult {
         /// Represents an `&`-separated argument from a URL query parameter string
         enum QParam<'a> {
             /// Just a key, e.g. `...&key&...`
@@ -331,6 +958,31 @@ impl Blocker {
             }
         }
 
+        fn param_matches(
+            removeparam: &RemoveparamValue,
+            param: &QParam,
+            regex_manager: &mut RegexManager,
+        ) -> bool {
+            let key = match param {
+                // Empty segments, e.g. from `&&`, are never considered parameters.
+                QParam::KeyOnly("") => return false,
+                QParam::KeyOnly(k) | QParam::KeyValue(k, _) => *k,
+            };
+            let matched = match &removeparam.matcher {
+                RemoveparamMatcher::All => true,
+                // Parameters without a value are only removed by name if the rule is negated.
+                RemoveparamMatcher::Name(name) if !removeparam.negated => {
+                    matches!(param, QParam::KeyValue(_, v) if !v.is_empty()) && key == *name
+                }
+                RemoveparamMatcher::Name(name) => key == *name,
+                RemoveparamMatcher::Regex {
+                    pattern,
+                    ignore_case,
+                } => regex_manager.matches_option_regex(pattern, *ignore_case, &param.to_string()),
+            };
+            matched != removeparam.negated
+        }
+
         let url = &request.original_url;
         // Only check for removeparam if there's a query string in the request URL
         if let Some(i) = find_char(b'?', url.as_bytes()) {
@@ -355,21 +1007,56 @@ impl Blocker {
                 .map(|param| (param, true))
                 .collect();
 
-            let filters = removeparam_filters.check_all(request, get_no_tags(), regex_manager);
+            let (exceptions, filters): (Vec<_>, Vec<_>) = removeparam_filters
+                .check_all(request, get_no_tags(), regex_manager)
+                .into_iter()
+                .partition(|f| f.filter_mask.is_exception());
+
+            // Exceptions disable any `removeparam` filter with the same value. An exception
+            // without a value disables all `removeparam` filters.
+            let mut excepted_values: HashSet<&str> = HashSet::new();
+            let mut except_all = false;
+            for exception in exceptions.iter() {
+                match &exception.modifier_option {
+                    Some(value) => {
+                        excepted_values.insert(value);
+                    }
+                    None => except_all = true,
+                }
+            }
+
+            let mut result = RemoveparamResult::default();
             let mut rewrite = false;
-            for removeparam_filter in filters {
-                if let Some(removeparam) = &removeparam_filter.modifier_option {
-                    params.iter_mut().for_each(|(param, include)| {
-                        if let QParam::KeyValue(k, v) = param
-                            && !v.is_empty()
-                            && k == removeparam
-                        {
-                            *include = false;
-                            rewrite = true;
-                        }
-                    });
+            for filter in filters {
+                if except_all
+                    || filter
+                        .modifier_option
+                        .as_deref()
+                        .is_some_and(|value| excepted_values.contains(value))
+                {
+                    result.excepted.push(filter);
+                    continue;
+                }
+                let Ok(removeparam) = RemoveparamValue::parse(filter.modifier_option.as_deref())
+                else {
+                    result.unused.push(filter);
+                    continue;
+                };
+                let mut applied = false;
+                params.iter_mut().for_each(|(param, include)| {
+                    if param_matches(&removeparam, param, regex_manager) {
+                        *include = false;
+                        applied = true;
+                    }
+                });
+                if applied {
+                    rewrite = true;
+                    result.applied.push(filter);
+                } else {
+                    result.unused.push(filter);
                 }
             }
+            result.exceptions = exceptions;
             if rewrite {
                 let p = itertools::join(
                     params
@@ -383,17 +1070,83 @@ impl Blocker {
                 } else {
                     format!("?{p}")
                 };
-                Some(format!(
+                result.rewritten_url = Some(format!(
                     "{}{}{}",
                     &url[0..i],
                     new_param_str,
                     &url[hash_index..]
-                ))
-            } else {
-                None
+                ));
             }
+            result
         } else {
-            None
+            RemoveparamResult::default()
+        }
+    }
+
+    /// Applies the `urltransform` filters matching `request` to `url`, or to the request's URL if
+    /// it hasn't already been rewritten. Returns the new URL if it was modified.
+    ///
+    /// An exception with a `urltransform` option disables any `urltransform` filter with the same
+    /// value, and an exception with an empty `urltransform` option disables all of them. Remaining
+    /// filters are applied in the order they appear in the filter lists; any rewrite that doesn't
+    /// produce a valid URL is skipped.
+    fn apply_urltransform(
+        urltransform_filters: &NetworkFilterList,
+        request: &Request,
+        url: Option<&str>,
+        regex_manager: &mut RegexManager,
+    ) -> Option<String> {
+        let (exceptions, filters): (Vec<_>, Vec<_>) = urltransform_filters
+            .check_all(request, get_no_tags(), regex_manager)
+            .into_iter()
+            .partition(|f| f.filter_mask.is_exception());
+        if filters.is_empty() {
+            return None;
+        }
+
+        let mut excepted_values: HashSet<String> = HashSet::new();
+        for exception in exceptions {
+            // An exception without a value disables all `urltransform` filters.
+            excepted_values.insert(exception.modifier_option?);
+        }
+
+        // Values are added to `excepted_values` as they're seen, so that duplicates (e.g. from
+        // multiple lists) are only applied once.
+        let mut filters: Vec<_> = filters
+            .into_iter()
+            .filter(|f| {
+                f.modifier_option
+                    .as_ref()
+                    .is_some_and(|value| excepted_values.insert(value.clone()))
+            })
+            .collect();
+        filters.sort_by(|a, b| {
+            (a.list_position, &a.modifier_option).cmp(&(b.list_position, &b.modifier_option))
+        });
+
+        let original = url.unwrap_or(&request.original_url);
+        let mut url = Cow::Borrowed(original);
+        for value in filters.iter().filter_map(|f| f.modifier_option.as_deref()) {
+            let Ok(transform) = ReplaceValue::parse(value) else {
+                continue;
+            };
+            let Some(regex) = regex_manager.get_replace_regex(value, &transform) else {
+                continue;
+            };
+            let replacement = translate_replacement(&transform.replacement, regex);
+            let limit = if transform.global { 0 } else { 1 };
+            let rewritten = regex.replacen(url.as_bytes(), limit, replacement.as_bytes());
+            if let Ok(rewritten) = std::str::from_utf8(&rewritten)
+                && rewritten != url
+                && crate::url_parser::parse_url(rewritten).is_some()
+            {
+                url = Cow::Owned(rewritten.to_string());
+            }
+        }
+
+        match url {
+            Cow::Owned(url) if url != original => Some(url),
+            _ => None,
         }
     }
 
@@ -448,6 +1201,355 @@ impl Blocker {
         Some(merged)
     }
 
+    /// Returns the merged `Permissions-Policy` header value from any `permissions` filters that
+    /// apply to `request`. Only applies to document and subdocument requests.
+    ///
+    /// An exception with a `permissions` option disables each of the directives it contains, and
+    /// an exception with an empty `permissions` option disables all of them. Remaining directives
+    /// are deduplicated, sorted, and joined by `, `.
+    pub fn get_permissions_policy(&self, request: &Request) -> Option<String> {
+        use crate::request::RequestType;
+
+        if request.request_type != RequestType::Document
+            && request.request_type != RequestType::Subdocument
+        {
+            return None;
+        }
+
+        let mut regex_manager = self.borrow_regex_manager();
+        let filters = self
+            .permissions()
+            .check_all(request, &self.tags_enabled, &mut regex_manager);
+
+        let mut disabled_directives: HashSet<String> = HashSet::new();
+        let mut enabled_directives: BTreeSet<String> = BTreeSet::new();
+
+        for filter in filters {
+            let directives = filter
+                .modifier_option
+                .as_deref()
+                .map(parse_permissions_directives);
+            match directives {
+                Some(Ok(directives)) if filter.filter_mask.is_exception() => {
+                    disabled_directives.extend(directives)
+                }
+                Some(Ok(directives)) => enabled_directives.extend(directives),
+                // Exception filters with empty `permissions` options will disable all
+                // `Permissions-Policy` injections for matching pages.
+                None if filter.filter_mask.is_exception() => return None,
+                _ => (),
+            }
+        }
+
+        enabled_directives.retain(|directive| !disabled_directives.contains(directive));
+        if enabled_directives.is_empty() {
+            return None;
+        }
+        Some(
+            enabled_directives
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    /// Decide if the response to a network request should be blocked, based on its headers. Only
+    /// `header` filters are considered here, e.g. `||example.com^$header=via:1.1 google`; they
+    /// are not checked by [`Blocker::check`], since headers aren't available until the response
+    /// has been received.
+    ///
+    /// Header names are matched case-insensitively. `header` filters can only be disabled by
+    /// exceptions that are also `header` filters matching the response, e.g.
+    /// `@@||example.com/video/$header=via`.
+    pub fn check_response_headers(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> BlockerResult {
+        let mut regex_manager = self.borrow_regex_manager();
+
+        let header_matches = |value: &str, regex_manager: &mut RegexManager| {
+            let Ok(header) = HeaderValue::parse(value) else {
+                return false;
+            };
+            headers
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case(header.name))
+                .any(|(_, value)| {
+                    let matched = match &header.matcher {
+                        HeaderMatcher::Present => true,
+                        HeaderMatcher::Literal(literal) => value.trim() == literal,
+                        HeaderMatcher::Regex {
+                            pattern,
+                            ignore_case,
+                        } => regex_manager.matches_option_regex(pattern, *ignore_case, value),
+                    };
+                    matched != header.negated
+                })
+        };
+
+        let matches = self
+            .header()
+            .check_all(request, &self.tags_enabled, &mut regex_manager)
+            .into_iter()
+            .filter(|f| {
+                f.modifier_option
+                    .as_deref()
+                    .is_some_and(|value| header_matches(value, &mut regex_manager))
+            });
+
+        Self::resolve_standalone_matches(matches)
+    }
+
+    /// Decide if a popup window opened by `opener_url` and navigating to `target_url` should be
+    /// blocked. Only `popup` and `popunder` filters are considered here; they are not checked by
+    /// [`Blocker::check`].
+    ///
+    /// `popup` filters are matched against `target_url`, as a document request from
+    /// `opener_url`. `popunder` filters are matched the other way around, against `opener_url`
+    /// as a document request from `target_url`. Each can only be disabled by exceptions of the
+    /// same kind, e.g. `@@||example.com^$popup`.
+    pub fn check_popup(&self, opener_url: &str, target_url: &str) -> BlockerResult {
+        let mut regex_manager = self.borrow_regex_manager();
+
+        let checks = [
+            (self.popup(), target_url, opener_url),
+            (self.popunder(), opener_url, target_url),
+        ];
+
+        let mut excepted = None;
+        for (list, url, source_url) in checks {
+            let Ok(request) = Request::new(url, source_url, "document", "") else {
+                continue;
+            };
+            if !request.is_supported {
+                continue;
+            }
+            let result = Self::resolve_standalone_matches(list.check_all(
+                &request,
+                &self.tags_enabled,
+                &mut regex_manager,
+            ));
+            if result.filter.is_some() && result.exception.is_none() {
+                return result;
+            }
+            if result.filter.is_some() && excepted.is_none() {
+                excepted = Some(result);
+            }
+        }
+
+        excepted.unwrap_or_default()
+    }
+
+    /// Resolves matches from a list of filters that aren't checked by [`Blocker::check`] into a
+    /// result, which can only be disabled by exceptions from the same list. An `important` filter
+    /// is preferred, since it can't be disabled by exceptions.
+    fn resolve_standalone_matches(matches: impl IntoIterator<Item = CheckResult>) -> BlockerResult {
+        let (exceptions, mut filters): (Vec<_>, Vec<_>) = matches
+            .into_iter()
+            .partition(|f| f.filter_mask.is_exception());
+
+        if let Some(i) = filters.iter().position(|f| f.filter_mask.is_important()) {
+            filters.swap(0, i);
+        }
+        let Some(filter) = filters.into_iter().next() else {
+            return BlockerResult::default();
+        };
+
+        let important = filter.filter_mask.is_important();
+        let exception = if important {
+            None
+        } else {
+            exceptions.into_iter().next()
+        };
+
+        BlockerResult {
+            filter: Some(filter.debug_data.unwrap_or_default()),
+            exception: exception.map(|e| e.debug_data.unwrap_or_default()),
+            important,
+            ..Default::default()
+        }
+    }
+
+    /// Decides how a DNS query for `hostname` with the record type `query_type`, e.g. `A` or
+    /// `AAAA`, should be answered, following AdGuard's DNS filtering syntax.
+    ///
+    /// Only filters that depend on nothing but the hostname are considered, like
+    /// `||example.com^` or hosts-format rules, along with the `important`, `dnstype`, `client` and
+    /// `dnsrewrite` options. `dnsrewrite` filters take precedence over blocking filters, and can
+    /// only be disabled by `dnsrewrite` exceptions: `@@||example.com^$dnsrewrite` disables all of
+    /// them, while an exception with a value only disables filters with an equivalent rewrite.
+    ///
+    /// `hostname` should be in its ASCII form, and may include the root label, e.g.
+    /// `ads.example.com.`.
+    pub fn check_hostname(
+        &self,
+        hostname: &str,
+        query_type: &str,
+        client: &DnsClient,
+    ) -> Result<DnsResult, RequestError> {
+        let hostname = hostname.strip_suffix('.').unwrap_or(hostname);
+        let request = Request::new(&format!("https://{hostname}/"), "", "other", "")?;
+        // Anything else in the URL, like a path, would have been split off the hostname.
+        if !request.hostname.eq_ignore_ascii_case(hostname) {
+            return Err(RequestError::HostnameParseError);
+        }
+
+        let mut regex_manager = self.borrow_regex_manager();
+        let accept = |filter: &FlatNetworkFilter<'_>, regex_manager: &mut RegexManager| {
+            filter.applies_to_dns_query(
+                query_type,
+                client.name.as_deref(),
+                client.ip_address,
+                regex_manager,
+            )
+        };
+
+        let (rewrite_exceptions, rewrite_filters): (Vec<_>, Vec<_>) = self
+            .dns_rewrite()
+            .check_all_accepted(&request, &self.tags_enabled, &mut regex_manager, accept)
+            .into_iter()
+            .partition(|f| f.filter_mask.is_exception());
+
+        let mut excepted_rewrites = HashSet::new();
+        let mut rewrites_disabled = false;
+        for exception in rewrite_exceptions {
+            match exception.modifier_option.as_deref().map(DnsRewrite::parse) {
+                Some(Ok(rewrite)) => {
+                    excepted_rewrites.insert(rewrite);
+                }
+                Some(Err(_)) => (),
+                None => rewrites_disabled = true,
+            }
+        }
+
+        if !rewrites_disabled {
+            let mut rewrites = vec![];
+            let mut applied = vec![];
+            for filter in rewrite_filters {
+                let Some(Ok(rewrite)) = filter.modifier_option.as_deref().map(DnsRewrite::parse)
+                else {
+                    continue;
+                };
+                if !excepted_rewrites.contains(&rewrite) {
+                    rewrites.push(rewrite);
+                    applied.push(filter.debug_data.unwrap_or_default());
+                }
+            }
+            if !rewrites.is_empty() {
+                rewrites.sort();
+                rewrites.dedup();
+                return Ok(DnsResult {
+                    action: DnsAction::Rewrite(rewrites),
+                    rewrite_filters: applied,
+                    ..Default::default()
+                });
+            }
+        }
+
+        // Filters with `dnstype` or `client` options are kept in a separate list, which is
+        // checked after the corresponding regular list.
+        let (dns_exceptions, mut dns_filters): (Vec<_>, Vec<_>) = self
+            .dns()
+            .check_all_accepted(&request, &self.tags_enabled, &mut regex_manager, accept)
+            .into_iter()
+            .partition(|f| f.filter_mask.is_exception());
+
+        let filter = self
+            .importants()
+            .check_first(&request, get_no_tags(), &mut regex_manager, accept)
+            .or_else(|| {
+                dns_filters
+                    .iter()
+                    .position(|f| f.filter_mask.is_important())
+                    .map(|i| dns_filters.swap_remove(i))
+            })
+            .or_else(|| {
+                self.filters()
+                    .check_first(&request, get_no_tags(), &mut regex_manager, accept)
+            })
+            .or_else(|| dns_filters.pop());
+        let Some(filter) = filter else {
+            return Ok(DnsResult::default());
+        };
+
+        let important = filter.filter_mask.is_important();
+        let exception = if important {
+            None
+        } else {
+            self.exceptions()
+                .check_first(&request, &self.tags_enabled, &mut regex_manager, accept)
+                .or_else(|| dns_exceptions.into_iter().next())
+        };
+
+        Ok(DnsResult {
+            action: if exception.is_none() {
+                DnsAction::Block
+            } else {
+                DnsAction::Allow
+            },
+            filter: Some(filter.debug_data.unwrap_or_default()),
+            exception: exception.map(|e| e.debug_data.unwrap_or_default()),
+            important,
+            rewrite_filters: vec![],
+        })
+    }
+
+    /// Returns the rewrites from `replace` filters that should be applied to the body of the
+    /// response to `request`, in the order they should be applied. Filters are ordered by their
+    /// location in the filter lists if it's known (see [`crate::FilterSet::new`]), and by their
+    /// option value otherwise.
+    ///
+    /// An exception with a `replace` option disables any `replace` filter with the same value,
+    /// and an exception with an empty `replace` option disables all of them.
+    pub fn get_body_replacements(&self, request: &Request) -> Vec<BodyReplacement> {
+        let mut regex_manager = self.borrow_regex_manager();
+        let (exceptions, filters): (Vec<_>, Vec<_>) = self
+            .replace()
+            .check_all(request, &self.tags_enabled, &mut regex_manager)
+            .into_iter()
+            .partition(|f| f.filter_mask.is_exception());
+
+        let mut excepted_values: HashSet<String> = HashSet::new();
+        for exception in exceptions {
+            match exception.modifier_option {
+                Some(value) => {
+                    excepted_values.insert(value);
+                }
+                None => return vec![],
+            }
+        }
+
+        // Values are added to `excepted_values` as they're seen, so that duplicates (e.g. from
+        // multiple lists) are only applied once.
+        let mut filters: Vec<_> = filters
+            .into_iter()
+            .filter(|f| {
+                f.modifier_option
+                    .as_ref()
+                    .is_some_and(|value| excepted_values.insert(value.clone()))
+            })
+            .collect();
+        filters.sort_by(|a, b| {
+            (a.list_position, &a.modifier_option).cmp(&(b.list_position, &b.modifier_option))
+        });
+
+        filters
+            .into_iter()
+            .filter_map(|f| {
+                let value = f.modifier_option.as_deref()?;
+                let replace = ReplaceValue::parse(value).ok()?;
+                let regex = regex_manager.get_replace_regex(value, &replace)?.clone();
+                Some(BodyReplacement {
+                    replacement: translate_replacement(&replace.replacement, &regex),
+                    global: replace.global,
+                    regex,
+                    filter: f.debug_data.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
     pub(crate) fn from_context(filter_data_context: FilterDataContextRef) -> Self {
         Self {
             filter_data_context,
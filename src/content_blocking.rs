@@ -1,5 +1,6 @@
 //! Transforms filter rules into content blocking syntax used on iOS and MacOS.
 
+use crate::filters::abstract_network::{split_pipe_delimited_domains, split_unescaped};
 use crate::filters::cosmetic::CosmeticFilter;
 use crate::filters::network::{NetworkFilter, NetworkFilterFeaturesMask, NetworkFilterMask};
 use crate::lists::ParsedLine;
@@ -193,12 +194,37 @@ pub enum CbRuleCreationFailure {
     NetworkRedirectUnsupported,
     /// Network rules with generichide options cannot be supported in content blocking syntax.
     NetworkGenerichideUnsupported,
+    /// Network rules with elemhide, specifichide, or genericblock options cannot be supported in
+    /// content blocking syntax.
+    NetworkPageExceptionUnsupported,
+    /// Network rules with cname options cannot be supported in content blocking syntax.
+    NetworkCnameUnsupported,
     /// Network rules with badfilter options cannot be supported in content blocking syntax.
     NetworkBadFilterUnsupported,
     /// Network rules with csp options cannot be supported in content blocking syntax.
     NetworkCspUnsupported,
     /// Network rules with removeparam options cannot be supported in content blocking syntax.
     NetworkRemoveparamUnsupported,
+    /// Network rules with replace options cannot be supported in content blocking syntax.
+    NetworkReplaceUnsupported,
+    /// Network rules with urltransform options cannot be supported in content blocking syntax.
+    NetworkUrlTransformUnsupported,
+    /// Network rules with header options cannot be supported in content blocking syntax.
+    NetworkHeaderUnsupported,
+    /// Network rules with permissions options cannot be supported in content blocking syntax.
+    NetworkPermissionsUnsupported,
+    /// Network rules with popunder options cannot be supported in content blocking syntax.
+    NetworkPopunderUnsupported,
+    /// Network rules with denyallow options cannot be supported in content blocking syntax.
+    NetworkDenyallowUnsupported,
+    /// Network rules with ipaddress options cannot be supported in content blocking syntax.
+    NetworkIpAddressUnsupported,
+    /// Network rules with dnstype, client, or dnsrewrite options only apply to DNS queries, so
+    /// they cannot be supported in content blocking syntax.
+    NetworkDnsOnlyUnsupported,
+    /// Network rules with strict1p or strict3p options cannot be supported in content blocking
+    /// syntax.
+    NetworkStrictPartyUnsupported,
     /// Content blocking syntax only supports a subset of regex features, namely:
     /// - Matching any character with “.”.
     /// - Matching ranges with the range syntax [a-b].
@@ -221,6 +247,9 @@ pub enum CbRuleCreationFailure {
     /// Cosmetic rules with scriptlet injections (i.e. `+js(...)`) cannot be represented in content
     /// blocking syntax.
     ScriptletInjectionsNotSupported,
+    /// HTML filtering rules (i.e. `##^...`) act on response bodies and headers, and cannot be
+    /// represented in content blocking syntax.
+    HtmlFilteringNotSupported,
     /// Valid content blocking rules can only include ASCII characters.
     RuleContainsNonASCII,
     /// `from` as a `domain` alias is not currently supported in content blocking syntax.
@@ -228,6 +257,9 @@ pub enum CbRuleCreationFailure {
     FromNotSupported,
     /// Content blocking rules cannot support procedural cosmetic filter operators.
     ProceduralCosmeticFiltersUnsupported,
+    /// Cosmetic rules with a `[$path=...]` modifier cannot be represented in content blocking
+    /// syntax.
+    CosmeticPathUnsupported,
 }
 
 impl TryFrom<ParsedLine<'_>> for CbRuleEquivalent {
@@ -315,6 +347,15 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
             if v.is_generic_hide() {
                 return Err(CbRuleCreationFailure::NetworkGenerichideUnsupported);
             }
+            if v.is_elem_hide() || v.is_specific_hide() || v.is_generic_block() {
+                return Err(CbRuleCreationFailure::NetworkPageExceptionUnsupported);
+            }
+            if v.is_cname() {
+                return Err(CbRuleCreationFailure::NetworkCnameUnsupported);
+            }
+            if v.is_dns_only() {
+                return Err(CbRuleCreationFailure::NetworkDnsOnlyUnsupported);
+            }
             debug_assert!(
                 !v.features_mask
                     .contains(NetworkFilterFeaturesMask::BAD_FILTER),
@@ -329,6 +370,21 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
             if v.is_removeparam() {
                 return Err(CbRuleCreationFailure::NetworkRemoveparamUnsupported);
             }
+            if v.is_replace() {
+                return Err(CbRuleCreationFailure::NetworkReplaceUnsupported);
+            }
+            if v.is_urltransform() {
+                return Err(CbRuleCreationFailure::NetworkUrlTransformUnsupported);
+            }
+            if v.is_header() {
+                return Err(CbRuleCreationFailure::NetworkHeaderUnsupported);
+            }
+            if v.is_permissions() {
+                return Err(CbRuleCreationFailure::NetworkPermissionsUnsupported);
+            }
+            if v.is_popunder() {
+                return Err(CbRuleCreationFailure::NetworkPopunderUnsupported);
+            }
 
             let load_type = if v
                 .mask
@@ -425,17 +481,36 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
                 .to_string(),
             };
 
-            if v.opt_to_domains.is_some() || v.opt_not_to_domains.is_some() {
+            if v.opt_to_domains.is_some()
+                || v.opt_not_to_domains.is_some()
+                || v.opt_to_entities.is_some()
+                || v.opt_not_to_entities.is_some()
+            {
                 return Err(CbRuleCreationFailure::FromNotSupported);
             }
+            if v.opt_denyallow_domains.is_some() {
+                return Err(CbRuleCreationFailure::NetworkDenyallowUnsupported);
+            }
+            if v.opt_ip_address.is_some() {
+                return Err(CbRuleCreationFailure::NetworkIpAddressUnsupported);
+            }
+            if v.mask.intersects(
+                NetworkFilterMask::STRICT_FIRST_PARTY | NetworkFilterMask::STRICT_THIRD_PARTY,
+            ) {
+                return Err(CbRuleCreationFailure::NetworkStrictPartyUnsupported);
+            }
 
             let (if_domain, unless_domain) = if v.opt_domains.is_some()
                 || v.opt_not_domains.is_some()
+                || v.opt_entities.is_some()
+                || v.opt_not_entities.is_some()
+                || v.opt_domain_regexes.is_some()
+                || v.opt_not_domain_regexes.is_some()
             {
                 let mut if_domain = vec![];
                 let mut unless_domain = vec![];
 
-                // Unwraps are okay here - any rules with opt_domains or opt_not_domains must have
+                // Unwraps are okay here - any rules with domain or entity restrictions must have
                 // an options section delimited by a '$' character, followed by a `domain=` option.
                 let opts = &raw_line[find_char(b'$', raw_line.as_bytes()).unwrap() + "$".len()..];
                 let domain_start_index =
@@ -445,12 +520,11 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
                         return Err(CbRuleCreationFailure::FromNotSupported);
                     };
                 let domains_start = &opts[domain_start_index + "domain=".len()..];
-                let domains = if let Some(comma) = find_char(b',', domains_start.as_bytes()) {
-                    &domains_start[..comma]
-                } else {
-                    domains_start
-                }
-                .split('|');
+                let domains = split_pipe_delimited_domains(
+                    split_unescaped(domains_start, b',')
+                        .next()
+                        .unwrap_or_default(),
+                );
 
                 let mut any_invalid = false;
 
@@ -500,7 +574,12 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
                 CbType::Block
             };
 
-            let resource_type = if v.mask.contains(NetworkFilterMask::FROM_NETWORK_TYPES) {
+            let resource_type = if v
+                .features_mask
+                .contains(NetworkFilterFeaturesMask::IS_POPUP)
+            {
+                Some(HashSet::from([CbResourceType::Popup]))
+            } else if v.mask.contains(NetworkFilterMask::FROM_NETWORK_TYPES) {
                 None
             } else {
                 let mut types = HashSet::new();
@@ -529,7 +608,7 @@ impl TryFrom<NetworkFilter<'_>> for CbRuleEquivalent {
                 push_if_flag!(FROM_WEBSOCKET);
                 push_if_flag!(FROM_XMLHTTPREQUEST, Raw);
                 push_if_flag!(FROM_FONT, Font);
-                // TODO - Popup, Document when implemented
+                // TODO - Document when implemented
 
                 if !unsupported_flags.is_empty() && types.is_empty() {
                     return Err(CbRuleCreationFailure::NoSupportedNetworkOptions(
@@ -615,6 +694,12 @@ impl TryFrom<CosmeticFilter> for CbRule {
         if v.mask.contains(CosmeticFilterMask::SCRIPT_INJECT) {
             return Err(CbRuleCreationFailure::ScriptletInjectionsNotSupported);
         }
+        if v.mask.contains(CosmeticFilterMask::HTML_FILTER) {
+            return Err(CbRuleCreationFailure::HtmlFilteringNotSupported);
+        }
+        if v.path.is_some() {
+            return Err(CbRuleCreationFailure::CosmeticPathUnsupported);
+        }
 
         if let Some(raw_line) = v.raw_line.as_deref() {
             let mut hostnames_vec = vec![];
@@ -624,11 +709,12 @@ impl TryFrom<CosmeticFilter> for CbRule {
 
             // Unwrap is okay here - cosmetic rules must have a '#' character
             let sharp_index = find_char(b'#', raw_line.as_bytes()).unwrap();
-            CosmeticFilter::locations_before_sharp(raw_line, sharp_index).for_each(
+            CosmeticFilter::locations_before_sharp(&raw_line[..sharp_index]).for_each(
                 |(location_type, location)| match location_type {
-                    LocationType::Entity | LocationType::NotEntity | LocationType::Unsupported => {
-                        any_unsupported = true
-                    }
+                    LocationType::Entity
+                    | LocationType::NotEntity
+                    | LocationType::HostnameRegex
+                    | LocationType::NotHostnameRegex => any_unsupported = true,
                     LocationType::Hostname => {
                         if let Ok(encoded) = idna::domain_to_ascii(location) {
                             hostnames_vec.push(encoded);
@@ -9,20 +9,27 @@
 //! relevant to a particular page.
 //! To build `CosmeticFilterCache`, use `CosmeticFilterCacheBuilder`.
 
-use crate::cosmetic_filter_utils::decode_script_with_permission;
-use crate::filters::cosmetic::{CosmeticFilterAction, CosmeticFilterOperator};
+use crate::cosmetic_filter_utils::{
+    SCOPED_HIDE, SCOPED_HTML_FILTER, SCOPED_HTML_FILTER_EXCEPTION, SCOPED_INJECT_SCRIPT,
+    SCOPED_PROCEDURAL_OR_ACTION, SCOPED_PROCEDURAL_OR_ACTION_EXCEPTION, SCOPED_UNHIDE,
+    SCOPED_UNINJECT_SCRIPT, decode_script_with_permission,
+};
+use crate::filters::cosmetic::{CosmeticFilterAction, CosmeticFilterOperator, location_regex};
 use crate::filters::filter_data_context::FilterDataContextRef;
+use crate::filters::flatbuffer_generated::fb;
 
 use crate::flatbuffers::containers::flat_map::FlatMapView;
 use crate::flatbuffers::containers::flat_multimap::FlatMultiMapView;
 use crate::flatbuffers::containers::hash_map::HashMapStringView;
 use crate::flatbuffers::containers::hash_set::HashSetView;
 use crate::flatbuffers::unsafe_tools::fb_vector_to_slice;
+use crate::html_filtering::{HtmlFilter, HtmlFilters};
+use crate::regex_manager::RegexManager;
 use crate::resources::{PermissionMask, ResourceStorage};
 
-use crate::utils::Hash;
+use crate::utils::{Hash, bin_lookup};
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
@@ -41,10 +48,18 @@ pub struct UrlSpecificResources {
     /// `injected_script` is the Javascript code for any scriptlets that should be injected into
     /// the page.
     pub injected_script: String,
-    /// `generichide` is set to true if there is a corresponding `$generichide` exception network
-    /// filter. If so, the page should not query for additional generic rules using
-    /// `hidden_class_id_selectors`.
+    /// `generichide` is set to true if there is a corresponding `$generichide` or `$elemhide`
+    /// exception network filter. If so, the page should not query for additional generic rules
+    /// using `hidden_class_id_selectors`.
     pub generichide: bool,
+    /// `specifichide` is set to true if there is a corresponding `$specifichide` or `$elemhide`
+    /// exception network filter. If so, hostname-specific hiding rules and procedural filters have
+    /// not been included. Scriptlets are unaffected.
+    pub specifichide: bool,
+    /// `elemhide` is set to true if both `generichide` and `specifichide` are set, e.g. from an
+    /// `$elemhide` exception network filter. If so, no cosmetic filtering should be applied to the
+    /// page other than scriptlets.
+    pub elemhide: bool,
 }
 
 impl UrlSpecificResources {
@@ -118,6 +133,60 @@ fn hostname_domain_hashes(hostname: &str, domain: &str) -> (Vec<Hash>, Vec<Hash>
     (request_entities, request_hostnames)
 }
 
+/// Checks whether a rule with a `[$path=...]` modifier or regex hostname locations applies to a
+/// page, given its hostname, the path and query of its URL, and the hashes of its hostname labels.
+fn scoped_filter_matches(
+    filter: &fb::ScopedCosmeticFilter,
+    hostname: &str,
+    path: &str,
+    request_entities: &[Hash],
+    request_hostnames: &[Hash],
+    regex_manager: &mut RegexManager,
+) -> bool {
+    let any_hash_matches = |filter_hashes: Option<flatbuffers::Vector<'_, u64>>,
+                            request_hashes: &[Hash]| {
+        filter_hashes.is_some_and(|filter_hashes| {
+            let filter_hashes = fb_vector_to_slice(filter_hashes);
+            request_hashes
+                .iter()
+                .any(|hash| bin_lookup(filter_hashes, *hash))
+        })
+    };
+    let mut any_regex_matches =
+        |regexes: Option<flatbuffers::Vector<'_, flatbuffers::ForwardsUOffset<&str>>>| {
+            regexes.is_some_and(|regexes| {
+                regexes
+                    .iter()
+                    .any(|regex| regex_manager.matches_option_regex(regex, false, hostname))
+            })
+        };
+
+    if any_hash_matches(filter.not_hostnames(), request_hostnames)
+        || any_hash_matches(filter.not_entities(), request_entities)
+        || any_regex_matches(filter.not_hostname_regexes())
+    {
+        return false;
+    }
+
+    if (filter.hostnames().is_some()
+        || filter.entities().is_some()
+        || filter.hostname_regexes().is_some())
+        && !any_hash_matches(filter.hostnames(), request_hostnames)
+        && !any_hash_matches(filter.entities(), request_entities)
+        && !any_regex_matches(filter.hostname_regexes())
+    {
+        return false;
+    }
+
+    match filter.path() {
+        Some(filter_path) => match location_regex(filter_path) {
+            Some(pattern) => regex_manager.matches_option_regex(pattern, false, path),
+            None => path.contains(filter_path),
+        },
+        None => true,
+    }
+}
+
 impl CosmeticFilterCache {
     pub fn from_context(filter_data_context: FilterDataContextRef) -> Self {
         Self {
@@ -223,18 +292,24 @@ impl CosmeticFilterCache {
     }
 
     /// Any rules that can't be handled by `hidden_class_id_selectors` are returned by
-    /// `hostname_cosmetic_resources`. As soon as a page navigation is committed, this method
-    /// should be queried to get the initial set of cosmetic filtering operations to apply to the
-    /// page. This provides any rules specifying elements to hide by selectors that are too complex
-    /// to be returned by `hidden_class_id_selectors` (i.e. not directly starting with a class or
-    /// id selector, like `div[class*="Ads"]`), or any rule that is only applicable to a particular
+    /// `url_cosmetic_resources`. As soon as a page navigation is committed, this method should be
+    /// queried to get the initial set of cosmetic filtering operations to apply to the page. This
+    /// provides any rules specifying elements to hide by selectors that are too complex to be
+    /// returned by `hidden_class_id_selectors` (i.e. not directly starting with a class or id
+    /// selector, like `div[class*="Ads"]`), or any rule that is only applicable to a particular
     /// hostname or set of hostnames (like `example.com##.a-class`). The first category is always
     /// injected into every page, and makes up a relatively small number of rules in practice.
-    pub fn hostname_cosmetic_resources(
+    ///
+    /// `path` is the path and query of the page URL, e.g. `/watch?v=123`, which is used for rules
+    /// with a `[$path=...]` modifier.
+    pub fn url_cosmetic_resources(
         &self,
         resources: &ResourceStorage,
         hostname: &str,
+        path: &str,
         generichide: bool,
+        specifichide: bool,
+        regex_manager: &mut RegexManager,
     ) -> UrlSpecificResources {
         let domain_str = {
             let (start, end) = crate::url_parser::get_host_domain(hostname);
@@ -269,9 +344,24 @@ impl CosmeticFilterCache {
             cosmetic_filters.hostname_inject_script_values(),
         );
 
+        let scoped_filters: Vec<_> = cosmetic_filters
+            .scoped_filters()
+            .iter()
+            .filter(|filter| {
+                scoped_filter_matches(
+                    filter,
+                    hostname,
+                    path,
+                    &request_entities,
+                    &request_hostnames,
+                    regex_manager,
+                )
+            })
+            .collect();
+
         for hash in hashes.iter() {
             // Handle top-level hide selectors
-            if let Some(hide_iterator) = hostname_hide_view.get(**hash) {
+            if let Some(hide_iterator) = hostname_hide_view.get(**hash).filter(|_| !specifichide) {
                 for hide_selector in hide_iterator {
                     if !exceptions.contains(hide_selector) {
                         specific_hide_selectors.insert(hide_selector.to_owned());
@@ -291,7 +381,8 @@ impl CosmeticFilterCache {
             }
 
             // Handle remaining rule types from HostnameSpecificRules
-            if let Some(hostname_rules) = hostname_rules_view.get(**hash) {
+            if let Some(hostname_rules) = hostname_rules_view.get(**hash).filter(|_| !specifichide)
+            {
                 // Process procedural actions
                 if let Some(procedural_actions_rules) = hostname_rules.procedural_action() {
                     for action in procedural_actions_rules.iter() {
@@ -301,6 +392,32 @@ impl CosmeticFilterCache {
             }
         }
 
+        for filter in scoped_filters.iter() {
+            let rule = filter.rule();
+            match filter.kind() {
+                SCOPED_HIDE if !specifichide => {
+                    specific_hide_selectors.insert(rule.to_owned());
+                }
+                SCOPED_INJECT_SCRIPT => {
+                    let (permission, script) = decode_script_with_permission(rule);
+                    script_injections
+                        .entry(script)
+                        .and_modify(|entry| *entry |= permission)
+                        .or_insert(permission);
+                }
+                SCOPED_PROCEDURAL_OR_ACTION if !specifichide => {
+                    procedural_actions.insert(rule.to_owned());
+                }
+                _ => (),
+            }
+        }
+
+        if !generichide {
+            for action in cosmetic_filters.generic_procedural_actions().iter() {
+                procedural_actions.insert(action.to_owned());
+            }
+        }
+
         // Process unhide/exception filters
         for hash in hashes.iter() {
             if let Some(hostname_rules) = hostname_rules_view.get(**hash) {
@@ -335,6 +452,28 @@ impl CosmeticFilterCache {
             }
         }
 
+        for filter in scoped_filters.iter() {
+            let rule = filter.rule();
+            match filter.kind() {
+                SCOPED_UNHIDE => {
+                    specific_hide_selectors.remove(rule);
+                    exceptions.insert(rule.to_owned());
+                }
+                SCOPED_PROCEDURAL_OR_ACTION_EXCEPTION => {
+                    procedural_actions.remove(rule);
+                }
+                SCOPED_UNINJECT_SCRIPT => {
+                    if rule.is_empty() {
+                        except_all_scripts = true;
+                        script_injections.clear();
+                    } else if !except_all_scripts {
+                        script_injections.remove(rule);
+                    }
+                }
+                _ => (),
+            }
+        }
+
         let hide_selectors = if generichide {
             specific_hide_selectors
         } else {
@@ -362,7 +501,170 @@ impl CosmeticFilterCache {
             exceptions,
             injected_script,
             generichide,
+            specifichide,
+            elemhide: generichide && specifichide,
+        }
+    }
+
+    /// Returns the HTML filters (i.e. `##^` rules) that apply to a document, after accounting for
+    /// any exceptions. Unlike `url_cosmetic_resources`, there are no generic rules to consider, so
+    /// nothing is returned if `specifichide` applies to the page.
+    ///
+    /// `path` is the path and query of the document URL, as in `url_cosmetic_resources`.
+    pub fn url_html_filters(
+        &self,
+        hostname: &str,
+        path: &str,
+        specifichide: bool,
+        regex_manager: &mut RegexManager,
+    ) -> HtmlFilters {
+        if specifichide {
+            return HtmlFilters::default();
+        }
+
+        let domain_str = {
+            let (start, end) = crate::url_parser::get_host_domain(hostname);
+            &hostname[start..end]
+        };
+
+        let (request_entities, request_hostnames) = hostname_domain_hashes(hostname, domain_str);
+
+        let cosmetic_filters = self.filter_data_context.memory.root().cosmetic_filters();
+        let hostname_rules_view = FlatMapView::new(
+            fb_vector_to_slice(cosmetic_filters.hostname_index()),
+            cosmetic_filters.hostname_values(),
+        );
+
+        // Ordered for deterministic output.
+        let mut html_filters = BTreeSet::new();
+        let mut exceptions = HashSet::new();
+
+        for hash in request_entities.iter().chain(request_hostnames.iter()) {
+            if let Some(hostname_rules) = hostname_rules_view.get(*hash) {
+                if let Some(rules) = hostname_rules.html_filter() {
+                    html_filters.extend(rules.iter().map(|rule| rule.to_owned()));
+                }
+                if let Some(rules) = hostname_rules.html_filter_exception() {
+                    exceptions.extend(rules.iter().map(|rule| rule.to_owned()));
+                }
+            }
+        }
+
+        for filter in cosmetic_filters.scoped_filters().iter() {
+            let kind = filter.kind();
+            if (kind == SCOPED_HTML_FILTER || kind == SCOPED_HTML_FILTER_EXCEPTION)
+                && scoped_filter_matches(
+                    &filter,
+                    hostname,
+                    path,
+                    &request_entities,
+                    &request_hostnames,
+                    regex_manager,
+                )
+            {
+                if kind == SCOPED_HTML_FILTER {
+                    html_filters.insert(filter.rule().to_owned());
+                } else {
+                    exceptions.insert(filter.rule().to_owned());
+                }
+            }
+        }
+
+        let mut result = HtmlFilters::default();
+        for rule in html_filters
+            .iter()
+            .filter(|rule| !exceptions.contains(*rule))
+        {
+            match serde_json::from_str(rule) {
+                Ok(HtmlFilter::Selector(selector)) => result.selectors.push(selector),
+                Ok(HtmlFilter::ResponseHeader(header)) => result.response_headers.push(header),
+                Err(_) => (),
+            }
+        }
+        result
+    }
+}
+
+/// Tracks the cosmetic filtering state of a single page, so that embedders don't need to
+/// re-implement the bookkeeping described in [`crate::Engine::hidden_class_id_selectors`].
+///
+/// A session is created with [`crate::Engine::cosmetic_session`], which queries
+/// [`crate::Engine::url_cosmetic_resources`] for the page. Afterwards, the classes and ids found by
+/// each DOM mutation batch should be passed to [`CosmeticSession::hidden_class_id_selectors`],
+/// which only returns selectors that haven't been returned before.
+pub struct CosmeticSession<'a> {
+    cache: &'a CosmeticFilterCache,
+    resources: UrlSpecificResources,
+    seen_classes: HashSet<String>,
+    seen_ids: HashSet<String>,
+    /// Every hide selector applied so far, including the initial `hide_selectors`.
+    hidden_selectors: BTreeSet<String>,
+}
+
+impl<'a> CosmeticSession<'a> {
+    pub(crate) fn new(cache: &'a CosmeticFilterCache, resources: UrlSpecificResources) -> Self {
+        let hidden_selectors = resources.hide_selectors.iter().cloned().collect();
+        Self {
+            cache,
+            resources,
+            seen_classes: HashSet::new(),
+            seen_ids: HashSet::new(),
+            hidden_selectors,
+        }
+    }
+
+    /// The initial cosmetic resources for the page. These should be applied as soon as the page
+    /// navigation is committed.
+    pub fn resources(&self) -> &UrlSpecificResources {
+        &self.resources
+    }
+
+    /// Returns generic hide selectors that became applicable due to the given classes and ids,
+    /// which are typically collected from a batch of DOM mutations.
+    ///
+    /// Classes and ids that were already passed to an earlier call are skipped, exceptions from
+    /// the page's resources are respected, and selectors that are already hidden are not returned
+    /// again. No selectors are returned if `generichide` applies to the page.
+    pub fn hidden_class_id_selectors(
+        &mut self,
+        classes: impl IntoIterator<Item = impl AsRef<str>>,
+        ids: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Vec<String> {
+        if self.resources.generichide {
+            return vec![];
+        }
+
+        let new_classes: Vec<String> = classes
+            .into_iter()
+            .map(|class| class.as_ref().to_string())
+            .filter(|class| self.seen_classes.insert(class.clone()))
+            .collect();
+        let new_ids: Vec<String> = ids
+            .into_iter()
+            .map(|id| id.as_ref().to_string())
+            .filter(|id| self.seen_ids.insert(id.clone()))
+            .collect();
+        if new_classes.is_empty() && new_ids.is_empty() {
+            return vec![];
+        }
+
+        self.cache
+            .hidden_class_id_selectors(new_classes, new_ids, &self.resources.exceptions)
+            .into_iter()
+            .filter(|selector| self.hidden_selectors.insert(selector.clone()))
+            .collect()
+    }
+
+    /// Returns a stylesheet hiding every selector applied to the page so far, each with a
+    /// separate `{ display: none !important; }` rule so that a single unsupported selector can't
+    /// invalidate the others. Selectors are deduplicated and sorted.
+    pub fn stylesheet(&self) -> String {
+        let mut stylesheet = String::new();
+        for selector in self.hidden_selectors.iter() {
+            stylesheet.push_str(selector);
+            stylesheet.push_str(" { display: none !important; }\n");
         }
+        stylesheet
     }
 }
 
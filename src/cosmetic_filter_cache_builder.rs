@@ -12,6 +12,7 @@ use crate::flatbuffers::containers::flat_map::FlatMapBuilder;
 use crate::flatbuffers::containers::flat_multimap::FlatMultiMapBuilder;
 use crate::flatbuffers::containers::hash_map::HashMapBuilder;
 use crate::flatbuffers::containers::hash_set::HashSetBuilder;
+use crate::html_filtering;
 
 use crate::flatbuffers::containers::flat_serialize::{
     FlatBuilder, FlatSerialize, serialize_vec_opt,
@@ -32,6 +33,8 @@ struct HostnameRule<'a> {
     uninject_script: Vec<WIPOffset<&'a str>>,
     procedural_action: Vec<WIPOffset<&'a str>>,
     procedural_action_exception: Vec<WIPOffset<&'a str>>,
+    html_filter: Vec<WIPOffset<&'a str>>,
+    html_filter_exception: Vec<WIPOffset<&'a str>>,
 }
 
 impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for HostnameRule<'a> {
@@ -46,6 +49,8 @@ impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for HostnameRule<'a> {
         let procedural_action = serialize_vec_opt(value.procedural_action, builder);
         let procedural_action_exception =
             serialize_vec_opt(value.procedural_action_exception, builder);
+        let html_filter = serialize_vec_opt(value.html_filter, builder);
+        let html_filter_exception = serialize_vec_opt(value.html_filter_exception, builder);
 
         fb::HostnameSpecificRules::create(
             builder.raw_builder(),
@@ -54,6 +59,55 @@ impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for HostnameRule<'a> {
                 uninject_script,
                 procedural_action,
                 procedural_action_exception,
+                html_filter,
+                html_filter_exception,
+            },
+        )
+    }
+}
+
+/// A rule with a `[$path=...]` modifier or regex hostname locations, to be serialized as a
+/// `ScopedCosmeticFilter`.
+struct ScopedRule {
+    hostnames: Vec<Hash>,
+    not_hostnames: Vec<Hash>,
+    entities: Vec<Hash>,
+    not_entities: Vec<Hash>,
+    hostname_regexes: Vec<String>,
+    not_hostname_regexes: Vec<String>,
+    path: Option<String>,
+    kind: u8,
+    rule: String,
+}
+
+impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for ScopedRule {
+    type Output = WIPOffset<fb::ScopedCosmeticFilter<'a>>;
+
+    fn serialize(
+        value: Self,
+        builder: &mut EngineFlatBuilder<'a>,
+    ) -> WIPOffset<fb::ScopedCosmeticFilter<'a>> {
+        let hostnames = serialize_vec_opt(value.hostnames, builder);
+        let not_hostnames = serialize_vec_opt(value.not_hostnames, builder);
+        let entities = serialize_vec_opt(value.entities, builder);
+        let not_entities = serialize_vec_opt(value.not_entities, builder);
+        let hostname_regexes = serialize_vec_opt(value.hostname_regexes, builder);
+        let not_hostname_regexes = serialize_vec_opt(value.not_hostname_regexes, builder);
+        let path = value.path.map(|path| builder.create_string(&path));
+        let rule = Some(builder.create_string(&value.rule));
+
+        fb::ScopedCosmeticFilter::create(
+            builder.raw_builder(),
+            &fb::ScopedCosmeticFilterArgs {
+                hostnames,
+                not_hostnames,
+                entities,
+                not_entities,
+                hostname_regexes,
+                not_hostname_regexes,
+                path,
+                kind: value.kind,
+                rule,
             },
         )
     }
@@ -67,6 +121,7 @@ pub(crate) struct CosmeticFilterCacheBuilder<'a> {
     simple_class_rules: HashSetBuilder<String>,
     simple_id_rules: HashSetBuilder<String>,
     misc_generic_selectors: HashSet<String>,
+    generic_procedural_actions: HashSet<String>,
     complex_class_rules: HashMapBuilder<String, StringVector>,
     complex_id_rules: HashMapBuilder<String, StringVector>,
 
@@ -74,11 +129,15 @@ pub(crate) struct CosmeticFilterCacheBuilder<'a> {
     hostname_inject_script: FlatMultiMapBuilder<Hash, WIPOffset<&'a str>>,
 
     specific_rules: HashMap<Hash, HostnameRule<'a>>,
+
+    scoped_rules: Vec<ScopedRule>,
 }
 
 impl<'a> CosmeticFilterCacheBuilder<'a> {
     pub fn add_filter(&mut self, rule: CosmeticFilter, builder: &mut EngineFlatBuilder<'a>) {
-        if rule.has_hostname_constraint() {
+        if rule.is_scoped() {
+            self.store_scoped_rule(rule);
+        } else if rule.has_hostname_constraint() {
             if let Some(generic_rule) = rule.hidden_generic_rule() {
                 self.add_generic_filter(generic_rule);
             }
@@ -90,11 +149,16 @@ impl<'a> CosmeticFilterCacheBuilder<'a> {
 
     /// Add a filter, assuming it has already been determined to be a generic rule
     fn add_generic_filter(&mut self, rule: CosmeticFilter) {
-        let selector = match rule.plain_css_selector() {
-            Some(s) => s.to_string(),
-            None => {
-                // Procedural cosmetic filters cannot be generic.
-                // Silently ignoring this filter.
+        let selector = match (rule.plain_css_selector(), &rule.action) {
+            (Some(s), None) => s.to_string(),
+            _ => {
+                // Procedural and action filters are only parsed as generic rules when
+                // `ParseOptions::allow_generic_procedural_filters` is set.
+                if let Some(SpecificFilterType::ProceduralOrAction(filter)) =
+                    Self::specific_filter_type(rule)
+                {
+                    self.generic_procedural_actions.insert(filter);
+                }
                 return;
             }
         };
@@ -130,48 +194,105 @@ impl<'a> CosmeticFilterCacheBuilder<'a> {
         }
     }
 
-    fn store_hostname_rule(&mut self, rule: CosmeticFilter, builder: &mut EngineFlatBuilder<'a>) {
+    /// Determines how a specific (i.e. non-generic) rule should be stored, consuming its selector
+    /// and action. Returns `None` for malformed rules that should be ignored.
+    fn specific_filter_type(rule: CosmeticFilter) -> Option<SpecificFilterType> {
         use SpecificFilterType::*;
 
         let unhide = rule.mask.contains(CosmeticFilterMask::UNHIDE);
         let script_inject = rule.mask.contains(CosmeticFilterMask::SCRIPT_INJECT);
 
-        let kind = match (
-            script_inject,
-            rule.plain_css_selector().map(|s| s.to_string()),
-            rule.action,
-        ) {
-            (false, Some(selector), None) => Hide(selector),
-            (true, Some(selector), None) => InjectScript((selector, rule.permission)),
-            (false, selector, action) => ProceduralOrAction(
-                serde_json::to_string(&ProceduralOrActionFilter {
-                    selector: selector
-                        .map(|selector| vec![CosmeticFilterOperator::CssSelector(selector)])
-                        .unwrap_or(rule.selector),
-                    action,
-                })
-                .unwrap(),
-            ),
-            (true, _, Some(_)) => return, // script injection with action - shouldn't be possible
-            (true, None, _) => return, // script injection without plain CSS selector - shouldn't be possible
+        let kind = if rule.mask.contains(CosmeticFilterMask::HTML_FILTER) {
+            let html_filter = if let [CosmeticFilterOperator::ResponseHeader(header)] =
+                rule.selector.as_slice()
+            {
+                html_filtering::HtmlFilter::ResponseHeader(header.clone())
+            } else {
+                html_filtering::HtmlFilter::Selector(rule.selector)
+            };
+            HtmlFilter(serde_json::to_string(&html_filter).unwrap())
+        } else {
+            match (
+                script_inject,
+                rule.plain_css_selector().map(|s| s.to_string()),
+                rule.action,
+            ) {
+                (false, Some(selector), None) => Hide(selector),
+                (true, Some(selector), None) => InjectScript((selector, rule.permission)),
+                (false, selector, action) => ProceduralOrAction(
+                    serde_json::to_string(&ProceduralOrActionFilter {
+                        selector: selector
+                            .map(|selector| vec![CosmeticFilterOperator::CssSelector(selector)])
+                            .unwrap_or(rule.selector),
+                        action,
+                    })
+                    .unwrap(),
+                ),
+                (true, _, Some(_)) => return None, // script injection with action - shouldn't be possible
+                (true, None, _) => return None, // script injection without plain CSS selector - shouldn't be possible
+            }
         };
 
-        let kind = if unhide { kind.negated() } else { kind };
+        Some(if unhide { kind.negated() } else { kind })
+    }
+
+    fn store_hostname_rule(
+        &mut self,
+        mut rule: CosmeticFilter,
+        builder: &mut EngineFlatBuilder<'a>,
+    ) {
+        let hostnames = rule.hostnames.take();
+        let entities = rule.entities.take();
+        let not_hostnames = rule.not_hostnames.take();
+        let not_entities = rule.not_entities.take();
+
+        let Some(kind) = Self::specific_filter_type(rule) else {
+            return;
+        };
 
         let tokens_to_insert = std::iter::empty()
-            .chain(rule.hostnames.unwrap_or_default())
-            .chain(rule.entities.unwrap_or_default());
+            .chain(hostnames.unwrap_or_default())
+            .chain(entities.unwrap_or_default());
 
         self.store_hostname_filter(tokens_to_insert, &kind, builder);
 
         let negated = kind.negated();
         let tokens_to_insert_negated = std::iter::empty()
-            .chain(rule.not_hostnames.unwrap_or_default())
-            .chain(rule.not_entities.unwrap_or_default());
+            .chain(not_hostnames.unwrap_or_default())
+            .chain(not_entities.unwrap_or_default());
 
         self.store_hostname_filter(tokens_to_insert_negated, &negated, builder);
     }
 
+    /// Unlike hostname rules, negated locations of scoped rules are stored as-is rather than as
+    /// inverted rules, since each scoped rule is matched against the full set of its locations.
+    fn store_scoped_rule(&mut self, mut rule: CosmeticFilter) {
+        let hostnames = rule.hostnames.take().unwrap_or_default();
+        let not_hostnames = rule.not_hostnames.take().unwrap_or_default();
+        let entities = rule.entities.take().unwrap_or_default();
+        let not_entities = rule.not_entities.take().unwrap_or_default();
+        let hostname_regexes = rule.hostname_regexes.take().unwrap_or_default();
+        let not_hostname_regexes = rule.not_hostname_regexes.take().unwrap_or_default();
+        let path = rule.path.take();
+
+        let Some((kind, rule)) = Self::specific_filter_type(rule).map(|kind| kind.into_scoped())
+        else {
+            return;
+        };
+
+        self.scoped_rules.push(ScopedRule {
+            hostnames,
+            not_hostnames,
+            entities,
+            not_entities,
+            hostname_regexes,
+            not_hostname_regexes,
+            path,
+            kind,
+            rule,
+        });
+    }
+
     fn store_hostname_filter(
         &mut self,
         tokens: impl IntoIterator<Item = Hash>,
@@ -231,6 +352,22 @@ impl<'a> CosmeticFilterCacheBuilder<'a> {
                     entry.procedural_action_exception.push(*s);
                 }
             }
+            HtmlFilter(s) => {
+                let mut cached_offset = None;
+                for token in tokens {
+                    let s = cached_offset.get_or_insert_with(|| builder.create_string(s));
+                    let entry = self.specific_rules.entry(token).or_default();
+                    entry.html_filter.push(*s);
+                }
+            }
+            HtmlFilterException(s) => {
+                let mut cached_offset = None;
+                for token in tokens {
+                    let s = cached_offset.get_or_insert_with(|| builder.create_string(s));
+                    let entry = self.specific_rules.entry(token).or_default();
+                    entry.html_filter_exception.push(*s);
+                }
+            }
         }
     }
 }
@@ -265,12 +402,18 @@ impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for CosmeticFilterCacheBuilder
         // Handle remaining rule types through HostnameSpecificRules
         let hostname_specific_rules = FlatMapBuilder::finish(value.specific_rules, builder);
 
+        let scoped_filters = Some(FlatSerialize::serialize(value.scoped_rules, builder));
+
         let simple_class_rules = Some(FlatSerialize::serialize(value.simple_class_rules, builder));
         let simple_id_rules = Some(FlatSerialize::serialize(value.simple_id_rules, builder));
         let misc_generic_selectors = Some(FlatSerialize::serialize(
             value.misc_generic_selectors,
             builder,
         ));
+        let generic_procedural_actions = Some(FlatSerialize::serialize(
+            value.generic_procedural_actions,
+            builder,
+        ));
 
         fb::CosmeticFilters::create(
             builder.raw_builder(),
@@ -288,6 +431,8 @@ impl<'a> FlatSerialize<'a, EngineFlatBuilder<'a>> for CosmeticFilterCacheBuilder
                 hostname_inject_script_values: Some(hostname_inject_script.values),
                 hostname_index: Some(hostname_specific_rules.keys),
                 hostname_values: Some(hostname_specific_rules.values),
+                scoped_filters,
+                generic_procedural_actions,
             },
         )
     }
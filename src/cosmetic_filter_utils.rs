@@ -69,6 +69,8 @@ pub(crate) enum SpecificFilterType {
     UninjectScript((String, PermissionMask)),
     ProceduralOrAction(String),
     ProceduralOrActionException(String),
+    HtmlFilter(String),
+    HtmlFilterException(String),
 }
 
 impl SpecificFilterType {
@@ -80,6 +82,39 @@ impl SpecificFilterType {
             Self::UninjectScript(s) => Self::InjectScript(s),
             Self::ProceduralOrAction(s) => Self::ProceduralOrActionException(s),
             Self::ProceduralOrActionException(s) => Self::ProceduralOrAction(s),
+            Self::HtmlFilter(s) => Self::HtmlFilterException(s),
+            Self::HtmlFilterException(s) => Self::HtmlFilter(s),
+        }
+    }
+}
+
+/// Values of `ScopedCosmeticFilter::kind`, each corresponding to a variant of
+/// `SpecificFilterType`.
+pub(crate) const SCOPED_HIDE: u8 = 0;
+pub(crate) const SCOPED_UNHIDE: u8 = 1;
+pub(crate) const SCOPED_INJECT_SCRIPT: u8 = 2;
+pub(crate) const SCOPED_UNINJECT_SCRIPT: u8 = 3;
+pub(crate) const SCOPED_PROCEDURAL_OR_ACTION: u8 = 4;
+pub(crate) const SCOPED_PROCEDURAL_OR_ACTION_EXCEPTION: u8 = 5;
+pub(crate) const SCOPED_HTML_FILTER: u8 = 6;
+pub(crate) const SCOPED_HTML_FILTER_EXCEPTION: u8 = 7;
+
+impl SpecificFilterType {
+    /// Returns the `kind` and `rule` to store for this filter in a `ScopedCosmeticFilter`.
+    /// Scriptlets are encoded with their permission, as in `hostname_inject_script_values`.
+    pub(crate) fn into_scoped(self) -> (u8, String) {
+        match self {
+            Self::Hide(s) => (SCOPED_HIDE, s),
+            Self::Unhide(s) => (SCOPED_UNHIDE, s),
+            Self::InjectScript((s, permission)) => (
+                SCOPED_INJECT_SCRIPT,
+                encode_script_with_permission(&s, &permission),
+            ),
+            Self::UninjectScript((s, _)) => (SCOPED_UNINJECT_SCRIPT, s),
+            Self::ProceduralOrAction(s) => (SCOPED_PROCEDURAL_OR_ACTION, s),
+            Self::ProceduralOrActionException(s) => (SCOPED_PROCEDURAL_OR_ACTION_EXCEPTION, s),
+            Self::HtmlFilter(s) => (SCOPED_HTML_FILTER, s),
+            Self::HtmlFilterException(s) => (SCOPED_HTML_FILTER_EXCEPTION, s),
         }
     }
 }
@@ -1,7 +1,9 @@
 //! The adblock [`Engine`] is the primary interface for adblocking.
 
-use crate::blocker::{Blocker, BlockerResult};
-use crate::cosmetic_filter_cache::{CosmeticFilterCache, UrlSpecificResources};
+use crate::blocker::{
+    Blocker, BlockerResult, BodyReplacement, DnsClient, DnsResult, RequestExplanation,
+};
+use crate::cosmetic_filter_cache::{CosmeticFilterCache, CosmeticSession, UrlSpecificResources};
 use crate::cosmetic_filter_cache_builder::CosmeticFilterCacheBuilder;
 use crate::data_format::{deserialize_dat_file, serialize_dat_file};
 use crate::filters::fb_builder::EngineFlatBuilder;
@@ -10,9 +12,10 @@ use crate::filters::filter_data_context::{FilterDataContext, FilterDataContextRe
 use crate::filters::flatbuffer_generated::fb;
 use crate::flatbuffers::containers::flat_serialize::{FlatBuilder, FlatSerialize};
 use crate::flatbuffers::unsafe_tools::VerifiedFlatbufferMemory;
+use crate::html_filtering::HtmlFilters;
 use crate::lists::{FilterSet, ParseOptions, ParsedLine, parse_filter};
 use crate::regex_manager::RegexManagerDiscardPolicy;
-use crate::request::Request;
+use crate::request::{Request, RequestError};
 use crate::resources::{Resource, ResourceStorage, ResourceStorageBackend};
 
 pub use crate::data_format::DeserializationError;
@@ -152,11 +155,14 @@ impl Engine {
             let mut cosmetic_filter_count = 0;
             let mut parse_error = 0;
             let mut invalid_lines = Vec::new();
-            for (line_number, line) in list_source.list_text.lines().enumerate() {
+            for (line_number, line) in list_source.lines() {
                 let parsed_line = parse_filter(line, debug, list_source.parse_options);
                 match parsed_line {
                     Ok(ParsedLine::Network(filter)) => {
-                        let debug_data = if debug {
+                        // `replace` and `urltransform` filters are applied in list order, so
+                        // they always keep their position.
+                        let debug_data = if debug || filter.is_replace() || filter.is_urltransform()
+                        {
                             NetworkFilterDebugData {
                                 source_index: source_index as u32,
                                 line_number: line_number as u32,
@@ -255,6 +261,66 @@ impl Engine {
         self.blocker.check(request, &self.resources)
     }
 
+    /// Like [`Engine::check_network_request`], but also checks `request` as if its hostname were
+    /// replaced by each of `cname_aliases`, i.e. the chain of DNS CNAME records it resolves
+    /// through, or just its canonical name. See [`Blocker::check_cname_aliases`] for details.
+    pub fn check_network_request_with_cname_aliases(
+        &self,
+        request: &Request,
+        cname_aliases: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> BlockerResult {
+        self.blocker
+            .check_cname_aliases(request, cname_aliases, &self.resources)
+    }
+
+    /// Decides how a DNS query for `hostname` with the record type `query_type`, e.g. `A` or
+    /// `AAAA`, should be answered: blocked, rewritten, or allowed. This is intended for DNS-level
+    /// blocking, so only filters that depend on nothing but the hostname are considered, like
+    /// `||example.com^` or hosts-format rules. See [`Blocker::check_hostname`] for details.
+    ///
+    /// Filters with a `client` option that includes specific clients don't apply here; use
+    /// [`Engine::check_hostname_for_client`] to check them as well.
+    pub fn check_hostname(
+        &self,
+        hostname: &str,
+        query_type: &str,
+    ) -> Result<DnsResult, RequestError> {
+        self.blocker
+            .check_hostname(hostname, query_type, &DnsClient::default())
+    }
+
+    /// Like [`Engine::check_hostname`], but for a query sent by `client`, which is matched
+    /// against `client` options.
+    pub fn check_hostname_for_client(
+        &self,
+        hostname: &str,
+        query_type: &str,
+        client: &DnsClient,
+    ) -> Result<DnsResult, RequestError> {
+        self.blocker.check_hostname(hostname, query_type, client)
+    }
+
+    /// Lists every filter matching `request`, along with whether it was applied, or why it was
+    /// overridden. This is intended for diagnosing breakage reports, and is slower than
+    /// [`Engine::check_network_request`].
+    ///
+    /// Filter sources and line numbers are only available if debugging was enabled (see
+    /// [`crate::FilterSet::new`]).
+    pub fn explain_network_request(&self, request: &Request) -> RequestExplanation {
+        self.blocker.explain(request, &self.resources)
+    }
+
+    /// Check if the response to a network request should be blocked, given its `headers` as
+    /// `(name, value)` pairs. Only `$header` filters are considered here, so this should be used
+    /// in addition to [`Engine::check_network_request`] once the response headers are available.
+    pub fn check_network_response(
+        &self,
+        request: &Request,
+        headers: &[(&str, &str)],
+    ) -> BlockerResult {
+        self.blocker.check_response_headers(request, headers)
+    }
+
     #[cfg(test)]
     pub(crate) fn check_network_request_exceptions(&self, request: &Request) -> bool {
         self.blocker.check_exceptions(request)
@@ -288,6 +354,30 @@ impl Engine {
         self.blocker.get_csp_directives(request)
     }
 
+    /// Check if a popup window opened by `opener_url` and navigating to `target_url` should be
+    /// blocked, according to any `$popup` or `$popunder` rules. Those rules are not considered by
+    /// [`Engine::check_network_request`].
+    pub fn check_popup(&self, opener_url: &str, target_url: &str) -> BlockerResult {
+        self.blocker.check_popup(opener_url, target_url)
+    }
+
+    /// Returns a `Permissions-Policy` header value that should be added to this request's
+    /// response, from any `$permissions` rules. Only applies to document and subdocument requests.
+    ///
+    /// Directives from multiple rules are deduplicated and joined by commas.
+    pub fn get_permissions_policy(&self, request: &Request) -> Option<String> {
+        self.blocker.get_permissions_policy(request)
+    }
+
+    /// Returns the rewrites from `$replace` filters that should be applied, in order, to the body
+    /// of this request's response. See [`BodyReplacement::apply`].
+    ///
+    /// These are independent of blocking; a blocked request's response will not be available to
+    /// rewrite.
+    pub fn get_body_replacements(&self, request: &Request) -> Vec<BodyReplacement> {
+        self.blocker.get_body_replacements(request)
+    }
+
     /// Sets this engine's tags to be _only_ the ones provided in `tags`.
     ///
     /// Tags can be used to cheaply enable or disable network rules with a corresponding `$tag`
@@ -363,7 +453,9 @@ impl Engine {
     /// CSS selectors corresponding to rules referencing those classes or ids, provided that the
     /// corresponding rules are not excepted.
     ///
-    /// `exceptions` should be passed directly from `UrlSpecificResources`.
+    /// `exceptions` should be passed directly from `UrlSpecificResources`. The exceptions and the
+    /// classes and ids already seen on the page must be tracked by the caller; see
+    /// [`Engine::cosmetic_session`] for an alternative that handles this automatically.
     pub fn hidden_class_id_selectors(
         &self,
         classes: impl IntoIterator<Item = impl AsRef<str>>,
@@ -379,20 +471,65 @@ impl Engine {
     /// `hidden_class_id_selectors` to obtain any stylesheets consisting of generic rules (if the
     /// returned `generichide` value is false).
     pub fn url_cosmetic_resources(&self, url: &str) -> UrlSpecificResources {
-        let request = if let Ok(request) = Request::new(url, url, "document", "get") {
-            request
-        } else {
+        let Some((request, path)) = Self::page_request(url) else {
             return UrlSpecificResources::empty();
         };
 
-        let generichide = self.blocker.check_generic_hide(&request);
-        self.cosmetic_cache.hostname_cosmetic_resources(
+        let elemhide = self.blocker.check_elem_hide(&request);
+        let generichide = elemhide || self.blocker.check_generic_hide(&request);
+        let specifichide = elemhide || self.blocker.check_specific_hide(&request);
+        self.cosmetic_cache.url_cosmetic_resources(
             &self.resources,
             &request.hostname,
+            &path,
             generichide,
+            specifichide,
+            &mut self.blocker.borrow_regex_manager(),
         )
     }
 
+    /// Like [`Engine::url_cosmetic_resources`], but returns a [`CosmeticSession`] that keeps track
+    /// of the classes and ids already seen on the page, for use with subsequent DOM mutations.
+    pub fn cosmetic_session(&self, url: &str) -> CosmeticSession<'_> {
+        CosmeticSession::new(&self.cosmetic_cache, self.url_cosmetic_resources(url))
+    }
+
+    /// Returns the HTML filters (i.e. `##^` rules) that apply to a document at a particular url.
+    /// These should be applied to the document's response before it reaches the browser, e.g.
+    /// from a proxy. See [`crate::html_filtering`] for more details.
+    ///
+    /// As with [`Engine::url_cosmetic_resources`], no filters are returned if the page is excepted
+    /// by an `$elemhide` or `$specifichide` rule.
+    pub fn url_html_filters(&self, url: &str) -> HtmlFilters {
+        let Some((request, path)) = Self::page_request(url) else {
+            return HtmlFilters::default();
+        };
+
+        let specifichide =
+            self.blocker.check_elem_hide(&request) || self.blocker.check_specific_hide(&request);
+        self.cosmetic_cache.url_html_filters(
+            &request.hostname,
+            &path,
+            specifichide,
+            &mut self.blocker.borrow_regex_manager(),
+        )
+    }
+
+    /// Returns the lowercase names of headers that should be removed from the response of a
+    /// document at a particular url, from `##^responseheader(...)` rules.
+    pub fn url_removed_response_headers(&self, url: &str) -> Vec<String> {
+        self.url_html_filters(url).response_headers
+    }
+
+    /// Builds a `document` request for the page at `url`, along with the path and query of the
+    /// url for matching `[$path=...]` rules.
+    fn page_request(url: &str) -> Option<(Request, String)> {
+        let request = Request::new(url, url, "document", "get").ok()?;
+        let parsed = url::Url::parse(url).ok()?;
+        let path = parsed[url::Position::BeforePath..url::Position::AfterQuery].to_string();
+        Some((request, path))
+    }
+
     pub fn set_regex_discard_policy(&self, new_discard_policy: RegexManagerDiscardPolicy) {
         self.blocker.set_regex_discard_policy(new_discard_policy);
     }
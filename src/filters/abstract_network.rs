@@ -1,12 +1,9 @@
-use memchr::memrchr as find_char_reverse;
+use super::network::{
+    HeaderMatcher, HeaderValue, IpAddressValue, NetworkFilterError, RemoveparamMatcher,
+    RemoveparamValue, ReplaceValue,
+};
 
-use super::network::NetworkFilterError;
-
-use regex::Regex;
-use std::sync::LazyLock;
-
-/// For now, only support `$removeparam` with simple alphanumeric/dash/underscore patterns.
-static VALID_PARAM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_\-]+$").unwrap());
+use std::borrow::Cow;
 
 #[derive(Clone, Copy)]
 pub(crate) enum NetworkFilterLeftAnchor {
@@ -33,34 +30,138 @@ pub(crate) struct NetworkFilterPattern {
 
 #[derive(Clone, Copy)]
 pub(crate) enum HttpMethod {
+    Connect,
+    Delete,
     Get,
     Head,
+    Options,
+    Patch,
     Post,
+    Put,
 }
 
 /// Parses a pipe-delimited string of domains into a vector of domain entries with negation flags.
-/// Splits the input string by '|', strips '~' prefixes to mark negated domains,
-/// and filters out entries that are regex patterns.
+/// Splits the input string by '|', and strips '~' prefixes to mark negated domains.
+///
+/// Regex entries like `/^img[a-z]+\./` are kept as-is, including their slashes, if
+/// `allow_regexes` is set and full regex handling is enabled. They're validated here, and
+/// otherwise filtered out.
 fn parse_pipe_delimited_domains<'a>(
     value: &'a str,
+    allow_regexes: bool,
 ) -> Result<Vec<(bool, &'a str)>, NetworkFilterError> {
-    let domains: Vec<(bool, &'a str)> = value
-        .split('|')
-        .map(|domain| {
-            if let Some(negated_domain) = domain.strip_prefix('~') {
-                (false, negated_domain)
-            } else {
-                (true, domain)
+    let mut domains: Vec<(bool, &'a str)> = vec![];
+    for domain in split_pipe_delimited_domains(value) {
+        let (enabled, domain) = if let Some(negated_domain) = domain.strip_prefix('~') {
+            (false, negated_domain)
+        } else {
+            (true, domain)
+        };
+        if let Some(pattern) = domain_regex(domain) {
+            if !allow_regexes || cfg!(not(feature = "full-regex-handling")) {
+                continue;
             }
-        })
-        .filter(|(_, d)| !(d.starts_with('/') && d.ends_with('/')))
-        .collect();
+            // Reject invalid regexes early, rather than silently failing to match later.
+            crate::regex_manager::compile_option_regex(&unescape_option_value(pattern), false)
+                .map_err(NetworkFilterError::RegexParsingError)?;
+        }
+        domains.push((enabled, domain));
+    }
     if domains.is_empty() {
         return Err(NetworkFilterError::NoSupportedDomains);
     }
     Ok(domains)
 }
 
+/// Returns the pattern from a `/.../` regex entry in a `$domain=` list, without its slashes.
+pub(crate) fn domain_regex(domain: &str) -> Option<&str> {
+    domain
+        .strip_prefix('/')
+        .and_then(|domain| domain.strip_suffix('/'))
+}
+
+/// Splits a `$domain=` value on each `|`, except within regex entries like `/^(foo|bar)\./`.
+/// A regex entry ends at the first unescaped `/` that's followed by a `|` or the end of the value.
+pub(crate) fn split_pipe_delimited_domains(value: &str) -> impl Iterator<Item = &str> {
+    split_delimited_domains(value, b'|')
+}
+
+/// Splits a list of domains on each `separator`, except within regex entries. Like
+/// [`split_pipe_delimited_domains`], but also used for the comma-separated locations of cosmetic
+/// filters.
+pub(crate) fn split_delimited_domains(value: &str, separator: u8) -> impl Iterator<Item = &str> {
+    let mut rest = Some(value);
+    std::iter::from_fn(move || {
+        let current = rest?;
+        let body_start = usize::from(current.starts_with('~'));
+        let regex_end = if current[body_start..].starts_with('/') {
+            unescaped_char_indices(&current[body_start + 1..], b'/')
+                .map(|i| body_start + i + 2)
+                .find(|&end| end == current.len() || current.as_bytes()[end] == separator)
+        } else {
+            None
+        };
+        match regex_end.or_else(|| memchr::memchr(separator, current.as_bytes())) {
+            Some(end) if end < current.len() => {
+                rest = Some(&current[end + 1..]);
+                Some(&current[..end])
+            }
+            _ => {
+                rest = None;
+                Some(current)
+            }
+        }
+    })
+}
+
+/// Returns the byte indices of every occurrence of `separator` in `s` that isn't escaped by a
+/// preceding backslash. Option values like `$removeparam=/.../` use `\,` and `\$` to include
+/// those characters without ending the option.
+fn unescaped_char_indices(s: &str, separator: u8) -> impl Iterator<Item = usize> + '_ {
+    let bytes = s.as_bytes();
+    memchr::memchr_iter(separator, bytes).filter(move |&i| {
+        let backslashes = bytes[..i].iter().rev().take_while(|b| **b == b'\\').count();
+        backslashes % 2 == 0
+    })
+}
+
+/// Splits `s` on each occurrence of `separator` that isn't escaped by a preceding backslash.
+pub(crate) fn split_unescaped(s: &str, separator: u8) -> impl Iterator<Item = &str> {
+    let mut start = 0;
+    unescaped_char_indices(s, separator)
+        .chain(std::iter::once(s.len()))
+        .map(move |end| {
+            let part = &s[start..end];
+            start = end + 1;
+            part
+        })
+}
+
+/// Removes the backslash from `\,` and `\$` escape sequences in an option value. Other escape
+/// sequences are preserved as-is.
+pub(crate) fn unescape_option_value(value: &str) -> Cow<'_, str> {
+    if !value.contains("\\,") && !value.contains("\\$") {
+        return Cow::Borrowed(value);
+    }
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(next @ (',' | '$')) => result.push(next),
+                Some(next) => {
+                    result.push(c);
+                    result.push(next);
+                }
+                None => result.push(c),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Cow::Owned(result)
+}
+
 /// Any option that appears on the right side of a network filter as initiated by a `$` character.
 /// All `bool` arguments below are `true` if the option stands alone, or `false` if the option is
 /// negated using a prepended `~`.
@@ -68,17 +169,34 @@ fn parse_pipe_delimited_domains<'a>(
 pub(crate) enum NetworkFilterOption<'a> {
     Domain(Vec<(bool, &'a str)>),
     To(Vec<(bool, &'a str)>),
+    Denyallow(Vec<&'a str>),
+    IpAddress(&'a str),
+    DnsType(&'a str),
+    Client(&'a str),
+    DnsRewrite(Option<&'a str>),
     Badfilter,
     Important,
     MatchCase,
     ThirdParty(bool),
     FirstParty(bool),
+    StrictThirdParty(bool),
+    StrictFirstParty(bool),
     Tag(&'a str),
     Redirect(&'a str),
     RedirectRule(&'a str),
     Csp(Option<&'a str>),
-    Removeparam(&'a str),
+    Removeparam(Option<&'a str>),
+    Replace(Option<&'a str>),
+    UrlTransform(Option<&'a str>),
+    Header(&'a str),
+    Permissions(Option<&'a str>),
+    Popup,
+    Popunder,
     Generichide,
+    Elemhide,
+    Specifichide,
+    Genericblock,
+    Cname,
     Document,
     Image(bool),
     Media(bool),
@@ -142,7 +260,7 @@ impl AbstractNetworkFilter<'_> {
             exception = true;
         }
 
-        let maybe_options_index: Option<usize> = find_char_reverse(b'$', line.as_bytes());
+        let maybe_options_index: Option<usize> = unescaped_char_indices(line, b'$').last();
 
         let mut options = None;
         if let Some(options_index) = maybe_options_index {
@@ -192,7 +310,7 @@ fn parse_filter_options<'a>(
 ) -> Result<Vec<NetworkFilterOption<'a>>, NetworkFilterError> {
     let mut result = vec![];
 
-    for raw_option in raw_options.split(',') {
+    for raw_option in split_unescaped(raw_options, b',') {
         // Check for negation: ~option
         let negation = raw_option.starts_with('~');
         let maybe_negated_option = raw_option.trim_start_matches('~');
@@ -206,9 +324,57 @@ fn parse_filter_options<'a>(
 
         result.push(match (option, negation) {
             ("domain", _) | ("from", _) => {
-                NetworkFilterOption::Domain(parse_pipe_delimited_domains(value)?)
+                NetworkFilterOption::Domain(parse_pipe_delimited_domains(value, true)?)
+            }
+            // Regexes are only supported in `$domain=`.
+            ("to", _) => NetworkFilterOption::To(parse_pipe_delimited_domains(value, false)?),
+            ("denyallow", true) => return Err(NetworkFilterError::NegatedDenyallow),
+            ("denyallow", false) => {
+                let domains: Vec<&str> = value.split('|').collect();
+                // Like uBO, only plain hostnames are supported here.
+                if domains.iter().any(|domain| {
+                    domain.is_empty()
+                        || domain.starts_with('~')
+                        || domain.starts_with('/')
+                        || domain.ends_with(".*")
+                }) {
+                    return Err(NetworkFilterError::DenyallowInvalidValue);
+                }
+                NetworkFilterOption::Denyallow(domains)
+            }
+            ("ipaddress", true) => return Err(NetworkFilterError::NegatedIpAddress),
+            ("ipaddress", false) => {
+                // Reject invalid regexes early, rather than silently failing to match later.
+                if let IpAddressValue::Regex {
+                    pattern,
+                    ignore_case,
+                } = IpAddressValue::parse(value)?
+                {
+                    crate::regex_manager::compile_option_regex(&pattern, ignore_case)
+                        .map_err(NetworkFilterError::RegexParsingError)?;
+                }
+                NetworkFilterOption::IpAddress(value)
+            }
+            ("dnstype", true) => return Err(NetworkFilterError::NegatedDnsType),
+            ("dnstype", false) => {
+                super::network::parse_dns_types(value)?;
+                NetworkFilterOption::DnsType(value)
+            }
+            ("client", true) => return Err(NetworkFilterError::NegatedClient),
+            ("client", false) => {
+                super::network::parse_client_value(value)?;
+                NetworkFilterOption::Client(value)
+            }
+            ("dnsrewrite", true) => return Err(NetworkFilterError::NegatedDnsRewrite),
+            ("dnsrewrite", false) => {
+                // As with `replace`, an empty value is only meaningful for exceptions.
+                if value.is_empty() {
+                    NetworkFilterOption::DnsRewrite(None)
+                } else {
+                    super::network::DnsRewrite::parse(value)?;
+                    NetworkFilterOption::DnsRewrite(Some(value))
+                }
             }
-            ("to", _) => NetworkFilterOption::To(parse_pipe_delimited_domains(value)?),
             ("badfilter", true) => return Err(NetworkFilterError::NegatedBadFilter),
             ("badfilter", false) => NetworkFilterOption::Badfilter,
             ("important", true) => return Err(NetworkFilterError::NegatedImportant),
@@ -217,6 +383,8 @@ fn parse_filter_options<'a>(
             ("match-case", false) => NetworkFilterOption::MatchCase,
             ("third-party", negated) | ("3p", negated) => NetworkFilterOption::ThirdParty(!negated),
             ("first-party", negated) | ("1p", negated) => NetworkFilterOption::FirstParty(!negated),
+            ("strict3p", negated) => NetworkFilterOption::StrictThirdParty(!negated),
+            ("strict1p", negated) => NetworkFilterOption::StrictFirstParty(!negated),
             ("tag", true) => return Err(NetworkFilterError::NegatedTag),
             ("tag", false) => NetworkFilterOption::Tag(value),
             // `rewrite` is the ABP-syntax alias for `redirect`. The `abp-resource:`-prefixed
@@ -244,20 +412,98 @@ fn parse_filter_options<'a>(
             ("csp", _) => {
                 NetworkFilterOption::Csp(if !value.is_empty() { Some(value) } else { None })
             }
-            ("removeparam", true) => return Err(NetworkFilterError::NegatedRemoveparam),
-            ("removeparam", false) => {
+            ("permissions", true) => return Err(NetworkFilterError::NegatedPermissions),
+            ("permissions", false) => {
                 if value.is_empty() {
-                    return Err(NetworkFilterError::EmptyRemoveparam);
+                    NetworkFilterOption::Permissions(None)
+                } else {
+                    super::network::parse_permissions_directives(value)?;
+                    NetworkFilterOption::Permissions(Some(value))
                 }
-                if !VALID_PARAM.is_match(value) {
-                    return Err(NetworkFilterError::RemoveparamRegexUnsupported);
+            }
+            // `queryprune` is the deprecated uBO name for `removeparam`.
+            ("removeparam", true) | ("queryprune", true) => {
+                return Err(NetworkFilterError::NegatedRemoveparam);
+            }
+            ("removeparam", false) | ("queryprune", false) => {
+                let value = if !value.is_empty() { Some(value) } else { None };
+                // Reject invalid regexes early, rather than silently failing to match later.
+                if let RemoveparamMatcher::Regex {
+                    pattern,
+                    ignore_case,
+                } = RemoveparamValue::parse(value)?.matcher
+                {
+                    crate::regex_manager::compile_option_regex(&pattern, ignore_case)
+                        .map_err(NetworkFilterError::RegexParsingError)?;
                 }
                 NetworkFilterOption::Removeparam(value)
             }
+            ("header", true) => return Err(NetworkFilterError::NegatedHeader),
+            ("header", false) => {
+                // Reject invalid regexes early, rather than silently failing to match later.
+                if let HeaderMatcher::Regex {
+                    pattern,
+                    ignore_case,
+                } = HeaderValue::parse(value)?.matcher
+                {
+                    crate::regex_manager::compile_option_regex(&pattern, ignore_case)
+                        .map_err(NetworkFilterError::RegexParsingError)?;
+                }
+                NetworkFilterOption::Header(value)
+            }
+            ("replace", true) => return Err(NetworkFilterError::NegatedReplace),
+            ("replace", false) => {
+                // An empty value is only meaningful for exceptions, where it disables all
+                // replacements. That's checked once the filter has been fully parsed.
+                if value.is_empty() {
+                    NetworkFilterOption::Replace(None)
+                } else {
+                    // Reject invalid regexes early, rather than silently failing to match later.
+                    ReplaceValue::parse(value)?
+                        .compile()
+                        .map_err(NetworkFilterError::RegexParsingError)?;
+                    NetworkFilterOption::Replace(Some(value))
+                }
+            }
+            ("urltransform", true) | ("uritransform", true) => {
+                return Err(NetworkFilterError::NegatedUrlTransform);
+            }
+            ("urltransform", false) | ("uritransform", false) => {
+                // As with `replace`, an empty value is only meaningful for exceptions.
+                if value.is_empty() {
+                    NetworkFilterOption::UrlTransform(None)
+                } else {
+                    ReplaceValue::parse(value)
+                        .map_err(|e| match e {
+                            NetworkFilterError::ReplaceRegexUnsupported => {
+                                NetworkFilterError::UrlTransformRegexUnsupported
+                            }
+                            _ => NetworkFilterError::UrlTransformInvalidValue,
+                        })?
+                        .compile()
+                        .map_err(NetworkFilterError::RegexParsingError)?;
+                    NetworkFilterOption::UrlTransform(Some(value))
+                }
+            }
+            ("popup", true) | ("popunder", true) => return Err(NetworkFilterError::NegatedPopup),
+            ("popup", false) => NetworkFilterOption::Popup,
+            ("popunder", false) => NetworkFilterOption::Popunder,
             ("generichide", true) | ("ghide", true) => {
                 return Err(NetworkFilterError::NegatedGenericHide);
             }
             ("generichide", false) | ("ghide", false) => NetworkFilterOption::Generichide,
+            ("elemhide", true) | ("ehide", true) => {
+                return Err(NetworkFilterError::NegatedElemHide);
+            }
+            ("elemhide", false) | ("ehide", false) => NetworkFilterOption::Elemhide,
+            ("specifichide", true) | ("shide", true) => {
+                return Err(NetworkFilterError::NegatedSpecificHide);
+            }
+            ("specifichide", false) | ("shide", false) => NetworkFilterOption::Specifichide,
+            ("genericblock", true) => return Err(NetworkFilterError::NegatedGenericBlock),
+            ("genericblock", false) => NetworkFilterOption::Genericblock,
+            ("cname", true) => return Err(NetworkFilterError::NegatedCname),
+            ("cname", false) => NetworkFilterOption::Cname,
             ("document", true) | ("doc", true) => return Err(NetworkFilterError::NegatedDocument),
             ("document", false) | ("doc", false) => NetworkFilterOption::Document,
             ("image", negated) => NetworkFilterOption::Image(!negated),
@@ -284,28 +530,28 @@ fn parse_filter_options<'a>(
                 if value.is_empty() {
                     return Err(NetworkFilterError::UnrecognisedOption);
                 }
-                let methods: Vec<(bool, HttpMethod)> = value
+                let methods = value
                     .split('|')
-                    .filter_map(|method| {
+                    .map(|method| {
                         let (enabled, name) = if let Some(negated) = method.strip_prefix('~') {
                             (false, negated)
                         } else {
                             (true, method)
                         };
-                        if name.eq_ignore_ascii_case("get") {
-                            Some((enabled, HttpMethod::Get))
-                        } else if name.eq_ignore_ascii_case("head") {
-                            Some((enabled, HttpMethod::Head))
-                        } else if name.eq_ignore_ascii_case("post") {
-                            Some((enabled, HttpMethod::Post))
-                        } else {
-                            None
-                        }
+                        let method = match name.to_ascii_lowercase().as_str() {
+                            "connect" => HttpMethod::Connect,
+                            "delete" => HttpMethod::Delete,
+                            "get" => HttpMethod::Get,
+                            "head" => HttpMethod::Head,
+                            "options" => HttpMethod::Options,
+                            "patch" => HttpMethod::Patch,
+                            "post" => HttpMethod::Post,
+                            "put" => HttpMethod::Put,
+                            _ => return Err(NetworkFilterError::MethodInvalidValue),
+                        };
+                        Ok((enabled, method))
                     })
-                    .collect();
-                if methods.is_empty() {
-                    return Err(NetworkFilterError::UnrecognisedOption);
-                }
+                    .collect::<Result<Vec<_>, _>>()?;
                 NetworkFilterOption::Method(methods)
             }
             (_, _) => return Err(NetworkFilterError::UnrecognisedOption),
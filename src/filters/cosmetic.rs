@@ -5,6 +5,10 @@ use memchr::{memchr as find_char, memmem, memrchr as find_char_reverse};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::filters::abstract_network::{
+    domain_regex, split_delimited_domains, split_unescaped, unescape_option_value,
+};
+use crate::lists::ParseOptions;
 use crate::resources::PermissionMask;
 use crate::utils::Hash;
 
@@ -34,12 +38,22 @@ pub enum CosmeticFilterError {
     DoubleNegation,
     #[error("empty rule")]
     EmptyRule,
-    #[error("html filtering is unsupported")]
-    HtmlFilteringUnsupported,
+    #[error("html filters cannot be generic")]
+    GenericHtmlFilter,
+    #[error("invalid response header name")]
+    InvalidResponseHeader,
+    #[error("response header cannot be removed by untrusted filters")]
+    UntrustedResponseHeader,
     #[error("scriptlet args could not be parsed")]
     InvalidScriptletArgs,
     #[error("location modifiers are unsupported")]
     LocationModifiersUnsupported,
+    #[error("invalid regex in location")]
+    InvalidLocationRegex,
+    #[error("invalid regex in action argument")]
+    InvalidActionRegex,
+    #[error("adguard rule has no supported equivalent")]
+    AdguardSyntaxUnsupported,
     #[error("procedural filters can only accept a single CSS selector")]
     ProceduralFilterWithMultipleSelectors,
 }
@@ -60,18 +74,112 @@ pub enum CosmeticFilterAction {
     ///
     /// The specified CSS styling should be applied to matching elements in the DOM.
     Style(String),
-    /// Rules with a remove attribute action, e.g. `example.com##.ad:remove-attr(onclick)`.
+    /// Rules with a remove attribute action, e.g. `example.com##.ad:remove-attr(onclick)` or
+    /// `example.com##.ad:remove-attr(/^data-ad/)`.
     ///
-    /// Argument is the name of an HTML attribute.
+    /// Argument is the name of an HTML attribute, or a regex matching attribute names.
     ///
-    /// The attribute should be removed from matching elements in the DOM.
-    RemoveAttr(String),
-    /// Rules with a remove class action, e.g. `example.com##.ad:remove-class(advert)`.
+    /// Matching attributes should be removed from matching elements in the DOM.
+    RemoveAttr(CosmeticFilterActionArg),
+    /// Rules with a remove class action, e.g. `example.com##.ad:remove-class(advert)` or
+    /// `example.com##.ad:remove-class(/^ad-/)`.
     ///
-    /// The parameter is the name of a CSS class.
+    /// The parameter is the name of a CSS class, or a regex matching class names.
     ///
-    /// The class should be removed from matching elements in the DOM.
-    RemoveClass(String),
+    /// Matching classes should be removed from matching elements in the DOM.
+    RemoveClass(CosmeticFilterActionArg),
+}
+
+/// Argument of a [`CosmeticFilterAction::RemoveAttr`] or [`CosmeticFilterAction::RemoveClass`]
+/// action.
+///
+/// Literal arguments serialize as a plain JSON string, while regex arguments serialize as an
+/// object with `regex` and `flags` fields, suitable for passing to a JS `RegExp` constructor.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CosmeticFilterActionArg {
+    /// An exact name, e.g. `onclick` or `"onclick"`. Quotes are removed during parsing.
+    Literal(String),
+    /// A regex of the form `/regex/flags`, stored without the surrounding slashes. The `i`, `m`,
+    /// and `u` flags are supported.
+    Regex { regex: String, flags: String },
+}
+
+impl CosmeticFilterActionArg {
+    /// Parses an unquoted, quoted, or `/regex/` action argument.
+    ///
+    /// Regexes are compiled by content scripts as JS `RegExp`s, so only their delimiters and flags
+    /// are validated here; see [`is_valid_js_regex`]. The pattern itself is passed through as-is.
+    fn parse(arg: &str) -> Result<Self, CosmeticFilterError> {
+        if let Some(quote) = arg.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            let inner = arg[1..]
+                .strip_suffix(quote)
+                .ok_or(CosmeticFilterError::InvalidActionSpecifier)?;
+            let mut unquoted = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => unquoted.push(
+                        chars
+                            .next()
+                            .ok_or(CosmeticFilterError::InvalidActionSpecifier)?,
+                    ),
+                    c if c == quote => return Err(CosmeticFilterError::InvalidActionSpecifier),
+                    c => unquoted.push(c),
+                }
+            }
+            return Ok(Self::Literal(unquoted));
+        }
+
+        if let Some((regex, flags)) = arg.strip_prefix('/').and_then(|rest| rest.rsplit_once('/')) {
+            if !is_valid_js_regex(regex, flags) {
+                return Err(CosmeticFilterError::InvalidActionRegex);
+            }
+            return Ok(Self::Regex {
+                regex: regex.to_string(),
+                flags: flags.to_string(),
+            });
+        }
+
+        Ok(Self::Literal(arg.to_string()))
+    }
+}
+
+/// Returns `true` if the parts of a `/regex/flags` argument form a JS regex literal, as far as can
+/// be told without compiling it: the pattern must be non-empty and delimited by the slashes (see
+/// [`is_delimited_js_regex`]), and flags must be among `i`, `m`, and `u`, without repeats.
+fn is_valid_js_regex(regex: &str, flags: &str) -> bool {
+    let valid_flags = flags
+        .char_indices()
+        .all(|(i, c)| matches!(c, 'i' | 'm' | 'u') && !flags[..i].contains(c));
+    !regex.is_empty() && valid_flags && is_delimited_js_regex(regex)
+}
+
+/// Returns `true` if `regex` can be enclosed in slashes to form a JS regex literal, i.e. it doesn't
+/// end with an unterminated escape or contain an unescaped `/` outside of a character class.
+fn is_delimited_js_regex(regex: &str) -> bool {
+    let mut in_class = false;
+    let mut chars = regex.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if chars.next().is_none() {
+                    return false;
+                }
+            }
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => return false,
+            _ => (),
+        }
+    }
+    true
+}
+
+impl From<&str> for CosmeticFilterActionArg {
+    fn from(value: &str) -> Self {
+        Self::Literal(value.to_string())
+    }
 }
 
 impl CosmeticFilterAction {
@@ -83,21 +191,15 @@ impl CosmeticFilterAction {
     }
 
     fn new_remove_attr(attr: &str) -> Result<Self, CosmeticFilterError> {
-        Self::forbid_regex_or_quoted_args(attr)?;
-        Ok(CosmeticFilterAction::RemoveAttr(attr.to_string()))
+        Ok(CosmeticFilterAction::RemoveAttr(
+            CosmeticFilterActionArg::parse(attr)?,
+        ))
     }
 
     fn new_remove_class(class: &str) -> Result<Self, CosmeticFilterError> {
-        Self::forbid_regex_or_quoted_args(class)?;
-        Ok(CosmeticFilterAction::RemoveClass(class.to_string()))
-    }
-
-    /// Regex and quoted args aren't supported yet
-    fn forbid_regex_or_quoted_args(arg: &str) -> Result<(), CosmeticFilterError> {
-        if arg.starts_with('/') || arg.starts_with('\"') || arg.starts_with('\'') {
-            return Err(CosmeticFilterError::UnsupportedSyntax);
-        }
-        Ok(())
+        Ok(CosmeticFilterAction::RemoveClass(
+            CosmeticFilterActionArg::parse(class)?,
+        ))
     }
 }
 
@@ -108,6 +210,7 @@ bitflags::bitflags! {
     pub struct CosmeticFilterMask: u8 {
         const UNHIDE = 1 << 0;
         const SCRIPT_INJECT = 1 << 1;
+        const HTML_FILTER = 1 << 2;
 
         // Careful with checking for NONE - will always match
         const NONE = 0;
@@ -122,6 +225,13 @@ pub struct CosmeticFilter {
     pub mask: CosmeticFilterMask,
     pub not_entities: Option<Vec<Hash>>,
     pub not_hostnames: Option<Vec<Hash>>,
+    /// Regexes from locations of the form `/regex/`, without the surrounding slashes.
+    pub hostname_regexes: Option<Vec<String>>,
+    /// Regexes from locations of the form `~/regex/`, without the surrounding slashes.
+    pub not_hostname_regexes: Option<Vec<String>>,
+    /// The value of a `[$path=...]` modifier, which is either a substring or a `/regex/` to be
+    /// matched against the path and query of the page URL.
+    pub path: Option<String>,
     pub raw_line: Option<Box<String>>,
     pub selector: Vec<CosmeticFilterOperator>,
     pub action: Option<CosmeticFilterAction>,
@@ -144,6 +254,15 @@ pub enum CosmeticFilterOperator {
     MinTextLength(String),
     Upward(String),
     Xpath(String),
+    MatchesMedia(String),
+    MatchesProp(String),
+    /// Comma-separated attribute names to watch, or empty to watch all attributes.
+    WatchAttr(String),
+    /// From `:others()`, which has no argument.
+    Others,
+    /// The lowercase name of a header to remove from a document's response, from HTML filters
+    /// like `example.com##^responseheader(refresh)`. Never combined with other operators.
+    ResponseHeader(String),
 }
 
 pub(crate) enum CosmeticFilterLocationType {
@@ -151,7 +270,8 @@ pub(crate) enum CosmeticFilterLocationType {
     NotEntity,
     Hostname,
     NotHostname,
-    Unsupported,
+    HostnameRegex,
+    NotHostnameRegex,
 }
 
 /// Contains hashes of all of the comma separated location items that were populated before the
@@ -166,48 +286,89 @@ struct CosmeticFilterLocations {
     hostnames: Option<Vec<Hash>>,
     /// Locations of the form `~hostname`
     not_hostnames: Option<Vec<Hash>>,
+    /// Locations of the form `/regex/`
+    hostname_regexes: Option<Vec<String>>,
+    /// Locations of the form `~/regex/`
+    not_hostname_regexes: Option<Vec<String>>,
+    /// The value of a `[$path=...]` modifier
+    path: Option<String>,
 }
 
 impl CosmeticFilter {
+    /// Splits the comma-separated locations before the `##` or `#@#` separator, i.e. everything
+    /// after any `[$...]` modifiers. Regex locations like `/^example\.(com|net)$/` may contain
+    /// commas.
     #[inline]
     pub(crate) fn locations_before_sharp(
-        line: &str,
-        sharp_index: usize,
+        locations: &str,
     ) -> impl Iterator<Item = (CosmeticFilterLocationType, &str)> {
-        line[0..sharp_index].split(',').filter_map(|part| {
+        split_delimited_domains(locations, b',').filter_map(|part| {
             if part.is_empty() {
                 return None;
             }
-            let hostname = part;
-            let negation = hostname.starts_with('~');
-            let entity = hostname.ends_with(".*");
-            let start = if negation { 1 } else { 0 };
-            let end = if entity {
-                hostname.len() - 2
-            } else {
-                hostname.len()
-            };
-            let location = &hostname[start..end];
+            let negation = part.starts_with('~');
+            let hostname = &part[usize::from(negation)..];
             // AdGuard regex syntax
-            if location.starts_with('/') {
-                return Some((CosmeticFilterLocationType::Unsupported, part));
+            if let Some(pattern) = location_regex(hostname) {
+                return Some(if negation {
+                    (CosmeticFilterLocationType::NotHostnameRegex, pattern)
+                } else {
+                    (CosmeticFilterLocationType::HostnameRegex, pattern)
+                });
             }
-            Some(match (negation, entity) {
-                (true, true) => (CosmeticFilterLocationType::NotEntity, location),
-                (true, false) => (CosmeticFilterLocationType::NotHostname, location),
-                (false, true) => (CosmeticFilterLocationType::Entity, location),
-                (false, false) => (CosmeticFilterLocationType::Hostname, location),
+            let location = hostname.strip_suffix(".*");
+            Some(match (negation, location) {
+                (true, Some(location)) => (CosmeticFilterLocationType::NotEntity, location),
+                (true, None) => (CosmeticFilterLocationType::NotHostname, hostname),
+                (false, Some(location)) => (CosmeticFilterLocationType::Entity, location),
+                (false, None) => (CosmeticFilterLocationType::Hostname, hostname),
             })
         })
     }
 
+    /// Parses AdGuard-style modifiers at the start of a cosmetic filter rule, e.g.
+    /// `[$path=/watch]example.com##.ad`. Only `path` is supported.
+    ///
+    /// On success, returns the value of the `path` modifier if there was one, along with the
+    /// remaining part of `before_sharp` that contains the rule's locations.
+    fn parse_location_modifiers(
+        before_sharp: &str,
+    ) -> Result<(Option<String>, &str), CosmeticFilterError> {
+        let Some(modifiers) = before_sharp.strip_prefix("[$") else {
+            if before_sharp.starts_with('[') {
+                return Err(CosmeticFilterError::LocationModifiersUnsupported);
+            }
+            return Ok((None, before_sharp));
+        };
+        // `split_unescaped` always yields at least one item
+        let inner = split_unescaped(modifiers, b']').next().unwrap_or_default();
+        let Some(locations) = modifiers[inner.len()..].strip_prefix(']') else {
+            return Err(CosmeticFilterError::LocationModifiersUnsupported);
+        };
+
+        let mut path = None;
+        for modifier in split_unescaped(inner, b',') {
+            match modifier.split_once('=') {
+                Some(("path", value)) if !value.is_empty() && path.is_none() => {
+                    let value = unescape_option_value(value);
+                    if let Some(pattern) = location_regex(&value) {
+                        crate::regex_manager::compile_option_regex(pattern, false)
+                            .map_err(|_| CosmeticFilterError::InvalidLocationRegex)?;
+                    }
+                    path = Some(value.into_owned());
+                }
+                _ => return Err(CosmeticFilterError::LocationModifiersUnsupported),
+            }
+        }
+        Ok((path, locations))
+    }
+
     /// Parses the contents of a cosmetic filter rule up to the `##` or `#@#` separator.
     ///
     /// On success, returns hashes of all the comma separated location items that were populated in
-    /// the rule.
+    /// the rule, along with any regex locations or `[$path=...]` modifier.
     ///
-    /// This should only be called if `sharp_index` is greater than 0, in which case all four are
-    /// guaranteed to be `None`.
+    /// This should only be called if `sharp_index` is greater than 0.
     #[inline]
     fn parse_before_sharp(
         line: &str,
@@ -217,40 +378,41 @@ impl CosmeticFilter {
         let mut not_entities_vec = vec![];
         let mut hostnames_vec = vec![];
         let mut not_hostnames_vec = vec![];
+        let mut hostname_regexes_vec = vec![];
+        let mut not_hostname_regexes_vec = vec![];
 
-        if line.starts_with('[') {
-            return Err(CosmeticFilterError::LocationModifiersUnsupported);
-        }
+        let (path, locations) = Self::parse_location_modifiers(&line[..sharp_index])?;
 
-        let mut any_unsupported = false;
-        for (location_type, location) in Self::locations_before_sharp(line, sharp_index) {
-            let hash = if location.is_ascii() {
-                crate::utils::fast_hash(location)
-            } else {
-                match idna::domain_to_ascii(location) {
-                    Ok(x) if !x.is_empty() => crate::utils::fast_hash(&x),
-                    _ => return Err(CosmeticFilterError::PunycodeError),
+        for (location_type, location) in Self::locations_before_sharp(locations) {
+            if let CosmeticFilterLocationType::HostnameRegex
+            | CosmeticFilterLocationType::NotHostnameRegex = location_type
+            {
+                crate::regex_manager::compile_option_regex(location, false)
+                    .map_err(|_| CosmeticFilterError::InvalidLocationRegex)?;
+            }
+            let hash = || {
+                if location.is_ascii() {
+                    Ok(crate::utils::fast_hash(location))
+                } else {
+                    match idna::domain_to_ascii(location) {
+                        Ok(x) if !x.is_empty() => Ok(crate::utils::fast_hash(&x)),
+                        _ => Err(CosmeticFilterError::PunycodeError),
+                    }
                 }
             };
             match location_type {
-                CosmeticFilterLocationType::NotEntity => not_entities_vec.push(hash),
-                CosmeticFilterLocationType::NotHostname => not_hostnames_vec.push(hash),
-                CosmeticFilterLocationType::Entity => entities_vec.push(hash),
-                CosmeticFilterLocationType::Hostname => hostnames_vec.push(hash),
-                CosmeticFilterLocationType::Unsupported => {
-                    any_unsupported = true;
+                CosmeticFilterLocationType::NotEntity => not_entities_vec.push(hash()?),
+                CosmeticFilterLocationType::NotHostname => not_hostnames_vec.push(hash()?),
+                CosmeticFilterLocationType::Entity => entities_vec.push(hash()?),
+                CosmeticFilterLocationType::Hostname => hostnames_vec.push(hash()?),
+                CosmeticFilterLocationType::HostnameRegex => {
+                    hostname_regexes_vec.push(location.to_string())
+                }
+                CosmeticFilterLocationType::NotHostnameRegex => {
+                    not_hostname_regexes_vec.push(location.to_string())
                 }
             }
         }
-        // Discard the rule altogether if there are no supported location types
-        if any_unsupported
-            && hostnames_vec.is_empty()
-            && entities_vec.is_empty()
-            && not_hostnames_vec.is_empty()
-            && not_entities_vec.is_empty()
-        {
-            return Err(CosmeticFilterError::UnsupportedSyntax);
-        }
 
         /// Sorts `vec` and wraps it in `Some` if it's not empty, or returns `None` if it is.
         #[inline]
@@ -267,12 +429,17 @@ impl CosmeticFilter {
         let hostnames = sorted_or_none(hostnames_vec);
         let not_entities = sorted_or_none(not_entities_vec);
         let not_hostnames = sorted_or_none(not_hostnames_vec);
+        let hostname_regexes = sorted_or_none(hostname_regexes_vec);
+        let not_hostname_regexes = sorted_or_none(not_hostname_regexes_vec);
 
         Ok(CosmeticFilterLocations {
             entities,
             not_entities,
             hostnames,
             not_hostnames,
+            hostname_regexes,
+            not_hostname_regexes,
+            path,
         })
     }
 
@@ -332,6 +499,27 @@ impl CosmeticFilter {
         })
     }
 
+    /// Translates the contents of an AdGuard `#%#//scriptlet(...)` rule into the equivalent
+    /// contents of a uBO `+js(...)` operator.
+    ///
+    /// Arbitrary JavaScript and scriptlets without a known uBO equivalent can't be translated.
+    fn translate_adguard_scriptlet(after_sharp: &str) -> Result<String, CosmeticFilterError> {
+        let args = after_sharp
+            .strip_prefix("//scriptlet(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or(CosmeticFilterError::AdguardSyntaxUnsupported)?;
+        let mut args = crate::resources::parse_adguard_scriptlet_args(args)
+            .ok_or(CosmeticFilterError::InvalidScriptletArgs)?;
+        let name = args
+            .first_mut()
+            .ok_or(CosmeticFilterError::InvalidScriptletArgs)?;
+        *name = crate::resources::ubo_scriptlet_name(name)
+            .ok_or(CosmeticFilterError::AdguardSyntaxUnsupported)?
+            .to_string();
+        crate::resources::format_scriptlet_args(&args)
+            .ok_or(CosmeticFilterError::InvalidScriptletArgs)
+    }
+
     /// Parses the contents of a cosmetic filter rule following the `##` or `#@#` separator.
     ///
     /// On success, returns `selector` and `style` according to the rule.
@@ -342,10 +530,6 @@ impl CosmeticFilter {
     fn parse_after_sharp_nonscript(
         after_sharp: &str,
     ) -> Result<(&str, Option<CosmeticFilterAction>), CosmeticFilterError> {
-        if after_sharp.starts_with('^') {
-            return Err(CosmeticFilterError::HtmlFilteringUnsupported);
-        }
-
         if let Some(result) = Self::parse_abp_style_injection(after_sharp) {
             return result;
         }
@@ -398,6 +582,44 @@ impl CosmeticFilter {
         Ok((selector, action))
     }
 
+    /// Parses the contents of an HTML filter rule following the `##^` or `#@#^` separator.
+    ///
+    /// Returns the selector of the rule, which is a single
+    /// [`CosmeticFilterOperator::ResponseHeader`] for `responseheader(...)` rules.
+    fn parse_html_filter(
+        html_filter: &str,
+        permission: PermissionMask,
+    ) -> Result<Vec<CosmeticFilterOperator>, CosmeticFilterError> {
+        /// Response headers that can be removed by filters from untrusted sources, as in uBlock
+        /// Origin.
+        const SAFE_RESPONSE_HEADERS: &[&str] = &["location", "refresh", "report-to", "set-cookie"];
+
+        if let Some(header) = html_filter
+            .strip_prefix("responseheader(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let header = header.trim().to_ascii_lowercase();
+            if header.is_empty()
+                || !header
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+            {
+                return Err(CosmeticFilterError::InvalidResponseHeader);
+            }
+            if permission.is_default() && !SAFE_RESPONSE_HEADERS.contains(&header.as_str()) {
+                return Err(CosmeticFilterError::UntrustedResponseHeader);
+            }
+            return Ok(vec![CosmeticFilterOperator::ResponseHeader(header)]);
+        }
+
+        let (selector, action) = Self::parse_after_sharp_nonscript(html_filter)?;
+        if action.is_some() {
+            // Matching elements are always removed from the document.
+            return Err(CosmeticFilterError::InvalidActionSpecifier);
+        }
+        validate_css_selector(selector, false)
+    }
+
     /// Returns the CSS selector, for rules which only consist of a CSS selector.
     /// If a rule contains procedural operators, this method will return `None`.
     pub fn plain_css_selector(&self) -> Option<&str> {
@@ -419,6 +641,25 @@ impl CosmeticFilter {
         debug: bool,
         permission: PermissionMask,
     ) -> Result<CosmeticFilter, CosmeticFilterError> {
+        Self::parse_with_options(
+            line,
+            debug,
+            ParseOptions {
+                permissions: permission,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Like [`CosmeticFilter::parse`], but the permissions are taken from `opts`, along with
+    /// [`ParseOptions::allow_generic_procedural_filters`].
+    pub fn parse_with_options(
+        line: &str,
+        debug: bool,
+        opts: ParseOptions,
+    ) -> Result<CosmeticFilter, CosmeticFilterError> {
+        let permission = opts.permissions;
+        let allow_generic_action = opts.allow_generic_procedural_filters;
         let mut mask = CosmeticFilterMask::NONE;
         if let Some(sharp_index) = find_char(b'#', line.as_bytes()) {
             let after_sharp_index = sharp_index + 1;
@@ -429,6 +670,8 @@ impl CosmeticFilter {
             };
 
             let mut translate_abp_syntax = false;
+            let mut adguard_script_inject = false;
+            let mut adguard_style_inject = false;
 
             // Consume filter options embedded in the `##` marker:
             let mut between_sharps = &line[after_sharp_index..second_sharp_index];
@@ -440,17 +683,18 @@ impl CosmeticFilter {
                 mask |= CosmeticFilterMask::UNHIDE;
                 between_sharps = &between_sharps[1..];
             }
-            if between_sharps.starts_with('%') {
-                // AdGuard script injection syntax - not supported
+            if let Some(rest) = between_sharps.strip_prefix('%') {
+                // AdGuard script injection syntax, translated when using `//scriptlet(...)`
                 // `#%#` / `#@%#`
-                return Err(CosmeticFilterError::UnsupportedSyntax);
-            }
-            if between_sharps.starts_with('$') {
-                // AdGuard `:style` syntax - not supported for now
-                // `#$?#` for CSS rules, `#@$?#` — for exceptions
-                return Err(CosmeticFilterError::UnsupportedSyntax);
+                adguard_script_inject = true;
+                between_sharps = rest;
+            } else if let Some(rest) = between_sharps.strip_prefix('$') {
+                // AdGuard CSS injection syntax, translated to a `:style` action
+                // `#$#` / `#$?#` for CSS rules, `#@$#` / `#@$?#` — for exceptions
+                adguard_style_inject = true;
+                between_sharps = rest;
             }
-            if between_sharps.starts_with('?') {
+            if !adguard_script_inject && between_sharps.starts_with('?') {
                 // ABP/ADG extended CSS syntax:
                 // - #?# — for element hiding, #@?# — for exceptions
                 translate_abp_syntax = true;
@@ -467,6 +711,9 @@ impl CosmeticFilter {
                 not_entities,
                 hostnames,
                 not_hostnames,
+                hostname_regexes,
+                not_hostname_regexes,
+                path,
             } = if sharp_index > 0 {
                 CosmeticFilter::parse_before_sharp(line, sharp_index)?
             } else {
@@ -479,35 +726,62 @@ impl CosmeticFilter {
                 return Err(CosmeticFilterError::EmptyRule);
             }
 
-            let (selector, action) = if line.len() - suffix_start_index > 4
+            let script_inject_args = if adguard_script_inject {
+                Some(CosmeticFilter::translate_adguard_scriptlet(after_sharp)?)
+            } else if line.len() - suffix_start_index > 4
                 && line[suffix_start_index..].starts_with("+js(")
                 && line.ends_with(')')
             {
-                if sharp_index == 0 {
-                    return Err(CosmeticFilterError::GenericScriptInject);
-                }
                 let args = &line[suffix_start_index + 4..line.len() - 1];
                 if crate::resources::parse_scriptlet_args(args).is_none() {
                     return Err(CosmeticFilterError::InvalidScriptletArgs);
                 }
+                Some(String::from(args))
+            } else {
+                None
+            };
+
+            let (selector, action) = if let Some(args) = script_inject_args {
+                if sharp_index == 0 {
+                    return Err(CosmeticFilterError::GenericScriptInject);
+                }
                 mask |= CosmeticFilterMask::SCRIPT_INJECT;
                 (
                     // TODO: overloading `CssSelector` here is not ideal.
-                    vec![CosmeticFilterOperator::CssSelector(String::from(
-                        &line[suffix_start_index + 4..line.len() - 1],
-                    ))],
+                    vec![CosmeticFilterOperator::CssSelector(args)],
+                    None,
+                )
+            } else if adguard_style_inject {
+                // `@media` queries and other at-rules have no `:style` equivalent.
+                if after_sharp.starts_with('@') {
+                    return Err(CosmeticFilterError::AdguardSyntaxUnsupported);
+                }
+                let (selector, action) = CosmeticFilter::parse_abp_style_injection(after_sharp)
+                    .ok_or(CosmeticFilterError::AdguardSyntaxUnsupported)??;
+                let validated_selector = validate_css_selector(selector, translate_abp_syntax)?;
+                if sharp_index == 0 && !allow_generic_action {
+                    return Err(CosmeticFilterError::GenericAction);
+                }
+                (validated_selector, action)
+            } else if let Some(html_filter) = after_sharp.strip_prefix('^') {
+                if sharp_index == 0 {
+                    return Err(CosmeticFilterError::GenericHtmlFilter);
+                }
+                mask |= CosmeticFilterMask::HTML_FILTER;
+                (
+                    CosmeticFilter::parse_html_filter(html_filter, permission)?,
                     None,
                 )
             } else {
                 let (selector, action) = CosmeticFilter::parse_after_sharp_nonscript(after_sharp)?;
                 let validated_selector = validate_css_selector(selector, translate_abp_syntax)?;
-                if sharp_index == 0 && action.is_some() {
+                if sharp_index == 0 && action.is_some() && !allow_generic_action {
                     return Err(CosmeticFilterError::GenericAction);
                 }
                 (validated_selector, action)
             };
 
-            if (not_entities.is_some() || not_hostnames.is_some())
+            if (not_entities.is_some() || not_hostnames.is_some() || not_hostname_regexes.is_some())
                 && mask.contains(CosmeticFilterMask::UNHIDE)
             {
                 return Err(CosmeticFilterError::DoubleNegation);
@@ -519,6 +793,9 @@ impl CosmeticFilter {
                 mask,
                 not_entities,
                 not_hostnames,
+                hostname_regexes,
+                not_hostname_regexes,
+                path,
                 raw_line: if debug {
                     Some(Box::new(String::from(line)))
                 } else {
@@ -529,7 +806,11 @@ impl CosmeticFilter {
                 permission,
             };
 
-            if !this.has_hostname_constraint() && this.plain_css_selector().is_none() {
+            if !this.has_hostname_constraint()
+                && this.path.is_none()
+                && this.plain_css_selector().is_none()
+                && !allow_generic_action
+            {
                 return Err(CosmeticFilterError::GenericAction);
             }
 
@@ -539,13 +820,23 @@ impl CosmeticFilter {
         }
     }
 
-    /// Any cosmetic filter rule that specifies (possibly negated) hostnames or entities has a
-    /// hostname constraint.
+    /// Any cosmetic filter rule that specifies (possibly negated) hostnames, entities, or hostname
+    /// regexes has a hostname constraint.
     pub fn has_hostname_constraint(&self) -> bool {
         self.hostnames.is_some()
             || self.entities.is_some()
             || self.not_entities.is_some()
             || self.not_hostnames.is_some()
+            || self.hostname_regexes.is_some()
+            || self.not_hostname_regexes.is_some()
+    }
+
+    /// Rules with a `[$path=...]` modifier or regex hostname locations can't be looked up by
+    /// hostname hashes alone, so they need to be checked against each page URL individually.
+    pub fn is_scoped(&self) -> bool {
+        self.path.is_some()
+            || self.hostname_regexes.is_some()
+            || self.not_hostname_regexes.is_some()
     }
 
     /// In general, adding a hostname or entity to a rule *increases* the number of situations in
@@ -557,12 +848,18 @@ impl CosmeticFilter {
     /// To account for this inconsistency, this method will generate and return the corresponding
     /// 'hidden' generic rule if one applies.
     ///
-    /// Note that this behavior is not applied to script injections or rules with actions.
+    /// Note that this behavior is not applied to script injections, HTML filters, procedural
+    /// filters, rules with actions, or scoped rules, which are matched against each page
+    /// individually.
     pub fn hidden_generic_rule(&self) -> Option<CosmeticFilter> {
-        if self.hostnames.is_some() || self.entities.is_some() {
+        if self.hostnames.is_some() || self.entities.is_some() || self.is_scoped() {
             None
         } else if (self.not_hostnames.is_some() || self.not_entities.is_some())
-            && (self.action.is_none() && !self.mask.contains(CosmeticFilterMask::SCRIPT_INJECT))
+            && self.action.is_none()
+            && self.plain_css_selector().is_some()
+            && !self
+                .mask
+                .intersects(CosmeticFilterMask::SCRIPT_INJECT | CosmeticFilterMask::HTML_FILTER)
         {
             let mut generic_rule = self.clone();
             generic_rule.not_hostnames = None;
@@ -574,6 +871,12 @@ impl CosmeticFilter {
     }
 }
 
+/// Returns the pattern from a `/regex/` location or `[$path=/regex/]` modifier, without its
+/// slashes.
+pub(crate) fn location_regex(value: &str) -> Option<&str> {
+    domain_regex(value).filter(|pattern| !pattern.is_empty())
+}
+
 /// Returns a slice of `hostname` up to and including the segment that overlaps with the first
 /// segment of `domain`, which has the effect of stripping ".com", ".co.uk", etc., as well as the
 /// public suffix itself.
@@ -661,7 +964,7 @@ mod css_validation {
 #[cfg(feature = "css-validation")]
 mod css_validation {
     //! Methods for validating CSS selectors and style rules extracted from cosmetic filter rules.
-    use super::{CosmeticFilterError, CosmeticFilterOperator};
+    use super::{CosmeticFilterError, CosmeticFilterOperator, is_valid_js_regex};
     use core::fmt::{Result as FmtResult, Write};
     use cssparser::{CowRcStr, ParseError, Parser, ParserInput, SourceLocation, ToCss, Token};
     use precomputed_hash::PrecomputedHash;
@@ -735,6 +1038,10 @@ mod css_validation {
                     | Component::NonTSPseudoClass(NonTSPseudoClass::MinTextLength(_))
                     | Component::NonTSPseudoClass(NonTSPseudoClass::Upward(_))
                     | Component::NonTSPseudoClass(NonTSPseudoClass::Xpath(_))
+                    | Component::NonTSPseudoClass(NonTSPseudoClass::MatchesMedia(_))
+                    | Component::NonTSPseudoClass(NonTSPseudoClass::MatchesProp(_))
+                    | Component::NonTSPseudoClass(NonTSPseudoClass::WatchAttr(_))
+                    | Component::NonTSPseudoClass(NonTSPseudoClass::Others)
             )
         }
 
@@ -963,7 +1270,11 @@ mod css_validation {
             <Self::Impl as selectors::parser::SelectorImpl>::NonTSPseudoClass,
             ParseError<'i, Self::Error>,
         > {
-            Ok(NonTSPseudoClass::AnythingElse(name.to_string(), None))
+            match name.as_ref() {
+                "others" => Ok(NonTSPseudoClass::Others),
+                "watch-attr" => Ok(NonTSPseudoClass::WatchAttr(String::new())),
+                _ => Ok(NonTSPseudoClass::AnythingElse(name.to_string(), None)),
+            }
         }
         fn parse_non_ts_functional_pseudo_class<'t>(
             &self,
@@ -1018,6 +1329,42 @@ mod css_validation {
                     let text = to_css_nested(arguments)?;
                     return Ok(NonTSPseudoClass::Xpath(text));
                 }
+                "matches-media" => {
+                    let text = to_css_nested(arguments)?;
+                    if text.trim().is_empty() {
+                        return Err(
+                            arguments.new_custom_error(SelectorParseErrorKind::InvalidState)
+                        );
+                    }
+                    return Ok(NonTSPseudoClass::MatchesMedia(text));
+                }
+                "matches-prop" => {
+                    let text = to_css_nested(arguments)?;
+                    if !is_valid_matches_prop_arg(&text) {
+                        return Err(
+                            arguments.new_custom_error(SelectorParseErrorKind::InvalidState)
+                        );
+                    }
+                    return Ok(NonTSPseudoClass::MatchesProp(text));
+                }
+                "watch-attr" => {
+                    let text = to_css_nested(arguments)?;
+                    if !is_valid_watch_attr_arg(&text) {
+                        return Err(
+                            arguments.new_custom_error(SelectorParseErrorKind::InvalidState)
+                        );
+                    }
+                    return Ok(NonTSPseudoClass::WatchAttr(text.trim().to_string()));
+                }
+                "others" => {
+                    let text = to_css_nested(arguments)?;
+                    if !text.trim().is_empty() {
+                        return Err(
+                            arguments.new_custom_error(SelectorParseErrorKind::InvalidState)
+                        );
+                    }
+                    return Ok(NonTSPseudoClass::Others);
+                }
                 "-abp-contains" | "-abp-has" | "-abp-properties" | "contains" | "if" | "if-not"
                 | "matches-property" | "nth-ancestor" | "properties" | "subject" | "remove"
                 | "remove-attr" | "remove-class" => {
@@ -1058,6 +1405,39 @@ mod css_validation {
         }
     }
 
+    /// `:matches-prop` takes a dot-separated property chain, optionally followed by `=` and a
+    /// value to compare against. Values of the form `/regex/flags` are compiled as JS `RegExp`s by
+    /// content scripts, so only their delimiters and flags are validated.
+    fn is_valid_matches_prop_arg(arg: &str) -> bool {
+        let (chain, value) = match arg.split_once('=') {
+            Some((chain, value)) => (chain, Some(value)),
+            None => (arg, None),
+        };
+        let chain_ok = chain.split('.').all(|prop| {
+            !prop.is_empty()
+                && prop
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        });
+        if !chain_ok {
+            return false;
+        }
+        match value.and_then(|value| value.strip_prefix('/')?.rsplit_once('/')) {
+            Some((pattern, flags)) => is_valid_js_regex(pattern, flags),
+            None => true,
+        }
+    }
+
+    /// `:watch-attr` takes an optional comma-separated list of attribute names.
+    fn is_valid_watch_attr_arg(arg: &str) -> bool {
+        let arg = arg.trim();
+        arg.is_empty()
+            || arg.split(',').all(|attr| {
+                let attr = attr.trim();
+                !attr.is_empty() && !attr.contains(char::is_whitespace)
+            })
+    }
+
     /// The `selectors` library requires an object that implements `SelectorImpl` to store data
     /// about a parsed selector. For performance, the actual content of parsed selectors is
     /// discarded as much as possible - it only matters whether the returned `Result` is `Ok` or
@@ -1180,6 +1560,14 @@ mod css_validation {
         Upward(String),
         /// The `:xpath` procedural operator.
         Xpath(String),
+        /// The `:matches-media` procedural operator.
+        MatchesMedia(String),
+        /// The `:matches-prop` procedural operator.
+        MatchesProp(String),
+        /// The `:watch-attr` procedural operator.
+        WatchAttr(String),
+        /// The `:others` procedural operator.
+        Others,
         /// Any native CSS pseudoclass that isn't a procedural operator. Second argument contains inner arguments, if present.
         AnythingElse(String, Option<String>),
     }
@@ -1207,6 +1595,10 @@ mod css_validation {
                 Self::MinTextLength(text) => write!(dest, "min-text-length({text})")?,
                 Self::Upward(text) => write!(dest, "upward({text})")?,
                 Self::Xpath(text) => write!(dest, "xpath({text})")?,
+                Self::MatchesMedia(text) => write!(dest, "matches-media({text})")?,
+                Self::MatchesProp(text) => write!(dest, "matches-prop({text})")?,
+                Self::WatchAttr(text) => write!(dest, "watch-attr({text})")?,
+                Self::Others => write!(dest, "others()")?,
                 Self::AnythingElse(name, None) => write!(dest, "{name}")?,
                 Self::AnythingElse(name, Some(args)) => write!(dest, "{name}({args})")?,
             }
@@ -1238,6 +1630,16 @@ mod css_validation {
                 }
                 NonTSPseudoClass::Upward(a) => Some(CosmeticFilterOperator::Upward(a.to_owned())),
                 NonTSPseudoClass::Xpath(a) => Some(CosmeticFilterOperator::Xpath(a.to_owned())),
+                NonTSPseudoClass::MatchesMedia(a) => {
+                    Some(CosmeticFilterOperator::MatchesMedia(a.to_owned()))
+                }
+                NonTSPseudoClass::MatchesProp(a) => {
+                    Some(CosmeticFilterOperator::MatchesProp(a.to_owned()))
+                }
+                NonTSPseudoClass::WatchAttr(a) => {
+                    Some(CosmeticFilterOperator::WatchAttr(a.to_owned()))
+                }
+                NonTSPseudoClass::Others => Some(CosmeticFilterOperator::Others),
                 _ => None,
             }
         }
@@ -1,9 +1,13 @@
 //! Flatbuffer-compatible versions of [NetworkFilter] and related functionality.
 
 use crate::filters::filter_data_context::FilterDataContext;
-use crate::filters::network::{NetworkFilterMask, NetworkFilterMaskHelper, NetworkMatchable};
+use crate::filters::network::{
+    NetworkFilterMask, NetworkFilterMaskHelper, NetworkFilterMethodMask, NetworkMatchable,
+};
 use crate::flatbuffers::unsafe_tools::fb_vector_to_slice;
 
+use std::net::IpAddr;
+
 use crate::regex_manager::RegexManager;
 use crate::request::Request;
 
@@ -143,6 +147,128 @@ impl<'a> FlatNetworkFilter<'a> {
             .map(|data| fb_vector_to_slice(data))
     }
 
+    #[inline(always)]
+    pub fn include_entities(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_entities()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn exclude_entities(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_not_entities()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn include_to_domains(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_to_domains()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn exclude_to_domains(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_not_to_domains()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn include_to_entities(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_to_entities()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn exclude_to_entities(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_not_to_entities()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
+    #[inline(always)]
+    pub fn include_domain_regexes(&self) -> Option<impl Iterator<Item = &'a str>> {
+        self.fb_filter.opt_domain_regexes().map(|v| v.iter())
+    }
+
+    #[inline(always)]
+    pub fn exclude_domain_regexes(&self) -> Option<impl Iterator<Item = &'a str>> {
+        self.fb_filter.opt_not_domain_regexes().map(|v| v.iter())
+    }
+
+    /// Returns `true` if the filter has no positive `$domain=` restriction.
+    #[inline(always)]
+    pub fn is_generic(&self) -> bool {
+        self.fb_filter.opt_domains().is_none()
+            && self.fb_filter.opt_entities().is_none()
+            && self.fb_filter.opt_domain_regexes().is_none()
+    }
+
+    #[inline(always)]
+    pub fn methods(&self) -> NetworkFilterMethodMask {
+        NetworkFilterMethodMask::from_bits_retain(self.fb_filter.methods())
+    }
+
+    #[inline(always)]
+    pub fn ip_address(&self) -> Option<&'a str> {
+        self.fb_filter.opt_ip_address()
+    }
+
+    #[inline(always)]
+    pub fn dns_type(&self) -> Option<&'a str> {
+        self.fb_filter.opt_dns_type()
+    }
+
+    #[inline(always)]
+    pub fn client(&self) -> Option<&'a str> {
+        self.fb_filter.opt_client()
+    }
+
+    /// Returns `true` if the filter can apply to a DNS query of type `query_type` from the given
+    /// client. That requires a filter which only depends on the request's hostname, like
+    /// `||example.com^` or a hosts-format rule, and whose `$dnstype` and `$client` options are
+    /// satisfied. The hostname itself is checked by [`NetworkMatchable::matches`].
+    pub fn applies_to_dns_query(
+        &self,
+        query_type: &str,
+        client_name: Option<&str>,
+        client_address: Option<IpAddr>,
+        regex_manager: &mut RegexManager,
+    ) -> bool {
+        use crate::filters::network_matchers::{check_client, check_dns_type};
+
+        self.mask
+            .contains(NetworkFilterMask::DEFAULT_OPTIONS | NetworkFilterMask::IS_HOSTNAME_ANCHOR)
+            && !self.mask.intersects(
+                NetworkFilterMask::STRICT_FIRST_PARTY
+                    | NetworkFilterMask::STRICT_THIRD_PARTY
+                    | NetworkFilterMask::IS_REGEX
+                    | NetworkFilterMask::IS_COMPLETE_REGEX
+                    | NetworkFilterMask::IS_HOSTNAME_REGEX,
+            )
+            && self.methods().is_empty()
+            && self.hostname().is_some()
+            && matches!(self.patterns(), FlatPatterns::Empty)
+            && self.is_generic()
+            && self.exclude_domains().is_none()
+            && self.exclude_entities().is_none()
+            && self.fb_filter.opt_not_domain_regexes().is_none()
+            && self.denyallow_domains().is_none()
+            && self.ip_address().is_none()
+            && check_dns_type(self.dns_type(), query_type, regex_manager)
+            && check_client(self.client(), client_name, client_address, regex_manager)
+    }
+
+    #[inline(always)]
+    pub fn denyallow_domains(&self) -> Option<&[u32]> {
+        self.fb_filter
+            .opt_denyallow_domains()
+            .map(|data| fb_vector_to_slice(data))
+    }
+
     #[inline(always)]
     pub fn hostname(&self) -> Option<&'a str> {
         if self.mask.is_hostname_anchor() {
@@ -225,6 +351,15 @@ impl<'a> FlatNetworkFilter<'a> {
         }
     }
 
+    /// Returns the `(source_index, line_number)` of the original filter rule, or
+    /// `NO_SOURCE_LINE_INFO` for both if it wasn't recorded. Unlike [`Self::get_rule_debug_info`],
+    /// this is also available outside of debug mode for filters that need it, like `replace` and
+    /// `urltransform`.
+    #[inline(always)]
+    pub(crate) fn list_position(&self) -> (u32, u32) {
+        (self.fb_filter.source_index(), self.fb_filter.line_number())
+    }
+
     /// Gets [FilterRuleDebugInfo] corresponding to the original filter rule if debug information
     /// was enabled.
     pub fn get_rule_debug_info(&self) -> Option<FilterRuleDebugInfo> {
@@ -252,21 +387,48 @@ impl NetworkFilterMaskHelper for FlatNetworkFilter<'_> {
 impl NetworkMatchable for FlatNetworkFilter<'_> {
     fn matches(&self, request: &Request, regex_manager: &mut RegexManager) -> bool {
         use crate::filters::network_matchers::{
-            check_excluded_domains_mapped, check_included_domains_mapped, check_options,
-            check_pattern,
+            check_denyallow_domains_mapped, check_excluded_domains_mapped,
+            check_included_domains_mapped, check_ip_address, check_options, check_pattern,
+            check_to_domains_mapped,
         };
-        if !check_options(self.mask, request) {
+        if !check_options(self.mask, self.methods(), request) {
+            return false;
+        }
+        if !check_ip_address(self.ip_address(), request, regex_manager) {
             return false;
         }
         if !check_included_domains_mapped(
             self.include_domains(),
+            self.include_entities(),
+            self.include_domain_regexes(),
             request,
             &self.filter_data_context.unique_domains_hashes_map,
+            regex_manager,
         ) {
             return false;
         }
         if !check_excluded_domains_mapped(
             self.exclude_domains(),
+            self.exclude_entities(),
+            self.exclude_domain_regexes(),
+            request,
+            &self.filter_data_context.unique_domains_hashes_map,
+            regex_manager,
+        ) {
+            return false;
+        }
+        if !check_denyallow_domains_mapped(
+            self.denyallow_domains(),
+            request,
+            &self.filter_data_context.unique_domains_hashes_map,
+        ) {
+            return false;
+        }
+        if !check_to_domains_mapped(
+            self.include_to_domains(),
+            self.exclude_to_domains(),
+            self.include_to_entities(),
+            self.exclude_to_entities(),
             request,
             &self.filter_data_context.unique_domains_hashes_map,
         ) {
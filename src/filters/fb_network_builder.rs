@@ -1,5 +1,6 @@
 //! Structures to store network filters to flatbuffer
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 
 use flatbuffers::WIPOffset;
@@ -27,7 +28,19 @@ pub(crate) enum NetworkFilterListId {
     Filters = 5,
     GenericHide = 6,
     TaggedFiltersAll = 7,
-    Size = 8,
+    Replace = 8,
+    Header = 9,
+    Permissions = 10,
+    Popup = 11,
+    Popunder = 12,
+    UrlTransform = 13,
+    ElemHide = 14,
+    SpecificHide = 15,
+    GenericBlock = 16,
+    Cname = 17,
+    Dns = 18,
+    DnsRewrite = 19,
+    Size = 20,
 }
 
 struct NetworkFilterFlatEntry<'a> {
@@ -73,45 +86,44 @@ impl<'a, 'f> FlatSerialize<'a, EngineFlatBuilder<'a>>
         (network_filter, debug_data): (NetworkFilter<'f>, NetworkFilterDebugData),
         builder: &mut EngineFlatBuilder<'a>,
     ) -> WIPOffset<fb::NetworkFilter<'a>> {
-        let opt_domains = network_filter.opt_domains.as_ref().map(|v| {
-            let mut o: Vec<u32> = v
-                .iter()
-                .map(|x| builder.get_or_insert_unique_domain_hash(x))
-                .collect();
-            o.sort_unstable();
-            o.dedup();
-            FlatSerialize::serialize(o, builder)
-        });
-
-        let opt_not_domains = network_filter.opt_not_domains.as_ref().map(|v| {
-            let mut o: Vec<u32> = v
-                .iter()
-                .map(|x| builder.get_or_insert_unique_domain_hash(x))
-                .collect();
-            o.sort_unstable();
-            o.dedup();
-            FlatSerialize::serialize(o, builder)
-        });
-
-        let opt_to_domains = network_filter.opt_to_domains.as_ref().map(|v| {
-            let mut o: Vec<u32> = v
-                .iter()
-                .map(|x| builder.get_or_insert_unique_domain_hash(x))
-                .collect();
-            o.sort_unstable();
-            o.dedup();
-            FlatSerialize::serialize(o, builder)
-        });
-
-        let opt_not_to_domains = network_filter.opt_not_to_domains.as_ref().map(|v| {
-            let mut o: Vec<u32> = v
-                .iter()
-                .map(|x| builder.get_or_insert_unique_domain_hash(x))
-                .collect();
-            o.sort_unstable();
-            o.dedup();
-            FlatSerialize::serialize(o, builder)
-        });
+        let mut serialize_domains = |domains: &Option<Vec<Hash>>| {
+            domains.as_ref().map(|v| {
+                let mut o: Vec<u32> = v
+                    .iter()
+                    .map(|x| builder.get_or_insert_unique_domain_hash(x))
+                    .collect();
+                o.sort_unstable();
+                o.dedup();
+                FlatSerialize::serialize(o, builder)
+            })
+        };
+
+        let opt_domains = serialize_domains(&network_filter.opt_domains);
+        let opt_not_domains = serialize_domains(&network_filter.opt_not_domains);
+        let opt_to_domains = serialize_domains(&network_filter.opt_to_domains);
+        let opt_not_to_domains = serialize_domains(&network_filter.opt_not_to_domains);
+        let opt_denyallow_domains = serialize_domains(&network_filter.opt_denyallow_domains);
+        let opt_entities = serialize_domains(&network_filter.opt_entities);
+        let opt_not_entities = serialize_domains(&network_filter.opt_not_entities);
+        let opt_to_entities = serialize_domains(&network_filter.opt_to_entities);
+        let opt_not_to_entities = serialize_domains(&network_filter.opt_not_to_entities);
+
+        let mut serialize_regexes = |regexes: &Option<Vec<Cow<'f, str>>>| {
+            regexes.as_ref().map(|v| {
+                FlatSerialize::serialize(v.iter().map(|r| r.as_ref()).collect::<Vec<_>>(), builder)
+            })
+        };
+
+        let opt_domain_regexes = serialize_regexes(&network_filter.opt_domain_regexes);
+        let opt_not_domain_regexes = serialize_regexes(&network_filter.opt_not_domain_regexes);
+
+        let opt_ip_address = network_filter
+            .opt_ip_address
+            .map(|s| builder.create_string(s));
+        let opt_dns_type = network_filter
+            .opt_dns_type
+            .map(|s| builder.create_string(s));
+        let opt_client = network_filter.opt_client.map(|s| builder.create_string(s));
 
         let modifier_option = network_filter
             .modifier_option
@@ -151,6 +163,7 @@ impl<'a, 'f> FlatSerialize<'a, EngineFlatBuilder<'a>>
             builder.raw_builder(),
             &fb::NetworkFilterArgs {
                 mask: network_filter.mask.bits(),
+                methods: network_filter.methods.bits(),
                 single_pattern,
                 multi_patterns,
                 modifier_option,
@@ -158,6 +171,16 @@ impl<'a, 'f> FlatSerialize<'a, EngineFlatBuilder<'a>>
                 opt_not_domains,
                 opt_to_domains,
                 opt_not_to_domains,
+                opt_denyallow_domains,
+                opt_entities,
+                opt_not_entities,
+                opt_to_entities,
+                opt_not_to_entities,
+                opt_domain_regexes,
+                opt_not_domain_regexes,
+                opt_ip_address,
+                opt_dns_type,
+                opt_client,
                 hostname,
                 tag,
                 raw_line,
@@ -234,8 +257,15 @@ impl<'a, 'f> NetworkRulesBuilder<'a, 'f> {
     pub fn new(optimize: bool) -> Self {
         let lists = (0..NetworkFilterListId::Size as usize)
             .map(|list_id| {
-                // Don't optimize removeparam, since it can fuse filters without respecting distinct
-                let optimize = optimize && list_id != NetworkFilterListId::RemoveParam as usize;
+                // Don't optimize removeparam, replace, header, permissions, urltransform or
+                // dnsrewrite, since it can fuse filters without respecting distinct option values
+                let optimize = optimize
+                    && list_id != NetworkFilterListId::RemoveParam as usize
+                    && list_id != NetworkFilterListId::Replace as usize
+                    && list_id != NetworkFilterListId::Header as usize
+                    && list_id != NetworkFilterListId::Permissions as usize
+                    && list_id != NetworkFilterListId::UrlTransform as usize
+                    && list_id != NetworkFilterListId::DnsRewrite as usize;
                 NetworkFilterListBuilder::new(optimize)
             })
             .collect::<Vec<_>>();
@@ -257,12 +287,6 @@ impl<'a, 'f> NetworkRulesBuilder<'a, 'f> {
             return;
         }
 
-        // For now, filters with $to options are parsed but ignored
-        // to preserve existing matching behavior.
-        if filter.has_to_option() {
-            return;
-        }
-
         // Redirects are independent of blocking behavior.
         if filter.is_redirect() {
             self.add_filter_internal(
@@ -274,12 +298,46 @@ impl<'a, 'f> NetworkRulesBuilder<'a, 'f> {
         }
         type FilterId = NetworkFilterListId;
 
-        let list_id: FilterId = if filter.is_csp() {
+        // Page-level exceptions are independent of each other, e.g. for
+        // `@@||example.com^$generichide,genericblock`.
+        let page_exception_lists = [
+            (filter.is_generic_hide(), FilterId::GenericHide),
+            (filter.is_elem_hide(), FilterId::ElemHide),
+            (filter.is_specific_hide(), FilterId::SpecificHide),
+            (filter.is_generic_block(), FilterId::GenericBlock),
+        ]
+        .into_iter()
+        .filter_map(|(applies, list_id)| applies.then_some(list_id))
+        .collect::<Vec<_>>();
+
+        let list_id: FilterId = if filter.is_popup() {
+            FilterId::Popup
+        } else if filter.is_popunder() {
+            FilterId::Popunder
+        } else if filter.is_csp() {
             FilterId::Csp
+        } else if filter.is_permissions() {
+            FilterId::Permissions
         } else if filter.is_removeparam() {
             FilterId::RemoveParam
-        } else if filter.is_generic_hide() {
-            FilterId::GenericHide
+        } else if filter.is_replace() {
+            FilterId::Replace
+        } else if filter.is_urltransform() {
+            FilterId::UrlTransform
+        } else if filter.is_header() {
+            FilterId::Header
+        } else if filter.is_cname() {
+            FilterId::Cname
+        } else if filter.is_dnsrewrite() {
+            FilterId::DnsRewrite
+        } else if filter.is_dns_only() {
+            // Kept apart from `Filters` and `Exceptions`, which also apply to regular requests.
+            FilterId::Dns
+        } else if !page_exception_lists.is_empty() {
+            for list_id in page_exception_lists {
+                self.add_filter_internal(filter.clone(), debug_data.clone(), list_id, builder);
+            }
+            return;
         } else if filter.is_exception() {
             FilterId::Exceptions
         } else if filter.is_important() {
@@ -1,8 +1,9 @@
 //! Contains representations and standalone behaviors of individual filter rules.
 
-mod abstract_network;
 mod network_matchers;
 
+pub(crate) mod abstract_network;
+
 pub mod cosmetic;
 pub(crate) mod fb_builder;
 pub(crate) mod fb_network;
@@ -7,6 +7,7 @@ use rustc_hash::FxHasher;
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::hash::Hasher;
+use std::net::IpAddr;
 use std::sync::LazyLock;
 use thiserror::Error;
 
@@ -21,8 +22,15 @@ use crate::regex_manager::RegexManager;
 use crate::request;
 use crate::utils::{self, Hash, TokensBuffer};
 
-/// For now, only support `$removeparam` with simple alphanumeric/dash/underscore patterns.
-static VALID_PARAM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_\-]+$").unwrap());
+/// Literal `$removeparam` names are restricted to characters that can appear unencoded in a query
+/// parameter key.
+static VALID_PARAM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z0-9_\-.%\[\]]+$").unwrap());
+
+/// A single `Permissions-Policy` directive from a `$permissions` option, e.g. `camera=()`,
+/// `autoplay=*`, or `geolocation=(self "https://example.com")`.
+static VALID_PERMISSIONS_DIRECTIVE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-z][a-z0-9-]*=(?:\*|\([^()]*\))$").unwrap());
 
 bitflags::bitflags! {
   /// Features that are properties used to classify the filter, but not stored
@@ -39,6 +47,17 @@ bitflags::bitflags! {
     /// Specifies that a redirect rule should also create a corresponding block rule.
     /// This is used to avoid returning two separate rules from `NetworkFilter::parse`.
     const ALSO_BLOCK_REDIRECT = 1 << 5;
+    const IS_REPLACE = 1 << 6;
+    const IS_HEADER = 1 << 7;
+    const IS_PERMISSIONS = 1 << 8;
+    const IS_POPUP = 1 << 9;
+    const IS_POPUNDER = 1 << 10;
+    const IS_URLTRANSFORM = 1 << 11;
+    const ELEM_HIDE = 1 << 12;
+    const SPECIFIC_HIDE = 1 << 13;
+    const GENERIC_BLOCK = 1 << 14;
+    const CNAME = 1 << 15;
+    const IS_DNSREWRITE = 1 << 16;
   }
 }
 
@@ -67,18 +86,90 @@ pub enum NetworkFilterError {
     NegatedAll,
     #[error("generichide without exception")]
     GenericHideWithoutException,
+    #[error("negated elemhide")]
+    NegatedElemHide,
+    #[error("elemhide without exception")]
+    ElemHideWithoutException,
+    #[error("negated specifichide")]
+    NegatedSpecificHide,
+    #[error("specifichide without exception")]
+    SpecificHideWithoutException,
+    #[error("negated genericblock")]
+    NegatedGenericBlock,
+    #[error("genericblock without exception")]
+    GenericBlockWithoutException,
+    #[error("negated cname")]
+    NegatedCname,
+    #[error("cname without exception")]
+    CnameWithoutException,
     #[error("method with generichide")]
     MethodWithGenerichide,
+    #[error("empty method")]
+    EmptyMethod,
+    #[error("invalid method value")]
+    MethodInvalidValue,
     #[error("empty redirection")]
     EmptyRedirection,
     #[error("empty removeparam")]
     EmptyRemoveparam,
     #[error("negated removeparam")]
     NegatedRemoveparam,
-    #[error("removeparam with exception")]
-    RemoveparamWithException,
     #[error("removeparam regex unsupported")]
     RemoveparamRegexUnsupported,
+    #[error("invalid removeparam value")]
+    RemoveparamInvalidValue,
+    #[error("empty replace")]
+    EmptyReplace,
+    #[error("negated replace")]
+    NegatedReplace,
+    #[error("replace regex unsupported")]
+    ReplaceRegexUnsupported,
+    #[error("invalid replace value")]
+    ReplaceInvalidValue,
+    #[error("empty urltransform")]
+    EmptyUrlTransform,
+    #[error("negated urltransform")]
+    NegatedUrlTransform,
+    #[error("urltransform regex unsupported")]
+    UrlTransformRegexUnsupported,
+    #[error("invalid urltransform value")]
+    UrlTransformInvalidValue,
+    #[error("urltransform from untrusted list")]
+    UntrustedUrlTransform,
+    #[error("empty header")]
+    EmptyHeader,
+    #[error("negated header")]
+    NegatedHeader,
+    #[error("header regex unsupported")]
+    HeaderRegexUnsupported,
+    #[error("invalid header value")]
+    HeaderInvalidValue,
+    #[error("empty ipaddress")]
+    EmptyIpAddress,
+    #[error("negated ipaddress")]
+    NegatedIpAddress,
+    #[error("ipaddress regex unsupported")]
+    IpAddressRegexUnsupported,
+    #[error("invalid ipaddress value")]
+    IpAddressInvalidValue,
+    #[error("negated dnstype")]
+    NegatedDnsType,
+    #[error("invalid dnstype value")]
+    DnsTypeInvalidValue,
+    #[error("negated client")]
+    NegatedClient,
+    #[error("invalid client value")]
+    ClientInvalidValue,
+    #[error("empty dnsrewrite")]
+    EmptyDnsRewrite,
+    #[error("negated dnsrewrite")]
+    NegatedDnsRewrite,
+    #[error("invalid dnsrewrite value")]
+    DnsRewriteInvalidValue,
+    #[error("dns option with unsupported option")]
+    DnsOptionWithUnsupportedOption,
+    #[error("dns option without hostname")]
+    DnsOptionWithoutHostname,
     #[error("redirection url invalid")]
     RedirectionUrlInvalid,
     #[error("multiple modifier options")]
@@ -95,10 +186,24 @@ pub enum NetworkFilterError {
     PunycodeError,
     #[error("csp with content type")]
     CspWithContentType,
+    #[error("negated permissions")]
+    NegatedPermissions,
+    #[error("invalid permissions value")]
+    PermissionsInvalidValue,
+    #[error("permissions with content type")]
+    PermissionsWithContentType,
+    #[error("negated popup")]
+    NegatedPopup,
+    #[error("popup with content type")]
+    PopupWithContentType,
     #[error("match-case without full regex")]
     MatchCaseWithoutFullRegex,
     #[error("no supported domains")]
     NoSupportedDomains,
+    #[error("negated denyallow")]
+    NegatedDenyallow,
+    #[error("invalid denyallow value")]
+    DenyallowInvalidValue,
 }
 
 bitflags::bitflags! {
@@ -122,6 +227,9 @@ bitflags::bitflags! {
         const MATCH_CASE = 1 << 14;
         const THIRD_PARTY = 1 << 16;
         const FIRST_PARTY = 1 << 17;
+        // Unlike the above, these are unset by default and restrict matching when set.
+        const STRICT_FIRST_PARTY = 1 << 15;
+        const STRICT_THIRD_PARTY = 1 << 23;
         // Full document rules are not implied by negated types.
         const FROM_DOCUMENT = 1 << 29;
 
@@ -137,14 +245,6 @@ bitflags::bitflags! {
         // "Other" network request types
         const UNMATCHED = 1 << 25;
 
-        const FROM_GET = 1 << 26;
-        const FROM_HEAD = 1 << 27;
-        const FROM_POST = 1 << 30;
-
-        const FROM_ANY_METHODS = Self::FROM_GET.bits() |
-            Self::FROM_HEAD.bits() |
-            Self::FROM_POST.bits();
-
         // Includes all request types that are implied by any negated types.
         const FROM_NETWORK_TYPES = Self::FROM_FONT.bits() |
             Self::FROM_IMAGE.bits() |
@@ -175,10 +275,27 @@ bitflags::bitflags! {
     }
 }
 
-impl NetworkFilterMask {
+bitflags::bitflags! {
+    /// HTTP methods from a `$method=` option. Kept apart from [`NetworkFilterMask`], which has no
+    /// room left for every method. Empty if the filter applies to any method.
+    #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+    #[serde(transparent)]
+    pub struct NetworkFilterMethodMask: u8 {
+        const CONNECT = 1; // 1 << 0;
+        const DELETE = 1 << 1;
+        const GET = 1 << 2;
+        const HEAD = 1 << 3;
+        const OPTIONS = 1 << 4;
+        const PATCH = 1 << 5;
+        const POST = 1 << 6;
+        const PUT = 1 << 7;
+    }
+}
+
+impl NetworkFilterMethodMask {
     #[inline]
     pub(crate) fn check_method_allowed(self, method: Option<&request::RequestMethod>) -> bool {
-        if !self.intersects(Self::FROM_ANY_METHODS) {
+        if self.is_empty() {
             return true;
         }
         let Some(method) = method else {
@@ -192,6 +309,22 @@ impl NetworkFilterMask {
     }
 }
 
+impl From<&request::RequestMethod> for NetworkFilterMethodMask {
+    fn from(method: &request::RequestMethod) -> NetworkFilterMethodMask {
+        match method {
+            request::RequestMethod::Connect => NetworkFilterMethodMask::CONNECT,
+            request::RequestMethod::Delete => NetworkFilterMethodMask::DELETE,
+            request::RequestMethod::Get => NetworkFilterMethodMask::GET,
+            request::RequestMethod::Head => NetworkFilterMethodMask::HEAD,
+            request::RequestMethod::Options => NetworkFilterMethodMask::OPTIONS,
+            request::RequestMethod::Patch => NetworkFilterMethodMask::PATCH,
+            request::RequestMethod::Post => NetworkFilterMethodMask::POST,
+            request::RequestMethod::Put => NetworkFilterMethodMask::PUT,
+            request::RequestMethod::Other => NetworkFilterMethodMask::empty(),
+        }
+    }
+}
+
 pub trait NetworkFilterMaskHelper {
     fn has_flag(&self, v: NetworkFilterMask) -> bool;
 
@@ -250,6 +383,16 @@ pub trait NetworkFilterMaskHelper {
         self.has_flag(NetworkFilterMask::FIRST_PARTY)
     }
 
+    #[inline]
+    fn strict_first_party(&self) -> bool {
+        self.has_flag(NetworkFilterMask::STRICT_FIRST_PARTY)
+    }
+
+    #[inline]
+    fn strict_third_party(&self) -> bool {
+        self.has_flag(NetworkFilterMask::STRICT_THIRD_PARTY)
+    }
+
     #[inline]
     fn for_http(&self) -> bool {
         self.has_flag(NetworkFilterMask::FROM_HTTP)
@@ -287,17 +430,6 @@ impl fmt::Display for NetworkFilterMask {
     }
 }
 
-impl From<&request::RequestMethod> for NetworkFilterMask {
-    fn from(method: &request::RequestMethod) -> NetworkFilterMask {
-        match method {
-            request::RequestMethod::Get => NetworkFilterMask::FROM_GET,
-            request::RequestMethod::Head => NetworkFilterMask::FROM_HEAD,
-            request::RequestMethod::Post => NetworkFilterMask::FROM_POST,
-            _ => NetworkFilterMask::NONE,
-        }
-    }
-}
-
 impl From<&request::RequestType> for NetworkFilterMask {
     fn from(request_type: &request::RequestType) -> NetworkFilterMask {
         match request_type {
@@ -401,17 +533,628 @@ impl FilterPart<'_> {
     }
 }
 
+/// Interpretation of the value of a `$removeparam` option, following uBlock Origin's syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RemoveparamValue<'a> {
+    /// `true` if the value was prefixed with `~`, in which case every parameter that does _not_
+    /// match should be removed instead.
+    pub(crate) negated: bool,
+    pub(crate) matcher: RemoveparamMatcher<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RemoveparamMatcher<'a> {
+    /// A bare `$removeparam`, which matches every parameter.
+    All,
+    /// `$removeparam=name`, which matches parameters with exactly the given name.
+    Name(&'a str),
+    /// `$removeparam=/regex/flags`, which matches parameters whose `name=value` representation
+    /// matches the regex. `i` is the only supported flag.
+    Regex {
+        pattern: Cow<'a, str>,
+        ignore_case: bool,
+    },
+}
+
+impl<'a> RemoveparamValue<'a> {
+    /// Interprets the raw value of a `$removeparam` option, as stored in
+    /// [`NetworkFilter::modifier_option`]. `None` represents a bare `$removeparam`.
+    ///
+    /// Regexes are not compiled here; see [`crate::regex_manager::compile_option_regex`].
+    pub(crate) fn parse(value: Option<&'a str>) -> Result<Self, NetworkFilterError> {
+        let Some(value) = value else {
+            return Ok(Self {
+                negated: false,
+                matcher: RemoveparamMatcher::All,
+            });
+        };
+        let (negated, value) = match value.strip_prefix('~') {
+            Some(negated_value) => (true, negated_value),
+            None => (false, value),
+        };
+        if value.is_empty() {
+            return Err(NetworkFilterError::EmptyRemoveparam);
+        }
+
+        let matcher = if let Some((pattern, ignore_case)) = parse_option_regex(
+            value,
+            NetworkFilterError::RemoveparamInvalidValue,
+            NetworkFilterError::RemoveparamRegexUnsupported,
+        )? {
+            RemoveparamMatcher::Regex {
+                pattern,
+                ignore_case,
+            }
+        } else if VALID_PARAM.is_match(value) {
+            RemoveparamMatcher::Name(value)
+        } else {
+            return Err(NetworkFilterError::RemoveparamInvalidValue);
+        };
+
+        Ok(Self { negated, matcher })
+    }
+}
+
+/// Interprets a `/regex/flags` option value, as used by `$removeparam`, `$header` and `$ipaddress`.
+/// Returns `None` if `value` isn't of that form, or else the unescaped pattern and whether it
+/// ignores case. `i` is the only supported flag.
+///
+/// `invalid` is returned for unsupported flags, and `unsupported` if regexes can't be used because
+/// the `full-regex-handling` feature is disabled.
+fn parse_option_regex(
+    value: &str,
+    invalid: NetworkFilterError,
+    unsupported: NetworkFilterError,
+) -> Result<Option<(Cow<'_, str>, bool)>, NetworkFilterError> {
+    if value.len() < 2 || !value.starts_with('/') {
+        return Ok(None);
+    }
+    let Some(end) = value.rfind('/').filter(|end| *end > 0) else {
+        return Err(invalid);
+    };
+    let ignore_case = match &value[end + 1..] {
+        "" => false,
+        "i" => true,
+        _ => return Err(invalid),
+    };
+    if !cfg!(feature = "full-regex-handling") {
+        return Err(unsupported);
+    }
+    Ok(Some((
+        super::abstract_network::unescape_option_value(&value[1..end]),
+        ignore_case,
+    )))
+}
+
+/// Interpretation of the value of a `$replace` or `$urltransform` option, i.e.
+/// `/pattern/replacement/flags`, following uBlock Origin's syntax. Occurrences of `/` within the
+/// pattern or replacement must be escaped as `\/`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReplaceValue<'a> {
+    pub(crate) pattern: Cow<'a, str>,
+    /// Replacement text, in the syntax of JavaScript's `String.prototype.replace`.
+    pub(crate) replacement: Cow<'a, str>,
+    /// `g` flag: replace every match, rather than just the first one.
+    pub(crate) global: bool,
+    /// `i` flag.
+    pub(crate) ignore_case: bool,
+    /// `m` flag: `^` and `$` match at the start and end of each line.
+    pub(crate) multi_line: bool,
+    /// `s` flag: `.` also matches `\n`.
+    pub(crate) dot_matches_new_line: bool,
+}
+
+impl<'a> ReplaceValue<'a> {
+    /// Interprets the raw value of a `$replace` option, as stored in
+    /// [`NetworkFilter::modifier_option`].
+    ///
+    /// The pattern is not compiled here; see [`ReplaceValue::compile`].
+    pub(crate) fn parse(value: &'a str) -> Result<Self, NetworkFilterError> {
+        fn unescape(part: &str) -> Cow<'_, str> {
+            match super::abstract_network::unescape_option_value(part) {
+                Cow::Borrowed(part) if !part.contains("\\/") => Cow::Borrowed(part),
+                part => Cow::Owned(part.replace("\\/", "/")),
+            }
+        }
+
+        if value.is_empty() {
+            return Err(NetworkFilterError::EmptyReplace);
+        }
+        let Some(value) = value.strip_prefix('/') else {
+            return Err(NetworkFilterError::ReplaceInvalidValue);
+        };
+        let mut parts = super::abstract_network::split_unescaped(value, b'/');
+        let (Some(pattern), Some(replacement), Some(flags), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(NetworkFilterError::ReplaceInvalidValue);
+        };
+        if pattern.is_empty() {
+            return Err(NetworkFilterError::ReplaceInvalidValue);
+        }
+
+        let mut result = Self {
+            pattern: unescape(pattern),
+            replacement: unescape(replacement),
+            global: false,
+            ignore_case: false,
+            multi_line: false,
+            dot_matches_new_line: false,
+        };
+        for flag in flags.chars() {
+            match flag {
+                'g' => result.global = true,
+                'i' => result.ignore_case = true,
+                'm' => result.multi_line = true,
+                's' => result.dot_matches_new_line = true,
+                // Patterns are always Unicode-aware.
+                'u' => (),
+                _ => return Err(NetworkFilterError::ReplaceInvalidValue),
+            }
+        }
+
+        if cfg!(feature = "full-regex-handling") {
+            Ok(result)
+        } else {
+            Err(NetworkFilterError::ReplaceRegexUnsupported)
+        }
+    }
+
+    /// Compiles the pattern, using the flags from the option value.
+    #[cfg_attr(not(feature = "full-regex-handling"), allow(dead_code))]
+    pub(crate) fn compile(&self) -> Result<regex::bytes::Regex, regex::Error> {
+        regex::bytes::RegexBuilder::new(&self.pattern)
+            .case_insensitive(self.ignore_case)
+            .multi_line(self.multi_line)
+            .dot_matches_new_line(self.dot_matches_new_line)
+            .build()
+    }
+}
+
+/// Interpretation of the value of a `$header` option, i.e. `name`, `name:value`,
+/// `name:/regex/flags`, or either of the latter two with the value prefixed by `~`, following
+/// uBlock Origin's syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct HeaderValue<'a> {
+    /// Name of the response header, which is matched case-insensitively.
+    pub(crate) name: &'a str,
+    /// `true` if the value was prefixed with `~`, in which case the filter matches if the header
+    /// is present but its value does _not_ match.
+    pub(crate) negated: bool,
+    pub(crate) matcher: HeaderMatcher<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum HeaderMatcher<'a> {
+    /// `$header=name`, which matches if the header is present with any value.
+    Present,
+    /// `$header=name:value`, which matches if the header's value is exactly `value`.
+    Literal(Cow<'a, str>),
+    /// `$header=name:/regex/flags`, which matches if the header's value matches the regex. `i` is
+    /// the only supported flag.
+    Regex {
+        pattern: Cow<'a, str>,
+        ignore_case: bool,
+    },
+}
+
+impl<'a> HeaderValue<'a> {
+    /// Interprets the raw value of a `$header` option, as stored in
+    /// [`NetworkFilter::modifier_option`].
+    ///
+    /// Regexes are not compiled here; see [`crate::regex_manager::compile_option_regex`].
+    pub(crate) fn parse(value: &'a str) -> Result<Self, NetworkFilterError> {
+        let (name, value) = match value.split_once(':') {
+            Some((name, value)) => (name, Some(value)),
+            None => (value, None),
+        };
+        if name.is_empty() {
+            return Err(NetworkFilterError::EmptyHeader);
+        }
+        if !name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+        {
+            return Err(NetworkFilterError::HeaderInvalidValue);
+        }
+        let Some(value) = value else {
+            return Ok(Self {
+                name,
+                negated: false,
+                matcher: HeaderMatcher::Present,
+            });
+        };
+        let (negated, value) = match value.strip_prefix('~') {
+            Some(negated_value) => (true, negated_value),
+            None => (false, value),
+        };
+        if value.is_empty() {
+            return Err(NetworkFilterError::HeaderInvalidValue);
+        }
+
+        let matcher = if let Some((pattern, ignore_case)) = parse_option_regex(
+            value,
+            NetworkFilterError::HeaderInvalidValue,
+            NetworkFilterError::HeaderRegexUnsupported,
+        )? {
+            HeaderMatcher::Regex {
+                pattern,
+                ignore_case,
+            }
+        } else {
+            HeaderMatcher::Literal(super::abstract_network::unescape_option_value(value))
+        };
+
+        Ok(Self {
+            name,
+            negated,
+            matcher,
+        })
+    }
+}
+
+/// Interpretation of the value of an `$ipaddress` option, following uBlock Origin's syntax.
+/// Filters with this option only match requests whose resolved address is known; see
+/// [`request::Request::ip_address`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum IpAddressValue<'a> {
+    /// `$ipaddress=lan`, which matches loopback, private, link-local and unspecified addresses, as
+    /// well as IPv6 unique local addresses.
+    Lan,
+    /// `$ipaddress=loopback`, which matches `127.0.0.0/8` and `::1`.
+    Loopback,
+    /// `$ipaddress=10.0.0.0/8`, which matches any address within the prefix. A plain address
+    /// like `$ipaddress=192.0.2.1` only matches that exact address.
+    Prefix { network: IpAddr, length: u8 },
+    /// `$ipaddress=/regex/flags`, which matches if the textual form of the address, e.g.
+    /// `192.0.2.1` or `2001:db8::1`, matches the regex. `i` is the only supported flag.
+    Regex {
+        pattern: Cow<'a, str>,
+        ignore_case: bool,
+    },
+}
+
+impl<'a> IpAddressValue<'a> {
+    /// Interprets the raw value of an `$ipaddress` option, as stored in
+    /// [`NetworkFilter::opt_ip_address`].
+    ///
+    /// Regexes are not compiled here; see [`crate::regex_manager::compile_option_regex`].
+    pub(crate) fn parse(value: &'a str) -> Result<Self, NetworkFilterError> {
+        match value {
+            "" => return Err(NetworkFilterError::EmptyIpAddress),
+            "lan" => return Ok(Self::Lan),
+            "loopback" => return Ok(Self::Loopback),
+            _ => (),
+        }
+
+        if let Some((pattern, ignore_case)) = parse_option_regex(
+            value,
+            NetworkFilterError::IpAddressInvalidValue,
+            NetworkFilterError::IpAddressRegexUnsupported,
+        )? {
+            return Ok(Self::Regex {
+                pattern,
+                ignore_case,
+            });
+        }
+
+        let (network, length) = match value.split_once('/') {
+            Some((network, length)) => (network, Some(length)),
+            None => (value, None),
+        };
+        let network: IpAddr = network
+            .parse()
+            .map_err(|_| NetworkFilterError::IpAddressInvalidValue)?;
+        let max_length = if network.is_ipv4() { 32 } else { 128 };
+        let length = match length {
+            Some(length) => length
+                .parse::<u8>()
+                .ok()
+                .filter(|length| *length <= max_length)
+                .ok_or(NetworkFilterError::IpAddressInvalidValue)?,
+            None => max_length,
+        };
+        Ok(Self::Prefix { network, length })
+    }
+
+    pub(crate) fn into_owned(self) -> IpAddressValue<'static> {
+        match self {
+            Self::Lan => IpAddressValue::Lan,
+            Self::Loopback => IpAddressValue::Loopback,
+            Self::Prefix { network, length } => IpAddressValue::Prefix { network, length },
+            Self::Regex {
+                pattern,
+                ignore_case,
+            } => IpAddressValue::Regex {
+                pattern: Cow::Owned(pattern.into_owned()),
+                ignore_case,
+            },
+        }
+    }
+
+    /// Returns `true` if `address` satisfies this value. IPv4-mapped IPv6 addresses are treated
+    /// like the IPv4 address they represent.
+    pub(crate) fn matches(&self, address: IpAddr, regex_manager: &mut RegexManager) -> bool {
+        let address = address.to_canonical();
+        match self {
+            Self::Lan => match address {
+                IpAddr::V4(address) => {
+                    address.is_loopback()
+                        || address.is_private()
+                        || address.is_link_local()
+                        || address.octets()[0] == 0
+                }
+                IpAddr::V6(address) => {
+                    address.is_loopback()
+                        || address.is_unspecified()
+                        || address.is_unique_local()
+                        || address.is_unicast_link_local()
+                }
+            },
+            Self::Loopback => address.is_loopback(),
+            Self::Prefix { network, length } => prefix_contains(*network, *length, address),
+            Self::Regex {
+                pattern,
+                ignore_case,
+            } => regex_manager.matches_option_regex(pattern, *ignore_case, &address.to_string()),
+        }
+    }
+}
+
+/// Returns `true` if `address` is within the prefix of `length` bits starting at `network`.
+/// IPv4-mapped IPv6 addresses are treated like the IPv4 address they represent.
+fn prefix_contains(network: IpAddr, length: u8, address: IpAddr) -> bool {
+    let (address, network, width) = match (address.to_canonical(), network) {
+        (IpAddr::V4(address), IpAddr::V4(network)) => (
+            u128::from(u32::from(address)),
+            u128::from(u32::from(network)),
+            32,
+        ),
+        (IpAddr::V6(address), IpAddr::V6(network)) => {
+            (u128::from(address), u128::from(network), 128)
+        }
+        _ => return false,
+    };
+    let shift = u32::from(width - length);
+    address.checked_shr(shift).unwrap_or(0) == network.checked_shr(shift).unwrap_or(0)
+}
+
+/// Splits the value of a `$dnstype` option, e.g. `A|AAAA` or `~CNAME`, into DNS record type
+/// names. Each is paired with `false` if it's negated using a prepended `~`, like `$domain=`.
+pub(crate) fn parse_dns_types(value: &str) -> Result<Vec<(bool, &str)>, NetworkFilterError> {
+    value
+        .split('|')
+        .map(|dns_type| {
+            let (included, name) = match dns_type.strip_prefix('~') {
+                Some(name) => (false, name),
+                None => (true, dns_type),
+            };
+            if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric()) {
+                return Err(NetworkFilterError::DnsTypeInvalidValue);
+            }
+            Ok((included, name))
+        })
+        .collect()
+}
+
+/// A single entry from the value of a `$client` option, following AdGuard's syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ClientValue<'a> {
+    /// A client name, e.g. `$client=laptop`. Names containing special characters can be quoted,
+    /// e.g. `$client='Frank\'s laptop'`, where a backslash escapes the following character.
+    Name(Cow<'a, str>),
+    /// A client address like `$client=192.168.0.1`, or a prefix like `$client=192.168.0.0/24`.
+    Address { network: IpAddr, length: u8 },
+}
+
+impl ClientValue<'_> {
+    pub(crate) fn into_owned(self) -> ClientValue<'static> {
+        match self {
+            Self::Name(name) => ClientValue::Name(Cow::Owned(name.into_owned())),
+            Self::Address { network, length } => ClientValue::Address { network, length },
+        }
+    }
+
+    /// Returns `true` if this entry refers to a client with the given name or address.
+    pub(crate) fn matches(&self, name: Option<&str>, address: Option<IpAddr>) -> bool {
+        match self {
+            Self::Name(expected) => name == Some(expected.as_ref()),
+            Self::Address { network, length } => {
+                address.is_some_and(|address| prefix_contains(*network, *length, address))
+            }
+        }
+    }
+}
+
+/// Splits the value of a `$client` option, e.g. `'Frank\'s laptop'|192.168.0.0/24|~tv`, into its
+/// entries. Each is paired with `false` if it's negated using a prepended `~`, like `$domain=`.
+pub(crate) fn parse_client_value(
+    value: &str,
+) -> Result<Vec<(bool, ClientValue<'_>)>, NetworkFilterError> {
+    let mut clients = vec![];
+    let mut rest = value;
+    loop {
+        let (included, entry) = match rest.strip_prefix('~') {
+            Some(entry) => (false, entry),
+            None => (true, rest),
+        };
+        let (client, remaining) = match entry.chars().next() {
+            Some(quote @ ('\'' | '"')) => {
+                let mut name = String::new();
+                let mut chars = entry.char_indices().skip(1);
+                let end = loop {
+                    match chars.next() {
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => name.push(c),
+                            None => return Err(NetworkFilterError::ClientInvalidValue),
+                        },
+                        Some((i, c)) if c == quote => break i + c.len_utf8(),
+                        Some((_, c)) => name.push(c),
+                        None => return Err(NetworkFilterError::ClientInvalidValue),
+                    }
+                };
+                (ClientValue::Name(Cow::Owned(name)), &entry[end..])
+            }
+            _ => {
+                let end = find_char(b'|', entry.as_bytes()).unwrap_or(entry.len());
+                let raw = &entry[..end];
+                let client = match IpAddressValue::parse(raw) {
+                    Ok(IpAddressValue::Prefix { network, length }) => {
+                        ClientValue::Address { network, length }
+                    }
+                    _ => ClientValue::Name(Cow::Borrowed(raw)),
+                };
+                (client, &entry[end..])
+            }
+        };
+        if matches!(&client, ClientValue::Name(name) if name.is_empty()) {
+            return Err(NetworkFilterError::ClientInvalidValue);
+        }
+        clients.push((included, client));
+
+        if remaining.is_empty() {
+            return Ok(clients);
+        }
+        rest = remaining
+            .strip_prefix('|')
+            .ok_or(NetworkFilterError::ClientInvalidValue)?;
+    }
+}
+
+/// DNS response codes that can be used in a `$dnsrewrite` option.
+const DNS_RCODES: &[&str] = &[
+    "NOERROR", "FORMERR", "SERVFAIL", "NXDOMAIN", "NOTIMP", "REFUSED", "YXDOMAIN", "YXRRSET",
+    "NXRRSET", "NOTAUTH", "NOTZONE",
+];
+
+/// How a DNS query should be answered according to a `$dnsrewrite` filter, following AdGuard's
+/// syntax.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct DnsRewrite {
+    /// The response code, e.g. `NOERROR`, `NXDOMAIN` or `REFUSED`.
+    pub rcode: String,
+    /// The type of the answer record, e.g. `A`, `AAAA` or `CNAME`, if the response should
+    /// include one.
+    pub rrtype: Option<String>,
+    /// The value of the answer record, e.g. an address for `A` and `AAAA` records, or a hostname
+    /// for `CNAME` records.
+    pub value: Option<String>,
+}
+
+impl DnsRewrite {
+    /// Parses the value of a `$dnsrewrite` option, in either its full `RCODE;RRTYPE;VALUE` form,
+    /// or one of its short forms:
+    /// - a response code like `REFUSED`, which produces a response without any answer records;
+    /// - an IPv4 or IPv6 address, which produces an `A` or `AAAA` record respectively;
+    /// - any other hostname, which produces a `CNAME` record.
+    pub(crate) fn parse(value: &str) -> Result<Self, NetworkFilterError> {
+        let invalid = NetworkFilterError::DnsRewriteInvalidValue;
+        let is_hostname = |value: &str| {
+            !value.is_empty()
+                && value
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_'))
+        };
+
+        let Some((rcode, rest)) = value.split_once(';') else {
+            if DNS_RCODES.contains(&value) {
+                return Ok(Self {
+                    rcode: value.to_string(),
+                    rrtype: None,
+                    value: None,
+                });
+            }
+            let rrtype = match value.parse::<IpAddr>() {
+                Ok(IpAddr::V4(_)) => "A",
+                Ok(IpAddr::V6(_)) => "AAAA",
+                Err(_) if is_hostname(value) => "CNAME",
+                Err(_) => return Err(invalid),
+            };
+            return Ok(Self {
+                rcode: "NOERROR".to_string(),
+                rrtype: Some(rrtype.to_string()),
+                value: Some(value.strip_suffix('.').unwrap_or(value).to_string()),
+            });
+        };
+
+        let (rrtype, value) = rest.split_once(';').ok_or(invalid.clone())?;
+        if !DNS_RCODES.contains(&rcode) {
+            return Err(invalid);
+        }
+        let rrtype = (!rrtype.is_empty()).then(|| rrtype.to_ascii_uppercase());
+        let value = (!value.is_empty()).then(|| value.to_string());
+        let valid = match (rrtype.as_deref(), value.as_deref()) {
+            (None, None) => true,
+            (None, Some(_)) => false,
+            (Some(rrtype), _) if !rrtype.bytes().all(|b| b.is_ascii_alphanumeric()) => false,
+            (Some("A"), value) => value.is_none_or(|v| v.parse::<std::net::Ipv4Addr>().is_ok()),
+            (Some("AAAA"), value) => value.is_none_or(|v| v.parse::<std::net::Ipv6Addr>().is_ok()),
+            (Some(_), _) => true,
+        };
+        if !valid {
+            return Err(invalid);
+        }
+        Ok(Self {
+            rcode: rcode.to_string(),
+            rrtype,
+            value,
+        })
+    }
+}
+
+/// Splits the value of a `$permissions` option into individual `Permissions-Policy` directives.
+/// Directives are separated by `|`, or by `\,` following AdGuard's syntax, e.g.
+/// `autoplay=()|camera=()`.
+pub(crate) fn parse_permissions_directives(value: &str) -> Result<Vec<String>, NetworkFilterError> {
+    super::abstract_network::unescape_option_value(value)
+        .split(['|', ','])
+        .map(|directive| {
+            let directive = directive.trim();
+            if VALID_PERMISSIONS_DIRECTIVE.is_match(directive) {
+                Ok(directive.to_string())
+            } else {
+                Err(NetworkFilterError::PermissionsInvalidValue)
+            }
+        })
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkFilter<'a> {
     pub mask: NetworkFilterMask,
+    /// HTTP methods from `$method=`. See [`NetworkFilterMethodMask`].
+    pub methods: NetworkFilterMethodMask,
     pub features_mask: NetworkFilterFeaturesMask,
     pub filter: FilterPart<'a>,
     pub opt_domains: Option<Vec<Hash>>,
     pub opt_not_domains: Option<Vec<Hash>>,
     pub opt_to_domains: Option<Vec<Hash>>,
     pub opt_not_to_domains: Option<Vec<Hash>>,
-    /// Used for `$redirect`, `$redirect-rule`, `$csp`, and `$removeparam` - only one of which is
-    /// supported per-rule.
+    /// Entities from `$domain=` entries like `google.*`, which match any hostname whose labels
+    /// before the public suffix end with `google`. Hashed without the trailing `.*`.
+    pub opt_entities: Option<Vec<Hash>>,
+    pub opt_not_entities: Option<Vec<Hash>>,
+    /// Like `opt_entities` and `opt_not_entities`, but for `$to=`.
+    pub opt_to_entities: Option<Vec<Hash>>,
+    pub opt_not_to_entities: Option<Vec<Hash>>,
+    /// Regexes from `$domain=` entries like `/^img[a-z]+\.com$/`, without the enclosing slashes,
+    /// which are matched against the source hostname.
+    pub opt_domain_regexes: Option<Vec<Cow<'a, str>>>,
+    pub opt_not_domain_regexes: Option<Vec<Cow<'a, str>>>,
+    /// Request hostnames that are excluded from matching by `$denyallow=`, along with any of
+    /// their subdomains.
+    pub opt_denyallow_domains: Option<Vec<Hash>>,
+    /// Value of an `$ipaddress=` option, which is matched against the address the request
+    /// resolved to. See [`IpAddressValue`].
+    pub opt_ip_address: Option<&'a str>,
+    /// Value of a `$dnstype=` option, which restricts the DNS record types that the filter applies
+    /// to. See [`parse_dns_types`].
+    pub opt_dns_type: Option<&'a str>,
+    /// Value of a `$client=` option, which restricts the DNS clients that the filter applies to.
+    /// See [`ClientValue`].
+    pub opt_client: Option<&'a str>,
+    /// Used for `$redirect`, `$redirect-rule`, `$csp`, `$removeparam`, `$replace`, `$header`,
+    /// `$permissions`, and `$dnsrewrite` - only one of which is supported per-rule.
     pub modifier_option: Option<&'a str>,
     pub hostname: Option<Cow<'a, str>>,
     pub(crate) tag: Option<&'a str>,
@@ -441,15 +1184,48 @@ impl PartialOrd for NetworkFilter<'_> {
 /// Ensure that no invalid option combinations were provided for a filter.
 fn validate_options(options: &[NetworkFilterOption<'_>]) -> Result<(), NetworkFilterError> {
     let mut has_csp = false;
+    let mut has_permissions = false;
+    let mut has_popup = false;
     let mut has_content_type = false;
+    let mut has_dns_option = false;
+    let mut has_non_dns_option = false;
     let mut modifier_options = 0;
     for option in options {
+        match option {
+            NetworkFilterOption::DnsType(..) | NetworkFilterOption::Client(..) => {
+                has_dns_option = true
+            }
+            NetworkFilterOption::DnsRewrite(..) => {
+                has_dns_option = true;
+                modifier_options += 1;
+            }
+            NetworkFilterOption::Important
+            | NetworkFilterOption::Badfilter
+            | NetworkFilterOption::MatchCase => (),
+            _ => has_non_dns_option = true,
+        }
         if matches!(option, NetworkFilterOption::Csp(..)) {
             has_csp = true;
             modifier_options += 1;
+        } else if matches!(option, NetworkFilterOption::Permissions(..)) {
+            has_permissions = true;
+            modifier_options += 1;
+        } else if matches!(
+            option,
+            NetworkFilterOption::Popup | NetworkFilterOption::Popunder
+        ) {
+            has_popup = true;
+            modifier_options += 1;
         } else if option.is_content_type() {
             has_content_type = true;
-        } else if option.is_redirection() || matches!(option, NetworkFilterOption::Removeparam(..))
+        } else if option.is_redirection()
+            || matches!(
+                option,
+                NetworkFilterOption::Removeparam(..)
+                    | NetworkFilterOption::Replace(..)
+                    | NetworkFilterOption::UrlTransform(..)
+                    | NetworkFilterOption::Header(..)
+            )
         {
             modifier_options += 1;
         }
@@ -457,9 +1233,19 @@ fn validate_options(options: &[NetworkFilterOption<'_>]) -> Result<(), NetworkFi
     if has_csp && has_content_type {
         return Err(NetworkFilterError::CspWithContentType);
     }
+    if has_permissions && has_content_type {
+        return Err(NetworkFilterError::PermissionsWithContentType);
+    }
+    if has_popup && has_content_type {
+        return Err(NetworkFilterError::PopupWithContentType);
+    }
     if modifier_options > 1 {
         return Err(NetworkFilterError::MultipleModifierOptions);
     }
+    // Other options can't be checked against DNS queries.
+    if has_dns_option && has_non_dns_option {
+        return Err(NetworkFilterError::DnsOptionWithUnsupportedOption);
+    }
 
     Ok(())
 }
@@ -477,34 +1263,66 @@ fn decode_hostname<'a>(host: &'a [u8]) -> Result<Cow<'a, str>, NetworkFilterErro
         .map_err(|_| NetworkFilterError::PunycodeError)
 }
 
-fn hash_pipe_delimited_domains(
-    domains: Vec<(bool, &str)>,
-    opt_domains: &mut Option<Vec<Hash>>,
-    opt_not_domains: &mut Option<Vec<Hash>>,
-) {
+/// Sorted and deduplicated hashes of the hostnames and entities from a `$domain=` or `$to=`
+/// option, split by whether or not they were negated. Regexes are kept in their original order.
+#[derive(Default)]
+struct DomainHashes<'a> {
+    domains: Option<Vec<Hash>>,
+    not_domains: Option<Vec<Hash>>,
+    entities: Option<Vec<Hash>>,
+    not_entities: Option<Vec<Hash>>,
+    regexes: Option<Vec<Cow<'a, str>>>,
+    not_regexes: Option<Vec<Cow<'a, str>>>,
+}
+
+fn hash_pipe_delimited_domains<'a>(domains: Vec<(bool, &'a str)>, hashes: &mut DomainHashes<'a>) {
     let mut opt_domains_array: Vec<Hash> = vec![];
     let mut opt_not_domains_array: Vec<Hash> = vec![];
+    let mut opt_entities_array: Vec<Hash> = vec![];
+    let mut opt_not_entities_array: Vec<Hash> = vec![];
+    let mut opt_regexes_array: Vec<Cow<'a, str>> = vec![];
+    let mut opt_not_regexes_array: Vec<Cow<'a, str>> = vec![];
 
     for (enabled, domain) in domains {
-        let domain_hash = utils::fast_hash(domain);
-        if !enabled {
-            opt_not_domains_array.push(domain_hash);
-        } else {
-            opt_domains_array.push(domain_hash);
+        if let Some(pattern) = super::abstract_network::domain_regex(domain) {
+            let pattern = super::abstract_network::unescape_option_value(pattern);
+            if enabled {
+                opt_regexes_array.push(pattern);
+            } else {
+                opt_not_regexes_array.push(pattern);
+            }
+            continue;
         }
+        let (collection, hash) = match (enabled, domain.strip_suffix(".*")) {
+            (true, None) => (&mut opt_domains_array, utils::fast_hash(domain)),
+            (false, None) => (&mut opt_not_domains_array, utils::fast_hash(domain)),
+            (true, Some(entity)) => (&mut opt_entities_array, utils::fast_hash(entity)),
+            (false, Some(entity)) => (&mut opt_not_entities_array, utils::fast_hash(entity)),
+        };
+        collection.push(hash);
     }
 
-    if !opt_domains_array.is_empty() {
-        opt_domains_array.sort_unstable();
-        // Some rules have duplicate domain options - avoid including duplicates
-        opt_domains_array.dedup();
-        *opt_domains = Some(opt_domains_array);
+    for (mut array, hashes) in [
+        (opt_domains_array, &mut hashes.domains),
+        (opt_not_domains_array, &mut hashes.not_domains),
+        (opt_entities_array, &mut hashes.entities),
+        (opt_not_entities_array, &mut hashes.not_entities),
+    ] {
+        if !array.is_empty() {
+            array.sort_unstable();
+            // Some rules have duplicate domain options - avoid including duplicates
+            array.dedup();
+            *hashes = Some(array);
+        }
     }
-    if !opt_not_domains_array.is_empty() {
-        opt_not_domains_array.sort_unstable();
-        // Some rules have duplicate domain options - avoid including duplicates
-        opt_not_domains_array.dedup();
-        *opt_not_domains = Some(opt_not_domains_array);
+
+    for (array, regexes) in [
+        (opt_regexes_array, &mut hashes.regexes),
+        (opt_not_regexes_array, &mut hashes.not_regexes),
+    ] {
+        if !array.is_empty() {
+            *regexes = Some(array);
+        }
     }
 }
 
@@ -512,7 +1330,7 @@ impl<'a> NetworkFilter<'a> {
     pub fn parse(
         line: &'a str,
         debug: bool,
-        _opts: ParseOptions,
+        opts: ParseOptions,
     ) -> Result<Self, NetworkFilterError> {
         let parsed = AbstractNetworkFilter::parse(line)?;
 
@@ -527,15 +1345,17 @@ impl<'a> NetworkFilter<'a> {
         // content type options.
         let mut cpt_mask_positive: NetworkFilterMask = NetworkFilterMask::NONE;
         let mut cpt_mask_negative: NetworkFilterMask = NetworkFilterMask::NONE;
-        let mut method_mask_positive: NetworkFilterMask = NetworkFilterMask::NONE;
-        let mut method_mask_negative: NetworkFilterMask = NetworkFilterMask::NONE;
+        let mut method_mask_positive = NetworkFilterMethodMask::empty();
+        let mut method_mask_negative = NetworkFilterMethodMask::empty();
 
         let mut hostname: Option<&'a [u8]> = None;
 
-        let mut opt_domains: Option<Vec<Hash>> = None;
-        let mut opt_not_domains: Option<Vec<Hash>> = None;
-        let mut opt_to_domains: Option<Vec<Hash>> = None;
-        let mut opt_not_to_domains: Option<Vec<Hash>> = None;
+        let mut from_domains = DomainHashes::default();
+        let mut to_domains = DomainHashes::default();
+        let mut opt_denyallow_domains: Option<Vec<Hash>> = None;
+        let mut opt_ip_address: Option<&'a str> = None;
+        let mut opt_dns_type: Option<&'a str> = None;
+        let mut opt_client: Option<&'a str> = None;
 
         let mut modifier_option: Option<&'a str> = None;
         let mut tag: Option<&'a str> = None;
@@ -560,9 +1380,9 @@ impl<'a> NetworkFilter<'a> {
             macro_rules! apply_method {
                 ($method:ident, $enabled:ident) => {
                     if $enabled {
-                        method_mask_positive.set(NetworkFilterMask::$method, true);
+                        method_mask_positive.set(NetworkFilterMethodMask::$method, true);
                     } else {
-                        method_mask_negative.set(NetworkFilterMask::$method, true);
+                        method_mask_negative.set(NetworkFilterMethodMask::$method, true);
                     }
                 };
             }
@@ -570,18 +1390,24 @@ impl<'a> NetworkFilter<'a> {
             options.into_iter().for_each(|option| {
                 match option {
                     NetworkFilterOption::Domain(domains) => {
-                        hash_pipe_delimited_domains(
-                            domains,
-                            &mut opt_domains,
-                            &mut opt_not_domains,
-                        );
+                        hash_pipe_delimited_domains(domains, &mut from_domains);
                     }
                     NetworkFilterOption::To(domains) => {
-                        hash_pipe_delimited_domains(
-                            domains,
-                            &mut opt_to_domains,
-                            &mut opt_not_to_domains,
-                        );
+                        hash_pipe_delimited_domains(domains, &mut to_domains);
+                    }
+                    NetworkFilterOption::Denyallow(domains) => {
+                        let mut hashes: Vec<Hash> =
+                            domains.into_iter().map(utils::fast_hash).collect();
+                        hashes.sort_unstable();
+                        hashes.dedup();
+                        opt_denyallow_domains = Some(hashes);
+                    }
+                    NetworkFilterOption::IpAddress(value) => opt_ip_address = Some(value),
+                    NetworkFilterOption::DnsType(value) => opt_dns_type = Some(value),
+                    NetworkFilterOption::Client(value) => opt_client = Some(value),
+                    NetworkFilterOption::DnsRewrite(value) => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_DNSREWRITE, true);
+                        modifier_option = value;
                     }
                     NetworkFilterOption::Badfilter => {
                         features_mask.set(NetworkFilterFeaturesMask::BAD_FILTER, true)
@@ -598,6 +1424,14 @@ impl<'a> NetworkFilter<'a> {
                     | NetworkFilterOption::FirstParty(false) => {
                         mask.set(NetworkFilterMask::FIRST_PARTY, false)
                     }
+                    NetworkFilterOption::StrictThirdParty(false)
+                    | NetworkFilterOption::StrictFirstParty(true) => {
+                        mask.set(NetworkFilterMask::STRICT_FIRST_PARTY, true)
+                    }
+                    NetworkFilterOption::StrictThirdParty(true)
+                    | NetworkFilterOption::StrictFirstParty(false) => {
+                        mask.set(NetworkFilterMask::STRICT_THIRD_PARTY, true)
+                    }
                     NetworkFilterOption::Tag(value) => tag = Some(value),
                     NetworkFilterOption::Redirect(value) => {
                         features_mask.set(NetworkFilterFeaturesMask::IS_REDIRECT, true);
@@ -610,8 +1444,35 @@ impl<'a> NetworkFilter<'a> {
                     }
                     NetworkFilterOption::Removeparam(value) => {
                         features_mask.set(NetworkFilterFeaturesMask::IS_REMOVEPARAM, true);
+                        modifier_option = value;
+                    }
+                    NetworkFilterOption::Replace(value) => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_REPLACE, true);
+                        modifier_option = value;
+                    }
+                    NetworkFilterOption::UrlTransform(value) => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_URLTRANSFORM, true);
+                        modifier_option = value;
+                    }
+                    NetworkFilterOption::Header(value) => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_HEADER, true);
                         modifier_option = Some(value);
                     }
+                    NetworkFilterOption::Permissions(value) => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_PERMISSIONS, true);
+                        // Like CSP rules, these only apply to document and subdocument requests.
+                        mask.set(NetworkFilterMask::FROM_DOCUMENT, true);
+                        modifier_option = value;
+                    }
+                    NetworkFilterOption::Popup => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_POPUP, true);
+                        // Popups are matched as documents opened by another page.
+                        mask.set(NetworkFilterMask::FROM_DOCUMENT, true);
+                    }
+                    NetworkFilterOption::Popunder => {
+                        features_mask.set(NetworkFilterFeaturesMask::IS_POPUNDER, true);
+                        mask.set(NetworkFilterMask::FROM_DOCUMENT, true);
+                    }
                     NetworkFilterOption::Csp(value) => {
                         features_mask.set(NetworkFilterFeaturesMask::IS_CSP, true);
                         // CSP rules can never have content types, and should always match against
@@ -623,6 +1484,18 @@ impl<'a> NetworkFilter<'a> {
                     NetworkFilterOption::Generichide => {
                         features_mask.set(NetworkFilterFeaturesMask::GENERIC_HIDE, true)
                     }
+                    NetworkFilterOption::Elemhide => {
+                        features_mask.set(NetworkFilterFeaturesMask::ELEM_HIDE, true)
+                    }
+                    NetworkFilterOption::Specifichide => {
+                        features_mask.set(NetworkFilterFeaturesMask::SPECIFIC_HIDE, true)
+                    }
+                    NetworkFilterOption::Genericblock => {
+                        features_mask.set(NetworkFilterFeaturesMask::GENERIC_BLOCK, true)
+                    }
+                    NetworkFilterOption::Cname => {
+                        features_mask.set(NetworkFilterFeaturesMask::CNAME, true)
+                    }
                     NetworkFilterOption::Document => {
                         cpt_mask_positive.set(NetworkFilterMask::FROM_DOCUMENT, true)
                     }
@@ -653,9 +1526,14 @@ impl<'a> NetworkFilter<'a> {
                     NetworkFilterOption::Method(methods) => {
                         for (enabled, method) in methods {
                             match method {
-                                HttpMethod::Get => apply_method!(FROM_GET, enabled),
-                                HttpMethod::Head => apply_method!(FROM_HEAD, enabled),
-                                HttpMethod::Post => apply_method!(FROM_POST, enabled),
+                                HttpMethod::Connect => apply_method!(CONNECT, enabled),
+                                HttpMethod::Delete => apply_method!(DELETE, enabled),
+                                HttpMethod::Get => apply_method!(GET, enabled),
+                                HttpMethod::Head => apply_method!(HEAD, enabled),
+                                HttpMethod::Options => apply_method!(OPTIONS, enabled),
+                                HttpMethod::Patch => apply_method!(PATCH, enabled),
+                                HttpMethod::Post => apply_method!(POST, enabled),
+                                HttpMethod::Put => apply_method!(PUT, enabled),
                             }
                         }
                     }
@@ -682,6 +1560,9 @@ impl<'a> NetworkFilter<'a> {
                 mask |= NetworkFilterMask::FROM_DOCUMENT
                     | NetworkFilterMask::FROM_SUBDOCUMENT
                     | NetworkFilterMask::FROM_XMLHTTPREQUEST;
+            } else if features_mask.contains(NetworkFilterFeaturesMask::IS_URLTRANSFORM) {
+                // Rewriting redirect wrappers mostly matters for navigations.
+                mask |= NetworkFilterMask::FROM_NETWORK_TYPES | NetworkFilterMask::FROM_DOCUMENT;
             } else {
                 mask |= NetworkFilterMask::FROM_NETWORK_TYPES;
             }
@@ -843,12 +1724,56 @@ impl<'a> NetworkFilter<'a> {
 
         let hostname_decoded = hostname.map(decode_hostname).transpose()?;
 
-        if features_mask.contains(NetworkFilterFeaturesMask::GENERIC_HIDE) && !parsed.exception {
-            return Err(NetworkFilterError::GenericHideWithoutException);
+        if !parsed.exception {
+            if features_mask.contains(NetworkFilterFeaturesMask::GENERIC_HIDE) {
+                return Err(NetworkFilterError::GenericHideWithoutException);
+            }
+            if features_mask.contains(NetworkFilterFeaturesMask::ELEM_HIDE) {
+                return Err(NetworkFilterError::ElemHideWithoutException);
+            }
+            if features_mask.contains(NetworkFilterFeaturesMask::SPECIFIC_HIDE) {
+                return Err(NetworkFilterError::SpecificHideWithoutException);
+            }
+            if features_mask.contains(NetworkFilterFeaturesMask::GENERIC_BLOCK) {
+                return Err(NetworkFilterError::GenericBlockWithoutException);
+            }
+            if features_mask.contains(NetworkFilterFeaturesMask::CNAME) {
+                return Err(NetworkFilterError::CnameWithoutException);
+            }
+            if features_mask.contains(NetworkFilterFeaturesMask::IS_DNSREWRITE)
+                && modifier_option.is_none()
+            {
+                return Err(NetworkFilterError::EmptyDnsRewrite);
+            }
+        }
+
+        // DNS queries can only be matched against a hostname, e.g. `||example.com^$dnstype=A`.
+        if (opt_dns_type.is_some()
+            || opt_client.is_some()
+            || features_mask.contains(NetworkFilterFeaturesMask::IS_DNSREWRITE))
+            && (hostname.is_none()
+                || filter.is_some()
+                || mask.contains(NetworkFilterMask::IS_HOSTNAME_REGEX))
+        {
+            return Err(NetworkFilterError::DnsOptionWithoutHostname);
         }
 
-        if features_mask.contains(NetworkFilterFeaturesMask::IS_REMOVEPARAM) && parsed.exception {
-            return Err(NetworkFilterError::RemoveparamWithException);
+        if features_mask.contains(NetworkFilterFeaturesMask::IS_REPLACE)
+            && modifier_option.is_none()
+            && !parsed.exception
+        {
+            return Err(NetworkFilterError::EmptyReplace);
+        }
+
+        if features_mask.contains(NetworkFilterFeaturesMask::IS_URLTRANSFORM) && !parsed.exception {
+            if modifier_option.is_none() {
+                return Err(NetworkFilterError::EmptyUrlTransform);
+            }
+            // Arbitrary URL rewrites are only accepted from lists with extra permissions.
+            // Exceptions can only disable them, so they're accepted from any list.
+            if opts.permissions.is_default() {
+                return Err(NetworkFilterError::UntrustedUrlTransform);
+            }
         }
 
         // uBlock Origin would block main document `https://example.com` requests with all of the
@@ -873,20 +1798,18 @@ impl<'a> NetworkFilter<'a> {
         // Finally, apply any explicitly negated request types
         mask &= !cpt_mask_negative;
 
-        if !method_mask_positive.is_empty() || !method_mask_negative.is_empty() {
-            mask |= method_mask_positive;
-            if (method_mask_negative & NetworkFilterMask::FROM_ANY_METHODS)
-                != NetworkFilterMask::NONE
-                && (method_mask_positive & NetworkFilterMask::FROM_ANY_METHODS).is_empty()
-            {
-                mask |= NetworkFilterMask::FROM_ANY_METHODS;
-            }
-            mask &= !method_mask_negative;
+        // Negated methods exclude themselves from the positive ones, or from every method if there
+        // are none, e.g. `$method=~get` applies to any method other than GET.
+        let mut methods = method_mask_positive;
+        if methods.is_empty() && !method_mask_negative.is_empty() {
+            methods = NetworkFilterMethodMask::all();
+        }
+        methods &= !method_mask_negative;
+        if methods.is_empty() && !method_mask_negative.is_empty() {
+            return Err(NetworkFilterError::EmptyMethod);
         }
 
-        if features_mask.contains(NetworkFilterFeaturesMask::GENERIC_HIDE)
-            && mask.intersects(NetworkFilterMask::FROM_ANY_METHODS)
-        {
+        if features_mask.contains(NetworkFilterFeaturesMask::GENERIC_HIDE) && !methods.is_empty() {
             return Err(NetworkFilterError::MethodWithGenerichide);
         }
 
@@ -898,11 +1821,22 @@ impl<'a> NetworkFilter<'a> {
             },
             hostname: hostname_decoded,
             mask,
+            methods,
             features_mask,
-            opt_domains,
-            opt_not_domains,
-            opt_to_domains,
-            opt_not_to_domains,
+            opt_domains: from_domains.domains,
+            opt_not_domains: from_domains.not_domains,
+            opt_to_domains: to_domains.domains,
+            opt_not_to_domains: to_domains.not_domains,
+            opt_entities: from_domains.entities,
+            opt_not_entities: from_domains.not_entities,
+            opt_to_entities: to_domains.entities,
+            opt_not_to_entities: to_domains.not_entities,
+            opt_domain_regexes: from_domains.regexes,
+            opt_not_domain_regexes: from_domains.not_regexes,
+            opt_denyallow_domains,
+            opt_ip_address,
+            opt_dns_type,
+            opt_client,
             tag,
             raw_line: if debug {
                 Some(Cow::Borrowed(line))
@@ -953,11 +1887,22 @@ impl<'a> NetworkFilter<'a> {
             filter: FilterPart::Empty,
             hostname: Some(decoded_hostname),
             mask,
+            methods: NetworkFilterMethodMask::empty(),
             features_mask: Default::default(),
             opt_domains: None,
             opt_not_domains: None,
             opt_to_domains: None,
             opt_not_to_domains: None,
+            opt_entities: None,
+            opt_not_entities: None,
+            opt_to_entities: None,
+            opt_not_to_entities: None,
+            opt_domain_regexes: None,
+            opt_not_domain_regexes: None,
+            opt_denyallow_domains: None,
+            opt_ip_address: None,
+            opt_dns_type: None,
+            opt_client: None,
             tag: None,
             raw_line: if debug { Some(Cow::Owned(rule)) } else { None },
             modifier_option: None,
@@ -969,11 +1914,20 @@ impl<'a> NetworkFilter<'a> {
         compute_filter_id(
             self.modifier_option,
             self.mask,
+            self.methods,
             self.features_mask,
             &self.filter,
             self.hostname.as_deref(),
             self.opt_domains.as_ref(),
             self.opt_not_domains.as_ref(),
+            self.opt_entities.as_ref(),
+            self.opt_not_entities.as_ref(),
+            self.opt_domain_regexes.as_ref(),
+            self.opt_not_domain_regexes.as_ref(),
+            self.opt_denyallow_domains.as_ref(),
+            self.opt_ip_address,
+            self.opt_dns_type,
+            self.opt_client,
         )
     }
 
@@ -983,8 +1937,11 @@ impl<'a> NetworkFilter<'a> {
     pub(crate) fn get_tokens(&self, tokens_buffer: &mut TokensBuffer) -> FilterTokens {
         tokens_buffer.clear();
 
-        // A single positive `$domain=` is the most selective key available.
+        // A single positive `$domain=` is the most selective key available. Entities and regexes
+        // can't be looked up by hash, so filters with any positive ones can't be keyed by domain.
         if self.opt_not_domains.is_none()
+            && self.opt_entities.is_none()
+            && self.opt_domain_regexes.is_none()
             && let Some(domains) = self.opt_domains.as_ref()
             && let [domain] = domains.as_slice()
         {
@@ -1027,8 +1984,10 @@ impl<'a> NetworkFilter<'a> {
             && self
                 .features_mask
                 .contains(NetworkFilterFeaturesMask::IS_REMOVEPARAM)
-            && let Some(removeparam) = &self.modifier_option
-            && VALID_PARAM.is_match(removeparam.as_ref())
+            && let Ok(RemoveparamValue {
+                negated: false,
+                matcher: RemoveparamMatcher::Name(removeparam),
+            }) = RemoveparamValue::parse(self.modifier_option)
         {
             utils::tokenize_to(&removeparam.to_ascii_lowercase(), tokens_buffer);
         }
@@ -1038,6 +1997,8 @@ impl<'a> NetworkFilter<'a> {
         if tokens_buffer.is_empty() {
             if let Some(opt_domains) = self.opt_domains.as_ref()
                 && !opt_domains.is_empty()
+                && self.opt_entities.is_none()
+                && self.opt_domain_regexes.is_none()
             {
                 let cap = tokens_buffer.remaining_capacity();
                 if opt_domains.len() <= cap {
@@ -1074,11 +2035,72 @@ impl<'a> NetworkFilter<'a> {
             .contains(NetworkFilterFeaturesMask::IS_REMOVEPARAM)
     }
 
+    pub fn is_replace(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_REPLACE)
+    }
+
+    pub fn is_header(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_HEADER)
+    }
+
+    pub fn is_permissions(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_PERMISSIONS)
+    }
+
+    pub fn is_urltransform(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_URLTRANSFORM)
+    }
+
+    pub fn is_popup(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_POPUP)
+    }
+
+    pub fn is_popunder(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_POPUNDER)
+    }
+
     pub fn is_generic_hide(&self) -> bool {
         self.features_mask
             .contains(NetworkFilterFeaturesMask::GENERIC_HIDE)
     }
 
+    pub fn is_elem_hide(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::ELEM_HIDE)
+    }
+
+    pub fn is_specific_hide(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::SPECIFIC_HIDE)
+    }
+
+    pub fn is_generic_block(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::GENERIC_BLOCK)
+    }
+
+    pub fn is_cname(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::CNAME)
+    }
+
+    pub fn is_dnsrewrite(&self) -> bool {
+        self.features_mask
+            .contains(NetworkFilterFeaturesMask::IS_DNSREWRITE)
+    }
+
+    /// Returns `true` if the filter only applies to DNS queries, i.e. it has a `$dnstype`,
+    /// `$client` or `$dnsrewrite` option.
+    pub fn is_dns_only(&self) -> bool {
+        self.opt_dns_type.is_some() || self.opt_client.is_some() || self.is_dnsrewrite()
+    }
+
     pub fn is_csp(&self) -> bool {
         self.features_mask
             .contains(NetworkFilterFeaturesMask::IS_CSP)
@@ -1095,7 +2117,10 @@ impl<'a> NetworkFilter<'a> {
     }
 
     pub fn has_to_option(&self) -> bool {
-        self.opt_to_domains.is_some() || self.opt_not_to_domains.is_some()
+        self.opt_to_domains.is_some()
+            || self.opt_not_to_domains.is_some()
+            || self.opt_to_entities.is_some()
+            || self.opt_not_to_entities.is_some()
     }
 
     #[cfg(test)]
@@ -1138,14 +2163,24 @@ fn write_str_to_hasher(hasher: &mut impl Hasher, s: &str) {
     hasher.write(s.as_bytes());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compute_filter_id(
     modifier_option: Option<&str>,
     mask: NetworkFilterMask,
+    methods: NetworkFilterMethodMask,
     features_mask: NetworkFilterFeaturesMask,
     filter: &FilterPart<'_>,
     hostname: Option<&str>,
     opt_domains: Option<&Vec<Hash>>,
     opt_not_domains: Option<&Vec<Hash>>,
+    opt_entities: Option<&Vec<Hash>>,
+    opt_not_entities: Option<&Vec<Hash>>,
+    opt_domain_regexes: Option<&Vec<Cow<'_, str>>>,
+    opt_not_domain_regexes: Option<&Vec<Cow<'_, str>>>,
+    opt_denyallow_domains: Option<&Vec<Hash>>,
+    opt_ip_address: Option<&str>,
+    opt_dns_type: Option<&str>,
+    opt_client: Option<&str>,
 ) -> Hash {
     let mut hasher = FxHasher::default();
 
@@ -1171,6 +2206,57 @@ fn compute_filter_id(
         }
     }
 
+    // Distinguish entities from hostnames with the same hashes, and from each other.
+    for (marker, entities) in [(1, opt_entities), (2, opt_not_entities)] {
+        if let Some(entities) = entities {
+            hasher.write_u64(marker);
+            hasher.write_u64(entities.len() as u64);
+            for e in entities {
+                hasher.write_u64(*e);
+            }
+        }
+    }
+
+    for (marker, regexes) in [(3, opt_domain_regexes), (4, opt_not_domain_regexes)] {
+        if let Some(regexes) = regexes {
+            hasher.write_u64(marker);
+            hasher.write_u64(regexes.len() as u64);
+            for r in regexes {
+                write_str_to_hasher(&mut hasher, r);
+            }
+        }
+    }
+
+    if let Some(domains) = opt_denyallow_domains {
+        // Distinguish from the same hashes in `opt_not_domains`.
+        hasher.write_u64(0);
+        hasher.write_u64(domains.len() as u64);
+        for d in domains {
+            hasher.write_u64(*d);
+        }
+    }
+
+    if let Some(s) = opt_ip_address {
+        // Distinguish from the same value in `modifier_option`.
+        hasher.write_u64(5);
+        write_str_to_hasher(&mut hasher, s);
+    }
+
+    if let Some(s) = opt_dns_type {
+        hasher.write_u64(6);
+        write_str_to_hasher(&mut hasher, s);
+    }
+
+    if let Some(s) = opt_client {
+        hasher.write_u64(7);
+        write_str_to_hasher(&mut hasher, s);
+    }
+
+    if !methods.is_empty() {
+        hasher.write_u64(8);
+        hasher.write_u64(u64::from(methods.bits()));
+    }
+
     match filter {
         FilterPart::Empty => {}
         FilterPart::Simple(s) => write_str_to_hasher(&mut hasher, s.as_ref()),
@@ -7,11 +7,14 @@
 
 use memchr::memmem;
 
-use crate::filters::network::{NetworkFilterMask, NetworkFilterMaskHelper};
+use crate::filters::network::{
+    NetworkFilterMask, NetworkFilterMaskHelper, NetworkFilterMethodMask,
+};
 use crate::regex_manager::RegexManager;
 use crate::request;
 use crate::utils::{self, Hash};
 use std::collections::HashMap;
+use std::net::IpAddr;
 
 fn get_url_after_hostname<'a>(url: &'a str, hostname: &str) -> &'a str {
     let start =
@@ -403,68 +406,222 @@ where
 }
 
 #[inline]
-pub fn check_options(mask: NetworkFilterMask, request: &request::Request) -> bool {
+pub fn check_options(
+    mask: NetworkFilterMask,
+    methods: NetworkFilterMethodMask,
+    request: &request::Request,
+) -> bool {
     // We first discard requests based on type, protocol and party. This is really
     // cheap and should be done first.
     if !mask.check_cpt_allowed(&request.request_type)
         || (!mask.first_party() && !request.is_third_party)
         || (!mask.third_party() && request.is_third_party)
+        || (mask.strict_first_party() && request.is_strict_third_party)
+        || (mask.strict_third_party() && !request.is_strict_third_party)
         || (request.is_https && !mask.for_https())
         || (request.is_http && !mask.for_http())
-        || !NetworkFilterMask::check_method_allowed(mask, request.method.as_ref())
+        || !methods.check_method_allowed(request.method.as_ref())
     {
         return false;
     }
     true
 }
 
+/// Returns `true` if any of `hashes` is mapped to an index within `domains`. `hashes` is only
+/// evaluated if there are `domains` to check.
+#[inline]
+fn any_hash_mapped_to<I: IntoIterator<Item = Hash>>(
+    hashes: impl FnOnce() -> I,
+    domains: Option<&[u32]>,
+    mapping: &HashMap<Hash, u32>,
+) -> bool {
+    domains.is_some_and(|domains| {
+        hashes().into_iter().any(|h| {
+            mapping
+                .get(&h)
+                .is_some_and(|index| utils::bin_lookup(domains, *index))
+        })
+    })
+}
+
+/// Returns `hostname` followed by each of its parent domains.
+fn hostname_suffixes(hostname: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(hostname)
+        .chain(hostname.match_indices('.').map(|(i, _)| &hostname[i + 1..]))
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Returns `true` if any of `regexes` matches the request's source hostname.
+#[inline]
+fn any_regex_matches<'r>(
+    regexes: Option<impl Iterator<Item = &'r str>>,
+    request: &request::Request,
+    regex_manager: &mut RegexManager,
+) -> bool {
+    regexes.is_some_and(|mut regexes| {
+        regexes.any(|pattern| {
+            regex_manager.matches_option_regex(pattern, false, &request.source_hostname)
+        })
+    })
+}
+
 #[inline]
-pub fn check_included_domains_mapped(
+pub fn check_included_domains_mapped<'r>(
     opt_domains: Option<&[u32]>,
+    opt_entities: Option<&[u32]>,
+    opt_regexes: Option<impl Iterator<Item = &'r str>>,
     request: &request::Request,
     mapping: &HashMap<Hash, u32>,
+    regex_manager: &mut RegexManager,
 ) -> bool {
-    // Source URL must be among these domains to match
-    if let Some(included_domains) = opt_domains.as_ref() {
-        if let Some(source_hashes) = request.source_hostname_hashes.as_ref() {
-            if source_hashes.iter().all(|h| {
-                mapping
-                    .get(h)
-                    .is_none_or(|index| !utils::bin_lookup(included_domains, *index))
-            }) {
-                return false;
-            }
-        } else {
-            // If there are domain restrictions but no source hostname, we can't apply the rule
-            return false;
-        }
+    if opt_domains.is_none() && opt_entities.is_none() && opt_regexes.is_none() {
+        return true;
+    }
+    // Source URL must be among these domains, entities, or regexes to match
+    if let Some(source_hashes) = request.source_hostname_hashes.as_ref() {
+        any_hash_mapped_to(|| source_hashes.iter().copied(), opt_domains, mapping)
+            || any_hash_mapped_to(
+                || request.get_source_entity_hashes().iter().copied(),
+                opt_entities,
+                mapping,
+            )
+            || any_regex_matches(opt_regexes, request, regex_manager)
+    } else {
+        // If there are domain restrictions but no source hostname, we can't apply the rule
+        false
     }
-    true
 }
 
 #[inline]
-pub fn check_excluded_domains_mapped(
+pub fn check_excluded_domains_mapped<'r>(
     opt_not_domains: Option<&[u32]>,
+    opt_not_entities: Option<&[u32]>,
+    opt_not_regexes: Option<impl Iterator<Item = &'r str>>,
     request: &request::Request,
     mapping: &HashMap<Hash, u32>,
+    regex_manager: &mut RegexManager,
 ) -> bool {
-    if let Some(excluded_domains) = opt_not_domains.as_ref() {
-        if let Some(source_hashes) = request.source_hostname_hashes.as_ref() {
-            if source_hashes.iter().any(|h| {
-                mapping
-                    .get(h)
-                    .is_some_and(|index| utils::bin_lookup(excluded_domains, *index))
-            }) {
-                return false;
-            }
-        } else {
-            // If there are domain restrictions but no source hostname
-            // (i.e. about:blank), apply the rule anyway.
-            return true;
+    if let Some(source_hashes) = request.source_hostname_hashes.as_ref() {
+        !any_hash_mapped_to(|| source_hashes.iter().copied(), opt_not_domains, mapping)
+            && !any_hash_mapped_to(
+                || request.get_source_entity_hashes().iter().copied(),
+                opt_not_entities,
+                mapping,
+            )
+            && !any_regex_matches(opt_not_regexes, request, regex_manager)
+    } else {
+        // If there are domain restrictions but no source hostname
+        // (i.e. about:blank), apply the rule anyway.
+        true
+    }
+}
+
+/// Checks that neither the request's hostname nor any of its parent domains are listed in the
+/// filter's `$denyallow` option.
+#[inline]
+pub fn check_denyallow_domains_mapped(
+    opt_denyallow_domains: Option<&[u32]>,
+    request: &request::Request,
+    mapping: &HashMap<Hash, u32>,
+) -> bool {
+    let Some(denyallow_domains) = opt_denyallow_domains else {
+        return true;
+    };
+    hostname_suffixes(&request.hostname).all(|domain| {
+        mapping
+            .get(&utils::fast_hash(domain))
+            .is_none_or(|index| !utils::bin_lookup(denyallow_domains, *index))
+    })
+}
+
+/// Checks the request's hostname against the filter's `$to` option. As with `$domain`, it must
+/// match one of the included hostnames or entities if there are any, and none of the excluded
+/// ones.
+#[inline]
+pub fn check_to_domains_mapped(
+    opt_to_domains: Option<&[u32]>,
+    opt_not_to_domains: Option<&[u32]>,
+    opt_to_entities: Option<&[u32]>,
+    opt_not_to_entities: Option<&[u32]>,
+    request: &request::Request,
+    mapping: &HashMap<Hash, u32>,
+) -> bool {
+    let hostname_hashes = || hostname_suffixes(&request.hostname).map(utils::fast_hash);
+    let entity_hashes = || request.get_entity_hashes().iter().copied();
+    let included = (opt_to_domains.is_none() && opt_to_entities.is_none())
+        || any_hash_mapped_to(hostname_hashes, opt_to_domains, mapping)
+        || any_hash_mapped_to(entity_hashes, opt_to_entities, mapping);
+    included
+        && !any_hash_mapped_to(hostname_hashes, opt_not_to_domains, mapping)
+        && !any_hash_mapped_to(entity_hashes, opt_not_to_entities, mapping)
+}
+
+/// Checks the address that the request resolved to against the filter's `$ipaddress` option.
+/// Filters with the option never match requests without a known address.
+#[inline]
+pub fn check_ip_address(
+    opt_ip_address: Option<&str>,
+    request: &request::Request,
+    regex_manager: &mut RegexManager,
+) -> bool {
+    let Some(value) = opt_ip_address else {
+        return true;
+    };
+    let Some(address) = request.ip_address else {
+        return false;
+    };
+    regex_manager
+        .get_ip_address_value(value)
+        .is_some_and(|value| value.matches(address, regex_manager))
+}
+
+/// Checks the record type of a DNS query, e.g. `AAAA`, against the filter's `$dnstype` option.
+/// Record type names are compared case-insensitively.
+#[inline]
+pub fn check_dns_type(
+    opt_dns_type: Option<&str>,
+    query_type: &str,
+    regex_manager: &mut RegexManager,
+) -> bool {
+    let Some(value) = opt_dns_type else {
+        return true;
+    };
+    let Some(dns_types) = regex_manager.get_dns_types(value) else {
+        return false;
+    };
+    let mut any_included = false;
+    for &(included, ref dns_type) in dns_types {
+        if dns_type.eq_ignore_ascii_case(query_type) {
+            return included;
         }
+        any_included |= included;
     }
+    !any_included
+}
 
-    true
+/// Checks the client that sent a DNS query against the filter's `$client` option. Filters that
+/// include specific clients never match queries from an unknown client.
+#[inline]
+pub fn check_client(
+    opt_client: Option<&str>,
+    client_name: Option<&str>,
+    client_address: Option<IpAddr>,
+    regex_manager: &mut RegexManager,
+) -> bool {
+    let Some(value) = opt_client else {
+        return true;
+    };
+    let Some(clients) = regex_manager.get_clients(value) else {
+        return false;
+    };
+    let mut any_included = false;
+    for &(included, ref client) in clients {
+        if client.matches(client_name, client_address) {
+            return included;
+        }
+        any_included |= included;
+    }
+    !any_included
 }
 
 #[cfg(test)]
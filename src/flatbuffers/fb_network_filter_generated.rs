@@ -24,18 +24,29 @@ pub mod fb {
 
     impl<'a> NetworkFilter<'a> {
         pub const VT_MASK: ::flatbuffers::VOffsetT = 4;
-        pub const VT_OPT_DOMAINS: ::flatbuffers::VOffsetT = 6;
-        pub const VT_OPT_NOT_DOMAINS: ::flatbuffers::VOffsetT = 8;
-        pub const VT_OPT_TO_DOMAINS: ::flatbuffers::VOffsetT = 10;
-        pub const VT_OPT_NOT_TO_DOMAINS: ::flatbuffers::VOffsetT = 12;
-        pub const VT_SINGLE_PATTERN: ::flatbuffers::VOffsetT = 14;
-        pub const VT_MULTI_PATTERNS: ::flatbuffers::VOffsetT = 16;
-        pub const VT_MODIFIER_OPTION: ::flatbuffers::VOffsetT = 18;
-        pub const VT_HOSTNAME: ::flatbuffers::VOffsetT = 20;
-        pub const VT_TAG: ::flatbuffers::VOffsetT = 22;
-        pub const VT_RAW_LINE: ::flatbuffers::VOffsetT = 24;
-        pub const VT_SOURCE_INDEX: ::flatbuffers::VOffsetT = 26;
-        pub const VT_LINE_NUMBER: ::flatbuffers::VOffsetT = 28;
+        pub const VT_METHODS: ::flatbuffers::VOffsetT = 6;
+        pub const VT_OPT_DOMAINS: ::flatbuffers::VOffsetT = 8;
+        pub const VT_OPT_NOT_DOMAINS: ::flatbuffers::VOffsetT = 10;
+        pub const VT_OPT_TO_DOMAINS: ::flatbuffers::VOffsetT = 12;
+        pub const VT_OPT_NOT_TO_DOMAINS: ::flatbuffers::VOffsetT = 14;
+        pub const VT_OPT_DENYALLOW_DOMAINS: ::flatbuffers::VOffsetT = 16;
+        pub const VT_OPT_ENTITIES: ::flatbuffers::VOffsetT = 18;
+        pub const VT_OPT_NOT_ENTITIES: ::flatbuffers::VOffsetT = 20;
+        pub const VT_OPT_TO_ENTITIES: ::flatbuffers::VOffsetT = 22;
+        pub const VT_OPT_NOT_TO_ENTITIES: ::flatbuffers::VOffsetT = 24;
+        pub const VT_OPT_DOMAIN_REGEXES: ::flatbuffers::VOffsetT = 26;
+        pub const VT_OPT_NOT_DOMAIN_REGEXES: ::flatbuffers::VOffsetT = 28;
+        pub const VT_OPT_IP_ADDRESS: ::flatbuffers::VOffsetT = 30;
+        pub const VT_OPT_DNS_TYPE: ::flatbuffers::VOffsetT = 32;
+        pub const VT_OPT_CLIENT: ::flatbuffers::VOffsetT = 34;
+        pub const VT_SINGLE_PATTERN: ::flatbuffers::VOffsetT = 36;
+        pub const VT_MULTI_PATTERNS: ::flatbuffers::VOffsetT = 38;
+        pub const VT_MODIFIER_OPTION: ::flatbuffers::VOffsetT = 40;
+        pub const VT_HOSTNAME: ::flatbuffers::VOffsetT = 42;
+        pub const VT_TAG: ::flatbuffers::VOffsetT = 44;
+        pub const VT_RAW_LINE: ::flatbuffers::VOffsetT = 46;
+        pub const VT_SOURCE_INDEX: ::flatbuffers::VOffsetT = 48;
+        pub const VT_LINE_NUMBER: ::flatbuffers::VOffsetT = 50;
 
         #[inline]
         pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
@@ -72,6 +83,36 @@ pub mod fb {
             if let Some(x) = args.single_pattern {
                 builder.add_single_pattern(x);
             }
+            if let Some(x) = args.opt_client {
+                builder.add_opt_client(x);
+            }
+            if let Some(x) = args.opt_dns_type {
+                builder.add_opt_dns_type(x);
+            }
+            if let Some(x) = args.opt_ip_address {
+                builder.add_opt_ip_address(x);
+            }
+            if let Some(x) = args.opt_not_domain_regexes {
+                builder.add_opt_not_domain_regexes(x);
+            }
+            if let Some(x) = args.opt_domain_regexes {
+                builder.add_opt_domain_regexes(x);
+            }
+            if let Some(x) = args.opt_not_to_entities {
+                builder.add_opt_not_to_entities(x);
+            }
+            if let Some(x) = args.opt_to_entities {
+                builder.add_opt_to_entities(x);
+            }
+            if let Some(x) = args.opt_not_entities {
+                builder.add_opt_not_entities(x);
+            }
+            if let Some(x) = args.opt_entities {
+                builder.add_opt_entities(x);
+            }
+            if let Some(x) = args.opt_denyallow_domains {
+                builder.add_opt_denyallow_domains(x);
+            }
             if let Some(x) = args.opt_not_to_domains {
                 builder.add_opt_not_to_domains(x);
             }
@@ -85,15 +126,43 @@ pub mod fb {
                 builder.add_opt_domains(x);
             }
             builder.add_mask(args.mask);
+            builder.add_methods(args.methods);
             builder.finish()
         }
 
         pub fn unpack(&self) -> NetworkFilterT {
             let mask = self.mask();
+            let methods = self.methods();
             let opt_domains = self.opt_domains().map(|x| x.into_iter().collect());
             let opt_not_domains = self.opt_not_domains().map(|x| x.into_iter().collect());
             let opt_to_domains = self.opt_to_domains().map(|x| x.into_iter().collect());
             let opt_not_to_domains = self.opt_not_to_domains().map(|x| x.into_iter().collect());
+            let opt_denyallow_domains = self
+                .opt_denyallow_domains()
+                .map(|x| x.into_iter().collect());
+            let opt_entities = self.opt_entities().map(|x| x.into_iter().collect());
+            let opt_not_entities = self.opt_not_entities().map(|x| x.into_iter().collect());
+            let opt_to_entities = self.opt_to_entities().map(|x| x.into_iter().collect());
+            let opt_not_to_entities = self.opt_not_to_entities().map(|x| x.into_iter().collect());
+            let opt_domain_regexes = self.opt_domain_regexes().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            let opt_not_domain_regexes = self.opt_not_domain_regexes().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            let opt_ip_address = self
+                .opt_ip_address()
+                .map(|x| alloc::string::ToString::to_string(x));
+            let opt_dns_type = self
+                .opt_dns_type()
+                .map(|x| alloc::string::ToString::to_string(x));
+            let opt_client = self
+                .opt_client()
+                .map(|x| alloc::string::ToString::to_string(x));
             let single_pattern = self
                 .single_pattern()
                 .map(|x| alloc::string::ToString::to_string(x));
@@ -116,10 +185,21 @@ pub mod fb {
             let line_number = self.line_number();
             NetworkFilterT {
                 mask,
+                methods,
                 opt_domains,
                 opt_not_domains,
                 opt_to_domains,
                 opt_not_to_domains,
+                opt_denyallow_domains,
+                opt_entities,
+                opt_not_entities,
+                opt_to_entities,
+                opt_not_to_entities,
+                opt_domain_regexes,
+                opt_not_domain_regexes,
+                opt_ip_address,
+                opt_dns_type,
+                opt_client,
                 single_pattern,
                 multi_patterns,
                 modifier_option,
@@ -142,6 +222,19 @@ pub mod fb {
                     .unwrap()
             }
         }
+        /// NetworkFilterMethodMask (network.rs), restricting the HTTP methods the
+        /// filter applies to. Empty if the filter applies to any method.
+        #[inline]
+        pub fn methods(&self) -> u8 {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<u8>(NetworkFilter::VT_METHODS, Some(0))
+                    .unwrap()
+            }
+        }
         /// These arrays contain sorted (ascending) indices in the |unique_domains_hashes|
         /// instead of the hashes themselves. This approach saves memory, as there
         /// typically aren’t many unique hashes
@@ -200,6 +293,131 @@ pub mod fb {
             }
         }
         #[inline]
+        pub fn opt_denyallow_domains(&self) -> Option<::flatbuffers::Vector<'a, u32>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u32>>>(
+                        NetworkFilter::VT_OPT_DENYALLOW_DOMAINS,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn opt_entities(&self) -> Option<::flatbuffers::Vector<'a, u32>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u32>>>(
+                        NetworkFilter::VT_OPT_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn opt_not_entities(&self) -> Option<::flatbuffers::Vector<'a, u32>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u32>>>(
+                        NetworkFilter::VT_OPT_NOT_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn opt_to_entities(&self) -> Option<::flatbuffers::Vector<'a, u32>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u32>>>(
+                        NetworkFilter::VT_OPT_TO_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn opt_not_to_entities(&self) -> Option<::flatbuffers::Vector<'a, u32>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u32>>>(
+                        NetworkFilter::VT_OPT_NOT_TO_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn opt_domain_regexes(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(NetworkFilter::VT_OPT_DOMAIN_REGEXES, None)
+            }
+        }
+        #[inline]
+        pub fn opt_not_domain_regexes(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(NetworkFilter::VT_OPT_NOT_DOMAIN_REGEXES, None)
+            }
+        }
+        #[inline]
+        pub fn opt_ip_address(&self) -> Option<&'a str> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(
+                    NetworkFilter::VT_OPT_IP_ADDRESS,
+                    None,
+                )
+            }
+        }
+        #[inline]
+        pub fn opt_dns_type(&self) -> Option<&'a str> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(
+                    NetworkFilter::VT_OPT_DNS_TYPE,
+                    None,
+                )
+            }
+        }
+        #[inline]
+        pub fn opt_client(&self) -> Option<&'a str> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<&str>>(NetworkFilter::VT_OPT_CLIENT, None)
+            }
+        }
+        #[inline]
         pub fn single_pattern(&self) -> Option<&'a str> {
             // Safety:
             // Created from valid Table for this object
@@ -298,6 +516,7 @@ pub mod fb {
         ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
             v.visit_table(pos)?
                 .visit_field::<u32>("mask", Self::VT_MASK, false)?
+                .visit_field::<u8>("methods", Self::VT_METHODS, false)?
                 .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
                     "opt_domains",
                     Self::VT_OPT_DOMAINS,
@@ -318,6 +537,56 @@ pub mod fb {
                     Self::VT_OPT_NOT_TO_DOMAINS,
                     false,
                 )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
+                    "opt_denyallow_domains",
+                    Self::VT_OPT_DENYALLOW_DOMAINS,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
+                    "opt_entities",
+                    Self::VT_OPT_ENTITIES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
+                    "opt_not_entities",
+                    Self::VT_OPT_NOT_ENTITIES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
+                    "opt_to_entities",
+                    Self::VT_OPT_TO_ENTITIES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u32>>>(
+                    "opt_not_to_entities",
+                    Self::VT_OPT_NOT_TO_ENTITIES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>("opt_domain_regexes", Self::VT_OPT_DOMAIN_REGEXES, false)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>(
+                    "opt_not_domain_regexes",
+                    Self::VT_OPT_NOT_DOMAIN_REGEXES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<&str>>(
+                    "opt_ip_address",
+                    Self::VT_OPT_IP_ADDRESS,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<&str>>(
+                    "opt_dns_type",
+                    Self::VT_OPT_DNS_TYPE,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<&str>>(
+                    "opt_client",
+                    Self::VT_OPT_CLIENT,
+                    false,
+                )?
                 .visit_field::<::flatbuffers::ForwardsUOffset<&str>>(
                     "single_pattern",
                     Self::VT_SINGLE_PATTERN,
@@ -350,10 +619,29 @@ pub mod fb {
     }
     pub struct NetworkFilterArgs<'a> {
         pub mask: u32,
+        pub methods: u8,
         pub opt_domains: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
         pub opt_not_domains: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
         pub opt_to_domains: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
         pub opt_not_to_domains: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_denyallow_domains: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_not_entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_to_entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_not_to_entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u32>>>,
+        pub opt_domain_regexes: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
+        pub opt_not_domain_regexes: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
+        pub opt_ip_address: Option<::flatbuffers::WIPOffset<&'a str>>,
+        pub opt_dns_type: Option<::flatbuffers::WIPOffset<&'a str>>,
+        pub opt_client: Option<::flatbuffers::WIPOffset<&'a str>>,
         pub single_pattern: Option<::flatbuffers::WIPOffset<&'a str>>,
         pub multi_patterns: Option<
             ::flatbuffers::WIPOffset<
@@ -372,10 +660,21 @@ pub mod fb {
         fn default() -> Self {
             NetworkFilterArgs {
                 mask: 540221439,
+                methods: 0,
                 opt_domains: None,
                 opt_not_domains: None,
                 opt_to_domains: None,
                 opt_not_to_domains: None,
+                opt_denyallow_domains: None,
+                opt_entities: None,
+                opt_not_entities: None,
+                opt_to_entities: None,
+                opt_not_to_entities: None,
+                opt_domain_regexes: None,
+                opt_not_domain_regexes: None,
+                opt_ip_address: None,
+                opt_dns_type: None,
+                opt_client: None,
                 single_pattern: None,
                 multi_patterns: None,
                 modifier_option: None,
@@ -399,6 +698,11 @@ pub mod fb {
                 .push_slot::<u32>(NetworkFilter::VT_MASK, mask, 540221439);
         }
         #[inline]
+        pub fn add_methods(&mut self, methods: u8) {
+            self.fbb_
+                .push_slot::<u8>(NetworkFilter::VT_METHODS, methods, 0);
+        }
+        #[inline]
         pub fn add_opt_domains(
             &mut self,
             opt_domains: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
@@ -439,6 +743,101 @@ pub mod fb {
             );
         }
         #[inline]
+        pub fn add_opt_denyallow_domains(
+            &mut self,
+            opt_denyallow_domains: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_DENYALLOW_DOMAINS,
+                opt_denyallow_domains,
+            );
+        }
+        #[inline]
+        pub fn add_opt_entities(
+            &mut self,
+            opt_entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_ENTITIES,
+                opt_entities,
+            );
+        }
+        #[inline]
+        pub fn add_opt_not_entities(
+            &mut self,
+            opt_not_entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_NOT_ENTITIES,
+                opt_not_entities,
+            );
+        }
+        #[inline]
+        pub fn add_opt_to_entities(
+            &mut self,
+            opt_to_entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_TO_ENTITIES,
+                opt_to_entities,
+            );
+        }
+        #[inline]
+        pub fn add_opt_not_to_entities(
+            &mut self,
+            opt_not_to_entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u32>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_NOT_TO_ENTITIES,
+                opt_not_to_entities,
+            );
+        }
+        #[inline]
+        pub fn add_opt_domain_regexes(
+            &mut self,
+            opt_domain_regexes: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_DOMAIN_REGEXES,
+                opt_domain_regexes,
+            );
+        }
+        #[inline]
+        pub fn add_opt_not_domain_regexes(
+            &mut self,
+            opt_not_domain_regexes: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_NOT_DOMAIN_REGEXES,
+                opt_not_domain_regexes,
+            );
+        }
+        #[inline]
+        pub fn add_opt_ip_address(&mut self, opt_ip_address: ::flatbuffers::WIPOffset<&'b str>) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_IP_ADDRESS,
+                opt_ip_address,
+            );
+        }
+        #[inline]
+        pub fn add_opt_dns_type(&mut self, opt_dns_type: ::flatbuffers::WIPOffset<&'b str>) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_DNS_TYPE,
+                opt_dns_type,
+            );
+        }
+        #[inline]
+        pub fn add_opt_client(&mut self, opt_client: ::flatbuffers::WIPOffset<&'b str>) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                NetworkFilter::VT_OPT_CLIENT,
+                opt_client,
+            );
+        }
+        #[inline]
         pub fn add_single_pattern(&mut self, single_pattern: ::flatbuffers::WIPOffset<&'b str>) {
             self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
                 NetworkFilter::VT_SINGLE_PATTERN,
@@ -514,10 +913,21 @@ pub mod fb {
         fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
             let mut ds = f.debug_struct("NetworkFilter");
             ds.field("mask", &self.mask());
+            ds.field("methods", &self.methods());
             ds.field("opt_domains", &self.opt_domains());
             ds.field("opt_not_domains", &self.opt_not_domains());
             ds.field("opt_to_domains", &self.opt_to_domains());
             ds.field("opt_not_to_domains", &self.opt_not_to_domains());
+            ds.field("opt_denyallow_domains", &self.opt_denyallow_domains());
+            ds.field("opt_entities", &self.opt_entities());
+            ds.field("opt_not_entities", &self.opt_not_entities());
+            ds.field("opt_to_entities", &self.opt_to_entities());
+            ds.field("opt_not_to_entities", &self.opt_not_to_entities());
+            ds.field("opt_domain_regexes", &self.opt_domain_regexes());
+            ds.field("opt_not_domain_regexes", &self.opt_not_domain_regexes());
+            ds.field("opt_ip_address", &self.opt_ip_address());
+            ds.field("opt_dns_type", &self.opt_dns_type());
+            ds.field("opt_client", &self.opt_client());
             ds.field("single_pattern", &self.single_pattern());
             ds.field("multi_patterns", &self.multi_patterns());
             ds.field("modifier_option", &self.modifier_option());
@@ -533,10 +943,21 @@ pub mod fb {
     #[derive(Debug, Clone, PartialEq)]
     pub struct NetworkFilterT {
         pub mask: u32,
+        pub methods: u8,
         pub opt_domains: Option<alloc::vec::Vec<u32>>,
         pub opt_not_domains: Option<alloc::vec::Vec<u32>>,
         pub opt_to_domains: Option<alloc::vec::Vec<u32>>,
         pub opt_not_to_domains: Option<alloc::vec::Vec<u32>>,
+        pub opt_denyallow_domains: Option<alloc::vec::Vec<u32>>,
+        pub opt_entities: Option<alloc::vec::Vec<u32>>,
+        pub opt_not_entities: Option<alloc::vec::Vec<u32>>,
+        pub opt_to_entities: Option<alloc::vec::Vec<u32>>,
+        pub opt_not_to_entities: Option<alloc::vec::Vec<u32>>,
+        pub opt_domain_regexes: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub opt_not_domain_regexes: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub opt_ip_address: Option<alloc::string::String>,
+        pub opt_dns_type: Option<alloc::string::String>,
+        pub opt_client: Option<alloc::string::String>,
         pub single_pattern: Option<alloc::string::String>,
         pub multi_patterns: Option<alloc::vec::Vec<alloc::string::String>>,
         pub modifier_option: Option<alloc::string::String>,
@@ -550,10 +971,21 @@ pub mod fb {
         fn default() -> Self {
             Self {
                 mask: 540221439,
+                methods: 0,
                 opt_domains: None,
                 opt_not_domains: None,
                 opt_to_domains: None,
                 opt_not_to_domains: None,
+                opt_denyallow_domains: None,
+                opt_entities: None,
+                opt_not_entities: None,
+                opt_to_entities: None,
+                opt_not_to_entities: None,
+                opt_domain_regexes: None,
+                opt_not_domain_regexes: None,
+                opt_ip_address: None,
+                opt_dns_type: None,
+                opt_client: None,
                 single_pattern: None,
                 multi_patterns: None,
                 modifier_option: None,
@@ -571,6 +1003,7 @@ pub mod fb {
             _fbb: &mut ::flatbuffers::FlatBufferBuilder<'b, A>,
         ) -> ::flatbuffers::WIPOffset<NetworkFilter<'b>> {
             let mask = self.mask;
+            let methods = self.methods;
             let opt_domains = self.opt_domains.as_ref().map(|x| _fbb.create_vector(x));
             let opt_not_domains = self.opt_not_domains.as_ref().map(|x| _fbb.create_vector(x));
             let opt_to_domains = self.opt_to_domains.as_ref().map(|x| _fbb.create_vector(x));
@@ -578,6 +1011,31 @@ pub mod fb {
                 .opt_not_to_domains
                 .as_ref()
                 .map(|x| _fbb.create_vector(x));
+            let opt_denyallow_domains = self
+                .opt_denyallow_domains
+                .as_ref()
+                .map(|x| _fbb.create_vector(x));
+            let opt_entities = self.opt_entities.as_ref().map(|x| _fbb.create_vector(x));
+            let opt_not_entities = self
+                .opt_not_entities
+                .as_ref()
+                .map(|x| _fbb.create_vector(x));
+            let opt_to_entities = self.opt_to_entities.as_ref().map(|x| _fbb.create_vector(x));
+            let opt_not_to_entities = self
+                .opt_not_to_entities
+                .as_ref()
+                .map(|x| _fbb.create_vector(x));
+            let opt_domain_regexes = self.opt_domain_regexes.as_ref().map(|x| {
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
+            let opt_not_domain_regexes = self.opt_not_domain_regexes.as_ref().map(|x| {
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
+            let opt_ip_address = self.opt_ip_address.as_ref().map(|x| _fbb.create_string(x));
+            let opt_dns_type = self.opt_dns_type.as_ref().map(|x| _fbb.create_string(x));
+            let opt_client = self.opt_client.as_ref().map(|x| _fbb.create_string(x));
             let single_pattern = self.single_pattern.as_ref().map(|x| _fbb.create_string(x));
             let multi_patterns = self.multi_patterns.as_ref().map(|x| {
                 let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
@@ -593,10 +1051,21 @@ pub mod fb {
                 _fbb,
                 &NetworkFilterArgs {
                     mask,
+                    methods,
                     opt_domains,
                     opt_not_domains,
                     opt_to_domains,
                     opt_not_to_domains,
+                    opt_denyallow_domains,
+                    opt_entities,
+                    opt_not_entities,
+                    opt_to_entities,
+                    opt_not_to_entities,
+                    opt_domain_regexes,
+                    opt_not_domain_regexes,
+                    opt_ip_address,
+                    opt_dns_type,
+                    opt_client,
                     single_pattern,
                     multi_patterns,
                     modifier_option,
@@ -979,6 +1448,8 @@ pub mod fb {
         pub const VT_UNINJECT_SCRIPT: ::flatbuffers::VOffsetT = 6;
         pub const VT_PROCEDURAL_ACTION: ::flatbuffers::VOffsetT = 8;
         pub const VT_PROCEDURAL_ACTION_EXCEPTION: ::flatbuffers::VOffsetT = 10;
+        pub const VT_HTML_FILTER: ::flatbuffers::VOffsetT = 12;
+        pub const VT_HTML_FILTER_EXCEPTION: ::flatbuffers::VOffsetT = 14;
 
         #[inline]
         pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
@@ -995,6 +1466,12 @@ pub mod fb {
             args: &'args HostnameSpecificRulesArgs<'args>,
         ) -> ::flatbuffers::WIPOffset<HostnameSpecificRules<'bldr>> {
             let mut builder = HostnameSpecificRulesBuilder::new(_fbb);
+            if let Some(x) = args.html_filter_exception {
+                builder.add_html_filter_exception(x);
+            }
+            if let Some(x) = args.html_filter {
+                builder.add_html_filter(x);
+            }
             if let Some(x) = args.procedural_action_exception {
                 builder.add_procedural_action_exception(x);
             }
@@ -1031,13 +1508,25 @@ pub mod fb {
                     .map(|s| alloc::string::ToString::to_string(s))
                     .collect()
             });
-            HostnameSpecificRulesT {
-                unhide,
-                uninject_script,
-                procedural_action,
-                procedural_action_exception,
-            }
-        }
+            let html_filter = self.html_filter().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            let html_filter_exception = self.html_filter_exception().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            HostnameSpecificRulesT {
+                unhide,
+                uninject_script,
+                procedural_action,
+                procedural_action_exception,
+                html_filter,
+                html_filter_exception,
+            }
+        }
 
         /// Simple hide exception rules, e.g. `example.com#@#.ad`.
         /// The content is the rule's CSS selector.
@@ -1103,6 +1592,37 @@ pub mod fb {
                 )
             }
         }
+        /// HTML filters, e.g. `example.com##^script:has-text(ads)` or
+        /// `example.com##^responseheader(refresh)`.
+        /// Each is an [`HtmlFilter`] enum serialized as JSON.
+        #[inline]
+        pub fn html_filter(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(HostnameSpecificRules::VT_HTML_FILTER, None)
+            }
+        }
+        /// Exceptions for HTML filters, e.g. `example.com#@#^script:has-text(ads)`.
+        /// Each is an [`HtmlFilter`] enum serialized as JSON.
+        #[inline]
+        pub fn html_filter_exception(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(HostnameSpecificRules::VT_HTML_FILTER_EXCEPTION, None)
+            }
+        }
     }
 
     impl ::flatbuffers::Verifiable for HostnameSpecificRules<'_> {
@@ -1128,6 +1648,16 @@ pub mod fb {
                     Self::VT_PROCEDURAL_ACTION_EXCEPTION,
                     false,
                 )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>("html_filter", Self::VT_HTML_FILTER, false)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>(
+                    "html_filter_exception",
+                    Self::VT_HTML_FILTER_EXCEPTION,
+                    false,
+                )?
                 .finish();
             Ok(())
         }
@@ -1153,6 +1683,16 @@ pub mod fb {
                 ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
             >,
         >,
+        pub html_filter: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
+        pub html_filter_exception: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
     }
     impl<'a> Default for HostnameSpecificRulesArgs<'a> {
         #[inline]
@@ -1162,6 +1702,8 @@ pub mod fb {
                 uninject_script: None,
                 procedural_action: None,
                 procedural_action_exception: None,
+                html_filter: None,
+                html_filter_exception: None,
             }
         }
     }
@@ -1220,6 +1762,30 @@ pub mod fb {
             );
         }
         #[inline]
+        pub fn add_html_filter(
+            &mut self,
+            html_filter: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                HostnameSpecificRules::VT_HTML_FILTER,
+                html_filter,
+            );
+        }
+        #[inline]
+        pub fn add_html_filter_exception(
+            &mut self,
+            html_filter_exception: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                HostnameSpecificRules::VT_HTML_FILTER_EXCEPTION,
+                html_filter_exception,
+            );
+        }
+        #[inline]
         pub fn new(
             _fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
         ) -> HostnameSpecificRulesBuilder<'a, 'b, A> {
@@ -1246,6 +1812,8 @@ pub mod fb {
                 "procedural_action_exception",
                 &self.procedural_action_exception(),
             );
+            ds.field("html_filter", &self.html_filter());
+            ds.field("html_filter_exception", &self.html_filter_exception());
             ds.finish()
         }
     }
@@ -1256,6 +1824,8 @@ pub mod fb {
         pub uninject_script: Option<alloc::vec::Vec<alloc::string::String>>,
         pub procedural_action: Option<alloc::vec::Vec<alloc::string::String>>,
         pub procedural_action_exception: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub html_filter: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub html_filter_exception: Option<alloc::vec::Vec<alloc::string::String>>,
     }
     impl Default for HostnameSpecificRulesT {
         fn default() -> Self {
@@ -1264,6 +1834,8 @@ pub mod fb {
                 uninject_script: None,
                 procedural_action: None,
                 procedural_action_exception: None,
+                html_filter: None,
+                html_filter_exception: None,
             }
         }
     }
@@ -1288,6 +1860,14 @@ pub mod fb {
                 let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
                 _fbb.create_vector(&w)
             });
+            let html_filter = self.html_filter.as_ref().map(|x| {
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
+            let html_filter_exception = self.html_filter_exception.as_ref().map(|x| {
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
             HostnameSpecificRules::create(
                 _fbb,
                 &HostnameSpecificRulesArgs {
@@ -1295,6 +1875,8 @@ pub mod fb {
                     uninject_script,
                     procedural_action,
                     procedural_action_exception,
+                    html_filter,
+                    html_filter_exception,
                 },
             )
         }
@@ -1309,155 +1891,649 @@ pub mod fb {
     impl<'a> ::flatbuffers::Follow<'a> for StringVector<'a> {
         type Inner = StringVector<'a>;
         #[inline]
-        unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
-            Self {
-                _tab: unsafe { ::flatbuffers::Table::new(buf, loc) },
+        unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+            Self {
+                _tab: unsafe { ::flatbuffers::Table::new(buf, loc) },
+            }
+        }
+    }
+
+    impl<'a> StringVector<'a> {
+        pub const VT_DATA: ::flatbuffers::VOffsetT = 4;
+
+        #[inline]
+        pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+            StringVector { _tab: table }
+        }
+        #[allow(unused_mut)]
+        pub fn create<
+            'bldr: 'args,
+            'args: 'mut_bldr,
+            'mut_bldr,
+            A: ::flatbuffers::Allocator + 'bldr,
+        >(
+            _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+            args: &'args StringVectorArgs<'args>,
+        ) -> ::flatbuffers::WIPOffset<StringVector<'bldr>> {
+            let mut builder = StringVectorBuilder::new(_fbb);
+            if let Some(x) = args.data {
+                builder.add_data(x);
+            }
+            builder.finish()
+        }
+
+        pub fn unpack(&self) -> StringVectorT {
+            let data = {
+                let x = self.data();
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            };
+            StringVectorT { data }
+        }
+
+        #[inline]
+        pub fn data(&self) -> ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<
+                        ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                    >>(StringVector::VT_DATA, None)
+                    .unwrap()
+            }
+        }
+    }
+
+    impl ::flatbuffers::Verifiable for StringVector<'_> {
+        #[inline]
+        fn run_verifier(
+            v: &mut ::flatbuffers::Verifier,
+            pos: usize,
+        ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
+            v.visit_table(pos)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>("data", Self::VT_DATA, true)?
+                .finish();
+            Ok(())
+        }
+    }
+    pub struct StringVectorArgs<'a> {
+        pub data: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
+    }
+    impl<'a> Default for StringVectorArgs<'a> {
+        #[inline]
+        fn default() -> Self {
+            StringVectorArgs {
+                data: None, // required field
+            }
+        }
+    }
+
+    pub struct StringVectorBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+        fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+        start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
+    }
+    impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> StringVectorBuilder<'a, 'b, A> {
+        #[inline]
+        pub fn add_data(
+            &mut self,
+            data: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_
+                .push_slot_always::<::flatbuffers::WIPOffset<_>>(StringVector::VT_DATA, data);
+        }
+        #[inline]
+        pub fn new(
+            _fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
+        ) -> StringVectorBuilder<'a, 'b, A> {
+            let start = _fbb.start_table();
+            StringVectorBuilder {
+                fbb_: _fbb,
+                start_: start,
+            }
+        }
+        #[inline]
+        pub fn finish(self) -> ::flatbuffers::WIPOffset<StringVector<'a>> {
+            let o = self.fbb_.end_table(self.start_);
+            self.fbb_.required(o, StringVector::VT_DATA, "data");
+            ::flatbuffers::WIPOffset::new(o.value())
+        }
+    }
+
+    impl ::core::fmt::Debug for StringVector<'_> {
+        fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+            let mut ds = f.debug_struct("StringVector");
+            ds.field("data", &self.data());
+            ds.finish()
+        }
+    }
+    #[non_exhaustive]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StringVectorT {
+        pub data: alloc::vec::Vec<alloc::string::String>,
+    }
+    impl Default for StringVectorT {
+        fn default() -> Self {
+            Self {
+                data: Default::default(),
+            }
+        }
+    }
+    impl StringVectorT {
+        pub fn pack<'b, A: ::flatbuffers::Allocator + 'b>(
+            &self,
+            _fbb: &mut ::flatbuffers::FlatBufferBuilder<'b, A>,
+        ) -> ::flatbuffers::WIPOffset<StringVector<'b>> {
+            let data = Some({
+                let x = &self.data;
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
+            StringVector::create(_fbb, &StringVectorArgs { data })
+        }
+    }
+    pub enum ScopedCosmeticFilterOffset {}
+    #[derive(Copy, Clone, PartialEq)]
+
+    /// A cosmetic rule that can't be looked up by hostname alone, because it has a `[$path=...]`
+    /// modifier or a regex hostname location, e.g. `[$path=/watch]example.com##.ad` or
+    /// `/^example\.(com|net)$/##.ad`. These are rare, so each is checked individually.
+    pub struct ScopedCosmeticFilter<'a> {
+        pub _tab: ::flatbuffers::Table<'a>,
+    }
+
+    impl<'a> ::flatbuffers::Follow<'a> for ScopedCosmeticFilter<'a> {
+        type Inner = ScopedCosmeticFilter<'a>;
+        #[inline]
+        unsafe fn follow(buf: &'a [u8], loc: usize) -> Self::Inner {
+            Self {
+                _tab: unsafe { ::flatbuffers::Table::new(buf, loc) },
+            }
+        }
+    }
+
+    impl<'a> ScopedCosmeticFilter<'a> {
+        pub const VT_HOSTNAMES: ::flatbuffers::VOffsetT = 4;
+        pub const VT_NOT_HOSTNAMES: ::flatbuffers::VOffsetT = 6;
+        pub const VT_ENTITIES: ::flatbuffers::VOffsetT = 8;
+        pub const VT_NOT_ENTITIES: ::flatbuffers::VOffsetT = 10;
+        pub const VT_HOSTNAME_REGEXES: ::flatbuffers::VOffsetT = 12;
+        pub const VT_NOT_HOSTNAME_REGEXES: ::flatbuffers::VOffsetT = 14;
+        pub const VT_PATH: ::flatbuffers::VOffsetT = 16;
+        pub const VT_KIND: ::flatbuffers::VOffsetT = 18;
+        pub const VT_RULE: ::flatbuffers::VOffsetT = 20;
+
+        #[inline]
+        pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
+            ScopedCosmeticFilter { _tab: table }
+        }
+        #[allow(unused_mut)]
+        pub fn create<
+            'bldr: 'args,
+            'args: 'mut_bldr,
+            'mut_bldr,
+            A: ::flatbuffers::Allocator + 'bldr,
+        >(
+            _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
+            args: &'args ScopedCosmeticFilterArgs<'args>,
+        ) -> ::flatbuffers::WIPOffset<ScopedCosmeticFilter<'bldr>> {
+            let mut builder = ScopedCosmeticFilterBuilder::new(_fbb);
+            if let Some(x) = args.rule {
+                builder.add_rule(x);
+            }
+            if let Some(x) = args.path {
+                builder.add_path(x);
+            }
+            if let Some(x) = args.not_hostname_regexes {
+                builder.add_not_hostname_regexes(x);
+            }
+            if let Some(x) = args.hostname_regexes {
+                builder.add_hostname_regexes(x);
+            }
+            if let Some(x) = args.not_entities {
+                builder.add_not_entities(x);
+            }
+            if let Some(x) = args.entities {
+                builder.add_entities(x);
+            }
+            if let Some(x) = args.not_hostnames {
+                builder.add_not_hostnames(x);
+            }
+            if let Some(x) = args.hostnames {
+                builder.add_hostnames(x);
+            }
+            builder.add_kind(args.kind);
+            builder.finish()
+        }
+
+        pub fn unpack(&self) -> ScopedCosmeticFilterT {
+            let hostnames = self.hostnames().map(|x| x.into_iter().collect());
+            let not_hostnames = self.not_hostnames().map(|x| x.into_iter().collect());
+            let entities = self.entities().map(|x| x.into_iter().collect());
+            let not_entities = self.not_entities().map(|x| x.into_iter().collect());
+            let hostname_regexes = self.hostname_regexes().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            let not_hostname_regexes = self.not_hostname_regexes().map(|x| {
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            });
+            let path = self.path().map(|x| alloc::string::ToString::to_string(x));
+            let kind = self.kind();
+            let rule = {
+                let x = self.rule();
+                alloc::string::ToString::to_string(x)
+            };
+            ScopedCosmeticFilterT {
+                hostnames,
+                not_hostnames,
+                entities,
+                not_entities,
+                hostname_regexes,
+                not_hostname_regexes,
+                path,
+                kind,
+                rule,
+            }
+        }
+
+        /// Hashes of the rule's hostnames and entities, as in [`CosmeticFilter`]. Each is sorted.
+        #[inline]
+        pub fn hostnames(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(
+                        ScopedCosmeticFilter::VT_HOSTNAMES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn not_hostnames(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(
+                        ScopedCosmeticFilter::VT_NOT_HOSTNAMES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn entities(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(
+                        ScopedCosmeticFilter::VT_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        #[inline]
+        pub fn not_entities(&self) -> Option<::flatbuffers::Vector<'a, u64>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'a, u64>>>(
+                        ScopedCosmeticFilter::VT_NOT_ENTITIES,
+                        None,
+                    )
+            }
+        }
+        /// Regexes matched against the page's hostname, without the surrounding slashes.
+        #[inline]
+        pub fn hostname_regexes(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(ScopedCosmeticFilter::VT_HOSTNAME_REGEXES, None)
             }
         }
-    }
-
-    impl<'a> StringVector<'a> {
-        pub const VT_DATA: ::flatbuffers::VOffsetT = 4;
-
         #[inline]
-        pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
-            StringVector { _tab: table }
+        pub fn not_hostname_regexes(
+            &self,
+        ) -> Option<::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                >>(ScopedCosmeticFilter::VT_NOT_HOSTNAME_REGEXES, None)
+            }
         }
-        #[allow(unused_mut)]
-        pub fn create<
-            'bldr: 'args,
-            'args: 'mut_bldr,
-            'mut_bldr,
-            A: ::flatbuffers::Allocator + 'bldr,
-        >(
-            _fbb: &'mut_bldr mut ::flatbuffers::FlatBufferBuilder<'bldr, A>,
-            args: &'args StringVectorArgs<'args>,
-        ) -> ::flatbuffers::WIPOffset<StringVector<'bldr>> {
-            let mut builder = StringVectorBuilder::new(_fbb);
-            if let Some(x) = args.data {
-                builder.add_data(x);
+        /// The value of the `[$path=...]` modifier, matched against the page's path and query.
+        #[inline]
+        pub fn path(&self) -> Option<&'a str> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab.get::<::flatbuffers::ForwardsUOffset<&str>>(
+                    ScopedCosmeticFilter::VT_PATH,
+                    None,
+                )
             }
-            builder.finish()
         }
-
-        pub fn unpack(&self) -> StringVectorT {
-            let data = {
-                let x = self.data();
-                x.iter()
-                    .map(|s| alloc::string::ToString::to_string(s))
-                    .collect()
-            };
-            StringVectorT { data }
+        /// One of the `SCOPED_*` constants from `cosmetic_filter_utils`.
+        #[inline]
+        pub fn kind(&self) -> u8 {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<u8>(ScopedCosmeticFilter::VT_KIND, Some(0))
+                    .unwrap()
+            }
         }
-
+        /// The contents of the rule, encoded the same way as the corresponding hostname-specific rule.
         #[inline]
-        pub fn data(&self) -> ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>> {
+        pub fn rule(&self) -> &'a str {
             // Safety:
             // Created from valid Table for this object
             // which contains a valid value in this slot
             unsafe {
                 self._tab
-                    .get::<::flatbuffers::ForwardsUOffset<
-                        ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
-                    >>(StringVector::VT_DATA, None)
+                    .get::<::flatbuffers::ForwardsUOffset<&str>>(
+                        ScopedCosmeticFilter::VT_RULE,
+                        None,
+                    )
                     .unwrap()
             }
         }
     }
 
-    impl ::flatbuffers::Verifiable for StringVector<'_> {
+    impl ::flatbuffers::Verifiable for ScopedCosmeticFilter<'_> {
         #[inline]
         fn run_verifier(
             v: &mut ::flatbuffers::Verifier,
             pos: usize,
         ) -> Result<(), ::flatbuffers::InvalidFlatbuffer> {
             v.visit_table(pos)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>(
+                    "hostnames",
+                    Self::VT_HOSTNAMES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>(
+                    "not_hostnames",
+                    Self::VT_NOT_HOSTNAMES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>(
+                    "entities",
+                    Self::VT_ENTITIES,
+                    false,
+                )?
+                .visit_field::<::flatbuffers::ForwardsUOffset<::flatbuffers::Vector<'_, u64>>>(
+                    "not_entities",
+                    Self::VT_NOT_ENTITIES,
+                    false,
+                )?
                 .visit_field::<::flatbuffers::ForwardsUOffset<
                     ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
-                >>("data", Self::VT_DATA, true)?
+                >>("hostname_regexes", Self::VT_HOSTNAME_REGEXES, false)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>("not_hostname_regexes", Self::VT_NOT_HOSTNAME_REGEXES, false)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("path", Self::VT_PATH, false)?
+                .visit_field::<u8>("kind", Self::VT_KIND, false)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<&str>>("rule", Self::VT_RULE, true)?
                 .finish();
             Ok(())
         }
     }
-    pub struct StringVectorArgs<'a> {
-        pub data: Option<
+    pub struct ScopedCosmeticFilterArgs<'a> {
+        pub hostnames: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+        pub not_hostnames: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+        pub entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+        pub not_entities: Option<::flatbuffers::WIPOffset<::flatbuffers::Vector<'a, u64>>>,
+        pub hostname_regexes: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
+        pub not_hostname_regexes: Option<
             ::flatbuffers::WIPOffset<
                 ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
             >,
         >,
+        pub path: Option<::flatbuffers::WIPOffset<&'a str>>,
+        pub kind: u8,
+        pub rule: Option<::flatbuffers::WIPOffset<&'a str>>,
     }
-    impl<'a> Default for StringVectorArgs<'a> {
+    impl<'a> Default for ScopedCosmeticFilterArgs<'a> {
         #[inline]
         fn default() -> Self {
-            StringVectorArgs {
-                data: None, // required field
+            ScopedCosmeticFilterArgs {
+                hostnames: None,
+                not_hostnames: None,
+                entities: None,
+                not_entities: None,
+                hostname_regexes: None,
+                not_hostname_regexes: None,
+                path: None,
+                kind: 0,
+                rule: None, // required field
             }
         }
     }
 
-    pub struct StringVectorBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
+    pub struct ScopedCosmeticFilterBuilder<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> {
         fbb_: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
         start_: ::flatbuffers::WIPOffset<::flatbuffers::TableUnfinishedWIPOffset>,
     }
-    impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> StringVectorBuilder<'a, 'b, A> {
+    impl<'a: 'b, 'b, A: ::flatbuffers::Allocator + 'a> ScopedCosmeticFilterBuilder<'a, 'b, A> {
         #[inline]
-        pub fn add_data(
+        pub fn add_hostnames(
             &mut self,
-            data: ::flatbuffers::WIPOffset<
+            hostnames: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u64>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_HOSTNAMES,
+                hostnames,
+            );
+        }
+        #[inline]
+        pub fn add_not_hostnames(
+            &mut self,
+            not_hostnames: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u64>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_NOT_HOSTNAMES,
+                not_hostnames,
+            );
+        }
+        #[inline]
+        pub fn add_entities(
+            &mut self,
+            entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u64>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_ENTITIES,
+                entities,
+            );
+        }
+        #[inline]
+        pub fn add_not_entities(
+            &mut self,
+            not_entities: ::flatbuffers::WIPOffset<::flatbuffers::Vector<'b, u64>>,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_NOT_ENTITIES,
+                not_entities,
+            );
+        }
+        #[inline]
+        pub fn add_hostname_regexes(
+            &mut self,
+            hostname_regexes: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_HOSTNAME_REGEXES,
+                hostname_regexes,
+            );
+        }
+        #[inline]
+        pub fn add_not_hostname_regexes(
+            &mut self,
+            not_hostname_regexes: ::flatbuffers::WIPOffset<
                 ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
             >,
         ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_NOT_HOSTNAME_REGEXES,
+                not_hostname_regexes,
+            );
+        }
+        #[inline]
+        pub fn add_path(&mut self, path: ::flatbuffers::WIPOffset<&'b str>) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_PATH,
+                path,
+            );
+        }
+        #[inline]
+        pub fn add_kind(&mut self, kind: u8) {
             self.fbb_
-                .push_slot_always::<::flatbuffers::WIPOffset<_>>(StringVector::VT_DATA, data);
+                .push_slot::<u8>(ScopedCosmeticFilter::VT_KIND, kind, 0);
+        }
+        #[inline]
+        pub fn add_rule(&mut self, rule: ::flatbuffers::WIPOffset<&'b str>) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                ScopedCosmeticFilter::VT_RULE,
+                rule,
+            );
         }
         #[inline]
         pub fn new(
             _fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
-        ) -> StringVectorBuilder<'a, 'b, A> {
+        ) -> ScopedCosmeticFilterBuilder<'a, 'b, A> {
             let start = _fbb.start_table();
-            StringVectorBuilder {
+            ScopedCosmeticFilterBuilder {
                 fbb_: _fbb,
                 start_: start,
             }
         }
         #[inline]
-        pub fn finish(self) -> ::flatbuffers::WIPOffset<StringVector<'a>> {
+        pub fn finish(self) -> ::flatbuffers::WIPOffset<ScopedCosmeticFilter<'a>> {
             let o = self.fbb_.end_table(self.start_);
-            self.fbb_.required(o, StringVector::VT_DATA, "data");
+            self.fbb_.required(o, ScopedCosmeticFilter::VT_RULE, "rule");
             ::flatbuffers::WIPOffset::new(o.value())
         }
     }
 
-    impl ::core::fmt::Debug for StringVector<'_> {
+    impl ::core::fmt::Debug for ScopedCosmeticFilter<'_> {
         fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-            let mut ds = f.debug_struct("StringVector");
-            ds.field("data", &self.data());
+            let mut ds = f.debug_struct("ScopedCosmeticFilter");
+            ds.field("hostnames", &self.hostnames());
+            ds.field("not_hostnames", &self.not_hostnames());
+            ds.field("entities", &self.entities());
+            ds.field("not_entities", &self.not_entities());
+            ds.field("hostname_regexes", &self.hostname_regexes());
+            ds.field("not_hostname_regexes", &self.not_hostname_regexes());
+            ds.field("path", &self.path());
+            ds.field("kind", &self.kind());
+            ds.field("rule", &self.rule());
             ds.finish()
         }
     }
     #[non_exhaustive]
     #[derive(Debug, Clone, PartialEq)]
-    pub struct StringVectorT {
-        pub data: alloc::vec::Vec<alloc::string::String>,
+    pub struct ScopedCosmeticFilterT {
+        pub hostnames: Option<alloc::vec::Vec<u64>>,
+        pub not_hostnames: Option<alloc::vec::Vec<u64>>,
+        pub entities: Option<alloc::vec::Vec<u64>>,
+        pub not_entities: Option<alloc::vec::Vec<u64>>,
+        pub hostname_regexes: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub not_hostname_regexes: Option<alloc::vec::Vec<alloc::string::String>>,
+        pub path: Option<alloc::string::String>,
+        pub kind: u8,
+        pub rule: alloc::string::String,
     }
-    impl Default for StringVectorT {
+    impl Default for ScopedCosmeticFilterT {
         fn default() -> Self {
             Self {
-                data: Default::default(),
+                hostnames: None,
+                not_hostnames: None,
+                entities: None,
+                not_entities: None,
+                hostname_regexes: None,
+                not_hostname_regexes: None,
+                path: None,
+                kind: 0,
+                rule: Default::default(),
             }
         }
     }
-    impl StringVectorT {
+    impl ScopedCosmeticFilterT {
         pub fn pack<'b, A: ::flatbuffers::Allocator + 'b>(
             &self,
             _fbb: &mut ::flatbuffers::FlatBufferBuilder<'b, A>,
-        ) -> ::flatbuffers::WIPOffset<StringVector<'b>> {
-            let data = Some({
-                let x = &self.data;
+        ) -> ::flatbuffers::WIPOffset<ScopedCosmeticFilter<'b>> {
+            let hostnames = self.hostnames.as_ref().map(|x| _fbb.create_vector(x));
+            let not_hostnames = self.not_hostnames.as_ref().map(|x| _fbb.create_vector(x));
+            let entities = self.entities.as_ref().map(|x| _fbb.create_vector(x));
+            let not_entities = self.not_entities.as_ref().map(|x| _fbb.create_vector(x));
+            let hostname_regexes = self.hostname_regexes.as_ref().map(|x| {
                 let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
                 _fbb.create_vector(&w)
             });
-            StringVector::create(_fbb, &StringVectorArgs { data })
+            let not_hostname_regexes = self.not_hostname_regexes.as_ref().map(|x| {
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
+            let path = self.path.as_ref().map(|x| _fbb.create_string(x));
+            let kind = self.kind;
+            let rule = Some({
+                let x = &self.rule;
+                _fbb.create_string(x)
+            });
+            ScopedCosmeticFilter::create(
+                _fbb,
+                &ScopedCosmeticFilterArgs {
+                    hostnames,
+                    not_hostnames,
+                    entities,
+                    not_entities,
+                    hostname_regexes,
+                    not_hostname_regexes,
+                    path,
+                    kind,
+                    rule,
+                },
+            )
         }
     }
     pub enum CosmeticFiltersOffset {}
@@ -1492,6 +2568,8 @@ pub mod fb {
         pub const VT_HOSTNAME_INJECT_SCRIPT_VALUES: ::flatbuffers::VOffsetT = 24;
         pub const VT_HOSTNAME_INDEX: ::flatbuffers::VOffsetT = 26;
         pub const VT_HOSTNAME_VALUES: ::flatbuffers::VOffsetT = 28;
+        pub const VT_SCOPED_FILTERS: ::flatbuffers::VOffsetT = 30;
+        pub const VT_GENERIC_PROCEDURAL_ACTIONS: ::flatbuffers::VOffsetT = 32;
 
         #[inline]
         pub unsafe fn init_from_table(table: ::flatbuffers::Table<'a>) -> Self {
@@ -1508,6 +2586,12 @@ pub mod fb {
             args: &'args CosmeticFiltersArgs<'args>,
         ) -> ::flatbuffers::WIPOffset<CosmeticFilters<'bldr>> {
             let mut builder = CosmeticFiltersBuilder::new(_fbb);
+            if let Some(x) = args.generic_procedural_actions {
+                builder.add_generic_procedural_actions(x);
+            }
+            if let Some(x) = args.scoped_filters {
+                builder.add_scoped_filters(x);
+            }
             if let Some(x) = args.hostname_values {
                 builder.add_hostname_values(x);
             }
@@ -1617,6 +2701,16 @@ pub mod fb {
                 let x = self.hostname_values();
                 x.iter().map(|t| t.unpack()).collect()
             };
+            let scoped_filters = {
+                let x = self.scoped_filters();
+                x.iter().map(|t| t.unpack()).collect()
+            };
+            let generic_procedural_actions = {
+                let x = self.generic_procedural_actions();
+                x.iter()
+                    .map(|s| alloc::string::ToString::to_string(s))
+                    .collect()
+            };
             CosmeticFiltersT {
                 simple_class_rules,
                 simple_id_rules,
@@ -1631,6 +2725,8 @@ pub mod fb {
                 hostname_inject_script_values,
                 hostname_index,
                 hostname_values,
+                scoped_filters,
+                generic_procedural_actions,
             }
         }
 
@@ -1853,6 +2949,44 @@ pub mod fb {
                     .unwrap()
             }
         }
+        /// Rules with a `[$path=...]` modifier or regex hostname locations.
+        #[inline]
+        pub fn scoped_filters(
+            &self,
+        ) -> ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ScopedCosmeticFilter<'a>>>
+        {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<
+                        ::flatbuffers::Vector<
+                            'a,
+                            ::flatbuffers::ForwardsUOffset<ScopedCosmeticFilter>,
+                        >,
+                    >>(CosmeticFilters::VT_SCOPED_FILTERS, None)
+                    .unwrap()
+            }
+        }
+        /// Procedural filters and/or filters with a [`CosmeticFilterAction`] to be applied on all
+        /// sites. Only populated when generic procedural filters are enabled at parse time.
+        /// Each is a [`ProceduralOrActionFilter`] struct serialized as JSON.
+        #[inline]
+        pub fn generic_procedural_actions(
+            &self,
+        ) -> ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>> {
+            // Safety:
+            // Created from valid Table for this object
+            // which contains a valid value in this slot
+            unsafe {
+                self._tab
+                    .get::<::flatbuffers::ForwardsUOffset<
+                        ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+                    >>(CosmeticFilters::VT_GENERIC_PROCEDURAL_ACTIONS, None)
+                    .unwrap()
+            }
+        }
     }
 
     impl ::flatbuffers::Verifiable for CosmeticFilters<'_> {
@@ -1934,6 +3068,16 @@ pub mod fb {
                         ::flatbuffers::ForwardsUOffset<HostnameSpecificRules>,
                     >,
                 >>("hostname_values", Self::VT_HOSTNAME_VALUES, true)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<ScopedCosmeticFilter>>,
+                >>("scoped_filters", Self::VT_SCOPED_FILTERS, true)?
+                .visit_field::<::flatbuffers::ForwardsUOffset<
+                    ::flatbuffers::Vector<'_, ::flatbuffers::ForwardsUOffset<&'_ str>>,
+                >>(
+                    "generic_procedural_actions",
+                    Self::VT_GENERIC_PROCEDURAL_ACTIONS,
+                    true,
+                )?
                 .finish();
             Ok(())
         }
@@ -1996,6 +3140,16 @@ pub mod fb {
                 >,
             >,
         >,
+        pub scoped_filters: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<ScopedCosmeticFilter<'a>>>,
+            >,
+        >,
+        pub generic_procedural_actions: Option<
+            ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'a, ::flatbuffers::ForwardsUOffset<&'a str>>,
+            >,
+        >,
     }
     impl<'a> Default for CosmeticFiltersArgs<'a> {
         #[inline]
@@ -2014,6 +3168,8 @@ pub mod fb {
                 hostname_inject_script_values: None, // required field
                 hostname_index: None,                // required field
                 hostname_values: None,               // required field
+                scoped_filters: None,                // required field
+                generic_procedural_actions: None,    // required field
             }
         }
     }
@@ -2177,6 +3333,30 @@ pub mod fb {
             );
         }
         #[inline]
+        pub fn add_scoped_filters(
+            &mut self,
+            scoped_filters: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<ScopedCosmeticFilter<'b>>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                CosmeticFilters::VT_SCOPED_FILTERS,
+                scoped_filters,
+            );
+        }
+        #[inline]
+        pub fn add_generic_procedural_actions(
+            &mut self,
+            generic_procedural_actions: ::flatbuffers::WIPOffset<
+                ::flatbuffers::Vector<'b, ::flatbuffers::ForwardsUOffset<&'b str>>,
+            >,
+        ) {
+            self.fbb_.push_slot_always::<::flatbuffers::WIPOffset<_>>(
+                CosmeticFilters::VT_GENERIC_PROCEDURAL_ACTIONS,
+                generic_procedural_actions,
+            );
+        }
+        #[inline]
         pub fn new(
             _fbb: &'b mut ::flatbuffers::FlatBufferBuilder<'a, A>,
         ) -> CosmeticFiltersBuilder<'a, 'b, A> {
@@ -2245,6 +3425,13 @@ pub mod fb {
                 .required(o, CosmeticFilters::VT_HOSTNAME_INDEX, "hostname_index");
             self.fbb_
                 .required(o, CosmeticFilters::VT_HOSTNAME_VALUES, "hostname_values");
+            self.fbb_
+                .required(o, CosmeticFilters::VT_SCOPED_FILTERS, "scoped_filters");
+            self.fbb_.required(
+                o,
+                CosmeticFilters::VT_GENERIC_PROCEDURAL_ACTIONS,
+                "generic_procedural_actions",
+            );
             ::flatbuffers::WIPOffset::new(o.value())
         }
     }
@@ -2277,6 +3464,11 @@ pub mod fb {
             );
             ds.field("hostname_index", &self.hostname_index());
             ds.field("hostname_values", &self.hostname_values());
+            ds.field("scoped_filters", &self.scoped_filters());
+            ds.field(
+                "generic_procedural_actions",
+                &self.generic_procedural_actions(),
+            );
             ds.finish()
         }
     }
@@ -2296,6 +3488,8 @@ pub mod fb {
         pub hostname_inject_script_values: alloc::vec::Vec<alloc::string::String>,
         pub hostname_index: alloc::vec::Vec<u64>,
         pub hostname_values: alloc::vec::Vec<HostnameSpecificRulesT>,
+        pub scoped_filters: alloc::vec::Vec<ScopedCosmeticFilterT>,
+        pub generic_procedural_actions: alloc::vec::Vec<alloc::string::String>,
     }
     impl Default for CosmeticFiltersT {
         fn default() -> Self {
@@ -2313,6 +3507,8 @@ pub mod fb {
                 hostname_inject_script_values: Default::default(),
                 hostname_index: Default::default(),
                 hostname_values: Default::default(),
+                scoped_filters: Default::default(),
+                generic_procedural_actions: Default::default(),
             }
         }
     }
@@ -2383,6 +3579,16 @@ pub mod fb {
                 let w: alloc::vec::Vec<_> = x.iter().map(|t| t.pack(_fbb)).collect();
                 _fbb.create_vector(&w)
             });
+            let scoped_filters = Some({
+                let x = &self.scoped_filters;
+                let w: alloc::vec::Vec<_> = x.iter().map(|t| t.pack(_fbb)).collect();
+                _fbb.create_vector(&w)
+            });
+            let generic_procedural_actions = Some({
+                let x = &self.generic_procedural_actions;
+                let w: alloc::vec::Vec<_> = x.iter().map(|s| _fbb.create_string(s)).collect();
+                _fbb.create_vector(&w)
+            });
             CosmeticFilters::create(
                 _fbb,
                 &CosmeticFiltersArgs {
@@ -2399,6 +3605,8 @@ pub mod fb {
                     hostname_inject_script_values,
                     hostname_index,
                     hostname_values,
+                    scoped_filters,
+                    generic_procedural_actions,
                 },
             )
         }
@@ -0,0 +1,296 @@
+//! HTML filtering rules, e.g. `example.com##^script:has-text(adblock)`, act on the response body
+//! of a document before it's parsed by the browser. Matching elements are removed from the
+//! document entirely, which can be used to defuse inline scripts that would otherwise run before
+//! any content script could intervene. Rules of the form `example.com##^responseheader(refresh)`
+//! are also supported, and specify headers to be removed from the document's response; see
+//! [`HtmlFilters::response_headers`].
+//!
+//! The applicable filters for a page can be queried with [`crate::Engine::url_html_filters`].
+//! With the `html-filtering` feature enabled, [`HtmlFilters::rewriter`] can then be used to apply
+//! them to a streamed response body.
+
+use crate::filters::cosmetic::CosmeticFilterOperator;
+
+use serde::{Deserialize, Serialize};
+
+/// A single HTML filter, as stored in the engine's serialized representation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "arg")]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum HtmlFilter {
+    /// Elements matching the selector should be removed from the document, e.g.
+    /// `example.com##^script:has-text(adblock)`.
+    Selector(Vec<CosmeticFilterOperator>),
+    /// The named header should be removed from the document's response, e.g.
+    /// `example.com##^responseheader(refresh)`. The name is always lowercase.
+    ResponseHeader(String),
+}
+
+/// Contains all HTML filters that apply to a particular hostname, after accounting for
+/// exceptions.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HtmlFilters {
+    /// Selectors for elements that should be removed from the document. Each may be a plain CSS
+    /// selector, or it can consist of multiple procedural operators.
+    pub selectors: Vec<Vec<CosmeticFilterOperator>>,
+    /// Lowercase names of headers that should be removed from the document's response.
+    pub response_headers: Vec<String>,
+}
+
+impl HtmlFilters {
+    /// Returns `true` if there are no HTML filters to apply.
+    pub fn is_empty(&self) -> bool {
+        self.selectors.is_empty() && self.response_headers.is_empty()
+    }
+
+    /// Creates a streaming rewriter that removes elements matching any of the `selectors` from an
+    /// HTML document. Rewritten output will be passed to `output` in chunks as it becomes
+    /// available.
+    ///
+    /// Plain CSS selectors are supported, as long as they can be handled by the underlying
+    /// streaming HTML parser. A CSS selector followed by a single `:has-text(...)` or
+    /// `:min-text-length(...)` operator is also supported, but only for elements whose contents
+    /// are raw text, i.e. `<script>`, `<style>`, `<title>`, and `<textarea>`. Any other selectors
+    /// are ignored.
+    #[cfg(feature = "html-filtering")]
+    pub fn rewriter<O: FnMut(&[u8])>(&self, output: O) -> HtmlFilterRewriter<O> {
+        rewriter::build(&self.selectors, output)
+    }
+}
+
+#[cfg(feature = "html-filtering")]
+pub use rewriter::{HtmlFilterRewriter, HtmlRewritingError};
+
+#[cfg(feature = "html-filtering")]
+mod rewriter {
+    use super::CosmeticFilterOperator;
+
+    use lol_html::html_content::{ContentType, Element};
+    use lol_html::{
+        DocumentContentHandlers, ElementContentHandlers, HtmlRewriter, Selector, Settings,
+    };
+    use thiserror::Error;
+
+    use std::borrow::Cow;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Elements whose contents are always parsed as text. Text-based operators can only be applied
+    /// to these, since the rewriter needs to buffer an element's entire contents before deciding
+    /// whether or not to remove it.
+    const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+    /// Failure cases when rewriting a document with [`HtmlFilterRewriter`].
+    #[derive(Debug, Error)]
+    #[error("html rewriting failed: {0}")]
+    pub struct HtmlRewritingError(#[from] lol_html::errors::RewritingError);
+
+    /// Streaming rewriter that removes elements matching a set of HTML filters from a document.
+    /// Create one using [`super::HtmlFilters::rewriter`].
+    pub struct HtmlFilterRewriter<O: FnMut(&[u8])> {
+        inner: HtmlRewriter<'static, O>,
+    }
+
+    impl<O: FnMut(&[u8])> HtmlFilterRewriter<O> {
+        /// Processes the next chunk of the document.
+        pub fn write(&mut self, chunk: &[u8]) -> Result<(), HtmlRewritingError> {
+            Ok(self.inner.write(chunk)?)
+        }
+
+        /// Finishes rewriting the document, flushing any remaining output.
+        pub fn end(self) -> Result<(), HtmlRewritingError> {
+            Ok(self.inner.end()?)
+        }
+    }
+
+    /// Condition on the text contents of an element, from a trailing text-based operator.
+    enum TextCondition {
+        Contains(String),
+        Regex(regex::Regex),
+        MinLength(usize),
+    }
+
+    impl TextCondition {
+        fn from_operator(operator: &CosmeticFilterOperator) -> Option<Self> {
+            match operator {
+                CosmeticFilterOperator::HasText(arg) => {
+                    let regex = arg.strip_prefix('/').and_then(|rest| rest.rsplit_once('/'));
+                    match regex {
+                        Some((pattern, flags)) => regex::RegexBuilder::new(pattern)
+                            .case_insensitive(flags.contains('i'))
+                            .build()
+                            .ok()
+                            .map(Self::Regex),
+                        None => Some(Self::Contains(arg.to_string())),
+                    }
+                }
+                CosmeticFilterOperator::MinTextLength(arg) => {
+                    arg.trim().parse().ok().map(Self::MinLength)
+                }
+                _ => None,
+            }
+        }
+
+        fn matches(&self, text: &str) -> bool {
+            match self {
+                Self::Contains(needle) => text.contains(needle.as_str()),
+                Self::Regex(regex) => regex.is_match(text),
+                Self::MinLength(length) => text.chars().count() >= *length,
+            }
+        }
+    }
+
+    /// A raw text element whose contents are being buffered in order to evaluate text conditions.
+    /// Raw text elements cannot be nested, so there is at most one of these at any point in the
+    /// document.
+    struct PendingElement {
+        start_tag: String,
+        tag_name: String,
+        text: String,
+        conditions: Vec<Rc<TextCondition>>,
+        removed: bool,
+    }
+
+    impl PendingElement {
+        fn into_html(self) -> Option<String> {
+            if self.removed || self.conditions.iter().any(|c| c.matches(&self.text)) {
+                None
+            } else {
+                Some(format!(
+                    "{}{}</{}>",
+                    self.start_tag, self.text, self.tag_name
+                ))
+            }
+        }
+    }
+
+    type SharedPendingElement = Rc<RefCell<Option<PendingElement>>>;
+
+    fn start_tag_html(el: &Element) -> String {
+        let mut html = format!("<{}", el.tag_name());
+        for attr in el.attributes() {
+            html.push(' ');
+            html.push_str(&attr.name());
+            html.push_str("=\"");
+            html.push_str(&attr.value().replace('&', "&amp;").replace('"', "&quot;"));
+            html.push('"');
+        }
+        html.push('>');
+        html
+    }
+
+    pub(super) fn build<O: FnMut(&[u8])>(
+        selectors: &[Vec<CosmeticFilterOperator>],
+        output: O,
+    ) -> HtmlFilterRewriter<O> {
+        let pending: SharedPendingElement = Rc::new(RefCell::new(None));
+
+        let mut removals = vec![];
+        let mut conditional_removals = vec![];
+        for selector in selectors {
+            match &selector[..] {
+                [CosmeticFilterOperator::CssSelector(css)] => {
+                    let Ok(css) = css.parse::<Selector>() else {
+                        continue;
+                    };
+                    let pending = pending.clone();
+                    removals.push((
+                        Cow::Owned(css),
+                        ElementContentHandlers::default().element(move |el: &mut Element| {
+                            el.remove();
+                            if let Some(p) = pending.borrow_mut().as_mut() {
+                                p.removed = true;
+                            }
+                            Ok(())
+                        }),
+                    ));
+                }
+                [CosmeticFilterOperator::CssSelector(css), operator] => {
+                    let (Ok(css), Some(condition)) = (
+                        css.parse::<Selector>(),
+                        TextCondition::from_operator(operator),
+                    ) else {
+                        continue;
+                    };
+                    let condition = Rc::new(condition);
+                    let pending = pending.clone();
+                    conditional_removals.push((
+                        Cow::Owned(css),
+                        ElementContentHandlers::default().element(move |el: &mut Element| {
+                            let tag_name = el.tag_name();
+                            if !RAW_TEXT_ELEMENTS.contains(&tag_name.as_str()) {
+                                return Ok(());
+                            }
+                            let mut current = pending.borrow_mut();
+                            if let Some(p) = current.as_mut() {
+                                // Already being buffered due to another filter.
+                                p.conditions.push(condition.clone());
+                                return Ok(());
+                            }
+                            if el.removed() {
+                                return Ok(());
+                            }
+                            *current = Some(PendingElement {
+                                start_tag: start_tag_html(el),
+                                tag_name,
+                                text: String::new(),
+                                conditions: vec![condition.clone()],
+                                removed: false,
+                            });
+                            el.remove_and_keep_content();
+                            if let Some(handlers) = el.end_tag_handlers() {
+                                let pending = pending.clone();
+                                handlers.push(lol_html::end_tag!(move |end| {
+                                    if let Some(html) =
+                                        pending.borrow_mut().take().and_then(|p| p.into_html())
+                                    {
+                                        end.before(&html, ContentType::Html);
+                                    }
+                                    Ok(())
+                                }));
+                            }
+                            Ok(())
+                        }),
+                    ));
+                }
+                _ => (),
+            }
+        }
+
+        let text_pending = pending.clone();
+        let document_handlers = DocumentContentHandlers::default()
+            .text(move |t: &mut lol_html::html_content::TextChunk| {
+                if let Some(p) = text_pending.borrow_mut().as_mut() {
+                    p.text.push_str(t.as_str());
+                    t.remove();
+                }
+                Ok(())
+            })
+            .end(move |end: &mut lol_html::html_content::DocumentEnd| {
+                // Unterminated elements should still be emitted if they aren't removed.
+                if let Some(html) = pending.borrow_mut().take().and_then(|p| p.into_html()) {
+                    end.append(&html, ContentType::Html);
+                }
+                Ok(())
+            });
+
+        // Unconditional removals are registered first so that conditional handlers can see
+        // whether an element has already been removed.
+        removals.extend(conditional_removals);
+
+        let inner = HtmlRewriter::new(
+            Settings {
+                element_content_handlers: removals,
+                document_content_handlers: vec![document_handlers],
+                ..Settings::new()
+            },
+            output,
+        );
+
+        HtmlFilterRewriter { inner }
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/unit/html_filtering.rs"]
+mod unit_tests;
@@ -28,9 +28,11 @@ mod data_format;
 pub mod engine;
 pub mod filters;
 mod flatbuffers;
+pub mod html_filtering;
 pub mod lists;
 mod network_filter_list;
 mod optimizer;
+mod preprocessor;
 pub mod regex_manager;
 pub mod request;
 pub mod resources;
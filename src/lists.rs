@@ -1,9 +1,11 @@
 //! Parsing functions and collections for handling multiple filter rules.
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 
 use crate::filters::cosmetic::{CosmeticFilter, CosmeticFilterError};
 use crate::filters::network::{NetworkFilter, NetworkFilterError};
+use crate::preprocessor::{PreprocessedLine, Preprocessor};
 use crate::resources::PermissionMask;
 
 use itertools::{Either, Itertools};
@@ -11,6 +13,9 @@ use memchr::memchr as find_char;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+#[doc(inline)]
+pub use crate::preprocessor::PreprocessorEnv;
+
 /// Specifies rule types to keep during parsing.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 pub enum RuleTypes {
@@ -66,6 +71,11 @@ pub struct ParseOptions {
     /// more info.
     #[serde(default)]
     pub permissions: PermissionMask,
+    /// Accept procedural and action cosmetic filters that apply to all sites, like
+    /// `##div:has-text(Sponsored)` or `##.ad:style(opacity: 0)`. These are rejected by default,
+    /// since they must be evaluated against every page; enable this only for small, curated lists.
+    #[serde(default)]
+    pub allow_generic_procedural_filters: bool,
 }
 
 impl Default for ParseOptions {
@@ -74,6 +84,7 @@ impl Default for ParseOptions {
             format: FilterFormat::Standard,
             rule_types: RuleTypes::All,
             permissions: PermissionMask::default(),
+            allow_generic_procedural_filters: false,
         }
     }
 }
@@ -82,7 +93,36 @@ impl Default for ParseOptions {
 pub(crate) struct ListSource {
     pub(crate) list_text: String,
     pub(crate) parse_options: ParseOptions,
+    /// Tokens that are considered true when evaluating `!#if` directives within the list.
+    pub(crate) preprocessor_env: PreprocessorEnv,
     pub(crate) metadata: FilterListMetadata,
+    /// Contents of other lists referenced by `!#include` directives, keyed by path.
+    pub(crate) includes: HashMap<String, String>,
+}
+
+impl ListSource {
+    /// Iterates over the lines of this list after evaluating preprocessor directives, along with
+    /// their zero-indexed line numbers in the original list. Lines from an included list are
+    /// numbered according to the `!#include` directive that references it.
+    pub(crate) fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        let env = &self.preprocessor_env;
+        Preprocessor::new(self.list_text.lines(), env).flat_map(move |(line_number, line)| {
+            let included = match line {
+                PreprocessedLine::Filter(line) => {
+                    return Either::Left(std::iter::once((line_number, line)));
+                }
+                PreprocessedLine::Include(path) => self.includes.get(path),
+            };
+            // Nested includes are not supported.
+            let included_lines = included.into_iter().flat_map(move |text| {
+                Preprocessor::new(text.lines(), env).filter_map(move |(_, line)| match line {
+                    PreprocessedLine::Filter(line) => Some((line_number, line)),
+                    PreprocessedLine::Include(_) => None,
+                })
+            });
+            Either::Right(included_lines)
+        })
+    }
 }
 
 /// Manages a set of rules to be added to an [`crate::Engine`].
@@ -237,15 +277,48 @@ impl FilterSet {
     /// Adds the contents of an entire filter list to this `FilterSet`. Filters that cannot be
     /// parsed successfully are ignored. Returns any discovered metadata about the list of rules
     /// added.
+    ///
+    /// Preprocessor directives are evaluated against an empty [`PreprocessorEnv`], and `!#include`
+    /// directives are ignored; use [`FilterSet::add_filter_list_with_includes`] to customize this.
     pub fn add_filter_list(&mut self, list_text: String, opts: ParseOptions) -> AddedFiltersRecord {
+        self.add_filter_list_with_includes(list_text, opts, &PreprocessorEnv::default(), |_| None)
+    }
+
+    /// Like [`FilterSet::add_filter_list`], but evaluates `!#if` directives against `env`, and
+    /// resolves `!#include` directives within the list. `loader` will be called with the path from each active `!#include` directive, and
+    /// should return the contents of the referenced list, or `None` if it can't be loaded. Paths
+    /// are passed exactly as they appear in the list; it's up to the caller to decide how they
+    /// should be resolved and which ones are allowed.
+    ///
+    /// Included lists are subject to the same `opts` and `env`, but `!#include` directives within them are
+    /// ignored. Filters from an included list are reported at the line number of the `!#include`
+    /// directive in the original list.
+    pub fn add_filter_list_with_includes(
+        &mut self,
+        list_text: String,
+        opts: ParseOptions,
+        env: &PreprocessorEnv,
+        mut loader: impl FnMut(&str) -> Option<String>,
+    ) -> AddedFiltersRecord {
         let metadata = match opts.format {
             FilterFormat::Standard => read_list_metadata(&list_text),
             FilterFormat::Hosts => FilterListMetadata::default(),
         };
+        let mut includes = HashMap::new();
+        for (_, line) in Preprocessor::new(list_text.lines(), env) {
+            if let PreprocessedLine::Include(path) = line
+                && !includes.contains_key(path)
+                && let Some(included) = loader(path)
+            {
+                includes.insert(path.to_string(), included);
+            }
+        }
         self.list_sources.push(ListSource {
             list_text,
             parse_options: opts,
+            preprocessor_env: env.clone(),
             metadata: metadata.clone(),
+            includes,
         });
         AddedFiltersRecord {
             source_index: self.list_sources.len() - 1,
@@ -269,7 +342,9 @@ impl FilterSet {
         self.list_sources.push(ListSource {
             list_text,
             parse_options: opts,
+            preprocessor_env: PreprocessorEnv::default(),
             metadata: FilterListMetadata::default(),
+            includes: HashMap::new(),
         });
     }
 
@@ -307,12 +382,13 @@ impl FilterSet {
         let mut network_filters = vec![];
         let mut cosmetic_filters = vec![];
         for list_source in list_sources.iter() {
-            let list_text = list_source.list_text.lines();
-            let parse_options = list_source.parse_options;
-            let (list_network_filters, list_cosmetic_filters) =
-                parse_filters(list_text, debug, parse_options);
-            network_filters.extend(list_network_filters);
-            cosmetic_filters.extend(list_cosmetic_filters);
+            for (_, line) in list_source.lines() {
+                match parse_filter(line, debug, list_source.parse_options) {
+                    Ok(ParsedLine::Network(f)) => network_filters.push(f),
+                    Ok(ParsedLine::Cosmetic(f)) => cosmetic_filters.push(f),
+                    Err(_) => (),
+                }
+            }
         }
 
         // Store bad filter id to skip them later.
@@ -417,6 +493,7 @@ pub enum FilterType {
 }
 
 /// Successful result of parsing a single line from a filter list
+#[allow(clippy::large_enum_variant)]
 pub enum ParsedLine<'a> {
     Network(NetworkFilter<'a>),
     Cosmetic(CosmeticFilter),
@@ -481,7 +558,7 @@ pub fn parse_filter<'a>(
                     .map_err(|e| e.into())
             }
             (FilterType::Cosmetic, RuleTypes::All | RuleTypes::CosmeticOnly) => {
-                CosmeticFilter::parse(filter, debug, opts.permissions)
+                CosmeticFilter::parse_with_options(filter, debug, opts)
                     .map(ParsedLine::Cosmetic)
                     .map_err(|e| e.into())
             }
@@ -537,20 +614,25 @@ pub fn parse_filter<'a>(
     }
 }
 
-/// Parse an entire list of filters, ignoring any errors
+/// Parse an entire list of filters, ignoring any errors. Preprocessor directives are evaluated
+/// against an empty [`PreprocessorEnv`], and `!#include` directives are ignored.
 pub fn parse_filters<'a>(
     list: impl IntoIterator<Item = &'a str>,
     debug: bool,
     opts: ParseOptions,
 ) -> (Vec<NetworkFilter<'a>>, Vec<CosmeticFilter>) {
-    let (network_filters, cosmetic_filters): (Vec<_>, Vec<_>) = list
-        .into_iter()
-        .filter_map(|line| match parse_filter(line, debug, opts) {
-            Ok(ParsedLine::Network(f)) => Some(Either::Left(f)),
-            Ok(ParsedLine::Cosmetic(f)) => Some(Either::Right(f)),
-            _ => None,
-        })
-        .partition_map(|x| x);
+    let (network_filters, cosmetic_filters): (Vec<_>, Vec<_>) =
+        Preprocessor::new(list.into_iter(), &PreprocessorEnv::default())
+            .filter_map(|(_, line)| match line {
+                PreprocessedLine::Filter(line) => Some(line),
+                PreprocessedLine::Include(_) => None,
+            })
+            .filter_map(|line| match parse_filter(line, debug, opts) {
+                Ok(ParsedLine::Network(f)) => Some(Either::Left(f)),
+                Ok(ParsedLine::Cosmetic(f)) => Some(Either::Right(f)),
+                _ => None,
+            })
+            .partition_map(|x| x);
 
     (network_filters, cosmetic_filters)
 }
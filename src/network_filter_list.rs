@@ -21,6 +21,11 @@ pub(crate) struct CheckResult {
     pub filter_mask: NetworkFilterMask,
     pub modifier_option: Option<String>,
     pub debug_data: Option<FilterRuleDebugInfo>,
+    /// Whether the filter applies regardless of the request's source, i.e. it has no positive
+    /// `$domain=` restriction.
+    pub is_generic: bool,
+    /// See [`FlatNetworkFilter::list_position`].
+    pub list_position: (u32, u32),
 }
 
 impl fmt::Display for CheckResult {
@@ -58,7 +63,7 @@ impl NetworkFilterList<'_> {
         )
     }
 
-    fn is_empty(&self) -> bool {
+    pub fn is_empty(&self) -> bool {
         self.list.filter_map_index().is_empty() && self.list.opt_domains_map_index().is_empty()
     }
 
@@ -72,6 +77,30 @@ impl NetworkFilterList<'_> {
         request: &Request,
         active_tags: &HashSet<String>,
         regex_manager: &mut RegexManager,
+    ) -> Option<CheckResult> {
+        self.check_first(request, active_tags, regex_manager, |_, _| true)
+    }
+
+    /// Like [`Self::check`], but ignores generic filters, i.e. those without any positive
+    /// `$domain=` restriction. Used for requests from pages with a `$genericblock` exception.
+    pub fn check_specific(
+        &self,
+        request: &Request,
+        active_tags: &HashSet<String>,
+        regex_manager: &mut RegexManager,
+    ) -> Option<CheckResult> {
+        self.check_first(request, active_tags, regex_manager, |filter, _| {
+            !filter.is_generic()
+        })
+    }
+
+    /// Like [`Self::check`], but only considers filters for which `accept` returns `true`.
+    pub fn check_first(
+        &self,
+        request: &Request,
+        active_tags: &HashSet<String>,
+        regex_manager: &mut RegexManager,
+        accept: impl Fn(&FlatNetworkFilter, &mut RegexManager) -> bool,
     ) -> Option<CheckResult> {
         if self.is_empty() {
             return None;
@@ -84,6 +113,7 @@ impl NetworkFilterList<'_> {
             request,
             active_tags,
             regex_manager,
+            &accept,
             |result| {
                 found = Some(result);
                 true
@@ -101,6 +131,7 @@ impl NetworkFilterList<'_> {
             request,
             active_tags,
             regex_manager,
+            &accept,
             |result| {
                 found = Some(result);
                 true
@@ -119,6 +150,17 @@ impl NetworkFilterList<'_> {
         request: &Request,
         active_tags: &HashSet<String>,
         regex_manager: &mut RegexManager,
+    ) -> Vec<CheckResult> {
+        self.check_all_accepted(request, active_tags, regex_manager, |_, _| true)
+    }
+
+    /// Like [`Self::check_all`], but only considers filters for which `accept` returns `true`.
+    pub fn check_all_accepted(
+        &self,
+        request: &Request,
+        active_tags: &HashSet<String>,
+        regex_manager: &mut RegexManager,
+        accept: impl Fn(&FlatNetworkFilter, &mut RegexManager) -> bool,
     ) -> Vec<CheckResult> {
         let mut filters: Vec<CheckResult> = vec![];
 
@@ -132,6 +174,7 @@ impl NetworkFilterList<'_> {
             request,
             active_tags,
             regex_manager,
+            &accept,
             |result| {
                 filters.push(result);
                 false
@@ -144,6 +187,7 @@ impl NetworkFilterList<'_> {
             request,
             active_tags,
             regex_manager,
+            &accept,
             |result| {
                 filters.push(result);
                 false
@@ -153,8 +197,9 @@ impl NetworkFilterList<'_> {
         filters
     }
 
-    /// Invokes `on_match` for each matching filter. Returns `true` if `on_match` requested an early
-    /// stop by returning `true`.
+    /// Invokes `on_match` for each matching filter, skipping any for which `accept` returns
+    /// `false`. Returns `true` if `on_match` requested an early stop by returning `true`.
+    #[allow(clippy::too_many_arguments)]
     fn match_filters<'a, I>(
         &self,
         filter_map: &FlatNetworkFilterMap<'_>,
@@ -162,6 +207,7 @@ impl NetworkFilterList<'_> {
         request: &Request,
         active_tags: &HashSet<String>,
         regex_manager: &mut RegexManager,
+        accept: &impl Fn(&FlatNetworkFilter, &mut RegexManager) -> bool,
         mut on_match: impl FnMut(CheckResult) -> bool,
     ) -> bool
     where
@@ -172,6 +218,10 @@ impl NetworkFilterList<'_> {
                 for fb_filter in iter {
                     let filter = FlatNetworkFilter::new(&fb_filter, self.filter_data_context);
 
+                    if !accept(&filter, regex_manager) {
+                        continue;
+                    }
+
                     // if matched, also needs to be tagged with an active tag (or not tagged at all)
                     if filter.matches(request, regex_manager)
                         && filter.tag().is_none_or(|t| active_tags.contains(t))
@@ -179,6 +229,8 @@ impl NetworkFilterList<'_> {
                             filter_mask: filter.mask,
                             modifier_option: filter.modifier_option(),
                             debug_data: filter.get_rule_debug_info(),
+                            is_generic: filter.is_generic(),
+                            list_position: filter.list_position(),
                         })
                     {
                         return true;
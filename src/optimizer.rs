@@ -14,6 +14,16 @@ trait Optimization {
 pub fn is_filter_optimizable_by_patterns(filter: &NetworkFilter<'_>) -> bool {
     filter.opt_domains.is_none()
         && filter.opt_not_domains.is_none()
+        && filter.opt_entities.is_none()
+        && filter.opt_not_entities.is_none()
+        && filter.opt_to_domains.is_none()
+        && filter.opt_not_to_domains.is_none()
+        && filter.opt_to_entities.is_none()
+        && filter.opt_not_to_entities.is_none()
+        && filter.opt_domain_regexes.is_none()
+        && filter.opt_not_domain_regexes.is_none()
+        && filter.opt_denyallow_domains.is_none()
+        && filter.opt_ip_address.is_none()
         && !filter.is_hostname_anchor()
         && !filter.is_redirect()
         && !filter.is_csp()
@@ -135,7 +145,12 @@ impl Optimization for SimplePatternGroup {
     }
 
     fn group_by_criteria(&self, filter: &NetworkFilter<'_>) -> String {
-        format!("{:b}:{:?}", filter.mask, filter.is_complete_regex())
+        format!(
+            "{:b}:{:b}:{:?}",
+            filter.mask,
+            filter.methods,
+            filter.is_complete_regex()
+        )
     }
     fn select(&self, filter: &NetworkFilter<'_>) -> bool {
         is_filter_optimizable_by_patterns(filter)
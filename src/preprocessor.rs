@@ -0,0 +1,188 @@
+//! Evaluates preprocessor directives in filter lists, as supported by uBlock Origin and AdGuard:
+//!
+//! - `!#if <condition>`, `!#else`, and `!#endif` enclose blocks of rules that should only be used
+//!   in certain environments, e.g. `!#if env_mobile` or `!#if !ext_ublock`. Conditions can be
+//!   combined with `!`, `&&`, `||`, and parentheses. Tokens are true if they're included in the
+//!   [`PreprocessorEnv`] passed to [`crate::FilterSet::add_filter_list_with_includes`], and false
+//!   otherwise.
+//! - `!#include <path>` inserts the contents of another list at that point. Filters can't load
+//!   other lists by themselves, so includes are only resolved if a loader is provided to
+//!   [`crate::FilterSet::add_filter_list_with_includes`].
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The set of tokens that are considered true when evaluating `!#if` directives in filter lists,
+/// e.g. `env_mobile`, `env_safari`, or `cap_html_filtering`. Any other token is considered false.
+///
+/// Cloning is cheap, since the set is shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct PreprocessorEnv(Arc<HashSet<String>>);
+
+impl PreprocessorEnv {
+    /// Returns `true` if `token` should be considered true in `!#if` conditions.
+    pub fn contains(&self, token: &str) -> bool {
+        self.0.contains(token)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for PreprocessorEnv {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        Self(Arc::new(iter.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// A line from a filter list that remains after preprocessing.
+#[derive(Debug, PartialEq)]
+pub(crate) enum PreprocessedLine<'a> {
+    /// Anything other than a preprocessor directive, from a block whose conditions were met.
+    Filter(&'a str),
+    /// The path from an `!#include` directive, from a block whose conditions were met.
+    Include(&'a str),
+}
+
+/// Iterator adapter that evaluates preprocessor directives within the lines of a filter list.
+/// Each remaining line is returned along with its original zero-indexed line number.
+pub(crate) struct Preprocessor<'a, I> {
+    lines: std::iter::Enumerate<I>,
+    env: &'a PreprocessorEnv,
+    /// Whether or not the current branch of each enclosing `!#if` block was taken.
+    conditions: Vec<bool>,
+}
+
+impl<'a, I: Iterator> Preprocessor<'a, I> {
+    pub(crate) fn new(lines: I, env: &'a PreprocessorEnv) -> Self {
+        Self {
+            lines: lines.enumerate(),
+            env,
+            conditions: vec![],
+        }
+    }
+}
+
+impl<'l, I: Iterator<Item = &'l str>> Iterator for Preprocessor<'_, I> {
+    type Item = (usize, PreprocessedLine<'l>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (line_number, line) in self.lines.by_ref() {
+            let Some(directive) = line.trim().strip_prefix("!#") else {
+                if self.conditions.iter().all(|c| *c) {
+                    return Some((line_number, PreprocessedLine::Filter(line)));
+                }
+                continue;
+            };
+
+            let (name, argument) = match directive.find(|c: char| c.is_whitespace() || c == '(') {
+                Some(i) => (&directive[..i], directive[i..].trim()),
+                None => (directive, ""),
+            };
+            match name {
+                "if" => self.conditions.push(evaluate(argument, self.env)),
+                "else" => {
+                    if let Some(condition) = self.conditions.last_mut() {
+                        *condition = !*condition;
+                    }
+                }
+                "endif" => {
+                    self.conditions.pop();
+                }
+                "include" if !argument.is_empty() && self.conditions.iter().all(|c| *c) => {
+                    return Some((line_number, PreprocessedLine::Include(argument)));
+                }
+                // Other directives, e.g. AdGuard's `!#safari_cb_affinity`, are ignored.
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// Evaluates the condition from an `!#if` directive. Malformed conditions are false.
+fn evaluate(condition: &str, env: &PreprocessorEnv) -> bool {
+    let mut tokens = tokenize(condition).into_iter().peekable();
+    let result = evaluate_or(&mut tokens, env);
+    match (result, tokens.next()) {
+        (Some(result), None) => result,
+        _ => false,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Not,
+    And,
+    Or,
+    Open,
+    Close,
+    Ident(&'a str),
+    Invalid,
+}
+
+fn tokenize(condition: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = condition.trim_start();
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("&&") {
+            (Token::And, 2)
+        } else if rest.starts_with("||") {
+            (Token::Or, 2)
+        } else if rest.starts_with('!') {
+            (Token::Not, 1)
+        } else if rest.starts_with('(') {
+            (Token::Open, 1)
+        } else if rest.starts_with(')') {
+            (Token::Close, 1)
+        } else {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            if len == 0 {
+                (
+                    Token::Invalid,
+                    rest.chars().next().map_or(1, char::len_utf8),
+                )
+            } else {
+                (Token::Ident(&rest[..len]), len)
+            }
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    tokens
+}
+
+type Tokens<'a> = std::iter::Peekable<std::vec::IntoIter<Token<'a>>>;
+
+fn evaluate_or(tokens: &mut Tokens, env: &PreprocessorEnv) -> Option<bool> {
+    let mut result = evaluate_and(tokens, env)?;
+    while tokens.next_if_eq(&Token::Or).is_some() {
+        // Evaluate both sides, so that the entire condition is always consumed.
+        result |= evaluate_and(tokens, env)?;
+    }
+    Some(result)
+}
+
+fn evaluate_and(tokens: &mut Tokens, env: &PreprocessorEnv) -> Option<bool> {
+    let mut result = evaluate_unary(tokens, env)?;
+    while tokens.next_if_eq(&Token::And).is_some() {
+        result &= evaluate_unary(tokens, env)?;
+    }
+    Some(result)
+}
+
+fn evaluate_unary(tokens: &mut Tokens, env: &PreprocessorEnv) -> Option<bool> {
+    match tokens.next()? {
+        Token::Not => evaluate_unary(tokens, env).map(|v| !v),
+        Token::Open => {
+            let result = evaluate_or(tokens, env)?;
+            tokens.next_if_eq(&Token::Close)?;
+            Some(result)
+        }
+        Token::Ident(token) => Some(env.contains(token)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[path = "../tests/unit/preprocessor.rs"]
+mod unit_tests;
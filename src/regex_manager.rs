@@ -2,13 +2,16 @@
 //! the [`crate::Engine`], infrequently used regexes can be discarded. The [`RegexManager`] is
 //! responsible for managing the storage of regexes used by filters.
 
-use crate::filters::network::{NetworkFilterMask, NetworkFilterMaskHelper};
+use crate::filters::network::{
+    ClientValue, IpAddressValue, NetworkFilterMask, NetworkFilterMaskHelper, ReplaceValue,
+    parse_client_value, parse_dns_types,
+};
 
 use regex::{
     Regex, bytes::Regex as BytesRegex, bytes::RegexBuilder as BytesRegexBuilder,
     bytes::RegexSet as BytesRegexSet, bytes::RegexSetBuilder as BytesRegexSetBuilder,
 };
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 
 use std::collections::HashMap;
 use std::fmt;
@@ -105,6 +108,61 @@ struct RegexEntry {
     usage_count: usize,
 }
 
+impl RegexEntry {
+    fn new(now: Instant) -> Self {
+        Self {
+            regex: None,
+            last_used: now,
+            usage_count: 0,
+        }
+    }
+
+    /// Records a use of this entry, compiling the regex if it's new or was discarded.
+    fn get_or_compile(
+        &mut self,
+        now: Instant,
+        compiled_regex_count: &mut usize,
+        compile: impl FnOnce() -> CompiledRegex,
+    ) -> &CompiledRegex {
+        self.usage_count += 1;
+        self.last_used = now;
+        if self.regex.is_none() {
+            self.regex = Some(compile());
+            *compiled_regex_count += 1;
+        }
+        self.regex.as_ref().unwrap()
+    }
+}
+
+type SourceMap = HashMap<String, RegexEntry, RandomState>;
+
+/// Returns the entry for `source` in `map`, inserting a new one if needed.
+fn source_entry<'m>(map: &'m mut SourceMap, source: &str, now: Instant) -> &'m mut RegexEntry {
+    if !map.contains_key(source) {
+        map.insert(source.to_string(), RegexEntry::new(now));
+    }
+    map.get_mut(source).unwrap()
+}
+
+/// Returns the parsed form of `value` from `map`, parsing it if it's not there yet.
+fn get_or_parse<'m, T>(
+    map: &'m mut HashMap<String, Option<T>, RandomState>,
+    value: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Option<&'m T> {
+    if !map.contains_key(value) {
+        map.insert(value.to_string(), parse(value));
+    }
+    map.get(value).unwrap().as_ref()
+}
+
+/// Identifies an entry of a [`SourceMap`] in a [`RegexDebugEntry`]. The key's buffer doesn't move
+/// while the entry exists, so this is unique for its lifetime.
+#[cfg(feature = "debug-info")]
+fn source_id(source: &str) -> u64 {
+    source.as_ptr() as u64
+}
+
 /// Used for customization of regex discarding behavior in the [`RegexManager`].
 pub struct RegexManagerDiscardPolicy {
     /// The [`RegexManager`] will check for and cleanup unused filters on this interval.
@@ -130,6 +188,18 @@ type RandomState = std::hash::BuildHasherDefault<seahash::SeaHasher>;
 /// The [`RegexManager`] is not thread safe, so any access to it must be synchronized externally.
 pub struct RegexManager {
     map: HashMap<u64, RegexEntry, RandomState>,
+    /// Regexes from filter option values, keyed by their source. They're kept apart from `map`,
+    /// whose keys identify filters, and split by whether they ignore case.
+    option_map: SourceMap,
+    option_map_ignore_case: SourceMap,
+    /// Patterns of `$replace` and `$urltransform` option values, keyed by the whole value.
+    replace_map: SourceMap,
+    /// Parsed `$ipaddress`, `$dnstype` and `$client` values, keyed by their source. Like option
+    /// regexes, they're parsed the first time they're needed rather than on every match. `None`
+    /// marks an invalid value.
+    ip_address_values: HashMap<String, Option<Arc<IpAddressValue<'static>>>, RandomState>,
+    dns_type_values: HashMap<String, Option<Vec<(bool, String)>>, RandomState>,
+    client_values: HashMap<String, Option<Vec<(bool, ClientValue<'static>)>>, RandomState>,
     compiled_regex_count: usize,
     now: Instant,
     #[cfg_attr(target_arch = "wasm32", allow(unused))]
@@ -141,6 +211,12 @@ impl Default for RegexManager {
     fn default() -> Self {
         Self {
             map: Default::default(),
+            option_map: Default::default(),
+            option_map_ignore_case: Default::default(),
+            replace_map: Default::default(),
+            ip_address_values: Default::default(),
+            dns_type_values: Default::default(),
+            client_values: Default::default(),
             compiled_regex_count: 0,
             now: Instant::now(),
             last_cleanup: Instant::now(),
@@ -236,6 +312,18 @@ where
     }
 }
 
+/// Compiles a standalone regex taken from the value of a filter option, like
+/// `$removeparam=/.../`. Unlike [`compile_regex`], the source is used as-is.
+pub(crate) fn compile_option_regex(
+    pattern: &str,
+    ignore_case: bool,
+) -> Result<BytesRegex, regex::Error> {
+    BytesRegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .unicode(false)
+        .build()
+}
+
 impl RegexManager {
     /// Check whether or not a regex network filter matches a certain URL pattern, using the
     /// [`RegexManager`]'s managed regex storage.
@@ -252,35 +340,112 @@ impl RegexManager {
         if !mask.is_regex() && !mask.is_complete_regex() {
             return true;
         }
-        use std::collections::hash_map::Entry;
-        match self.map.entry(key) {
-            Entry::Occupied(mut e) => {
-                let v = e.get_mut();
-                v.usage_count += 1;
-                v.last_used = self.now;
-                if v.regex.is_none() {
-                    // A discarded entry, recreate it:
-                    v.regex = Some(make_regexp(mask, filters));
-                    self.compiled_regex_count += 1;
-                }
-                v.regex.as_ref().unwrap().is_match(pattern)
-            }
-            Entry::Vacant(e) => {
-                self.compiled_regex_count += 1;
-                let new_entry = RegexEntry {
-                    regex: Some(make_regexp(mask, filters)),
-                    last_used: self.now,
-                    usage_count: 1,
-                };
-                e.insert(new_entry)
-                    .regex
-                    .as_ref()
-                    .unwrap()
-                    .is_match(pattern)
-            }
+        self.get_or_compile(key, || make_regexp(mask, filters))
+            .is_match(pattern)
+    }
+
+    /// Check whether or not a regex from a filter option value matches `text`, using the
+    /// [`RegexManager`]'s managed regex storage. Entries are keyed by the regex source, so
+    /// identical option values from different filters share a single compiled regex.
+    pub(crate) fn matches_option_regex(
+        &mut self,
+        pattern: &str,
+        ignore_case: bool,
+        text: &str,
+    ) -> bool {
+        let map = if ignore_case {
+            &mut self.option_map_ignore_case
+        } else {
+            &mut self.option_map
+        };
+        source_entry(map, pattern, self.now)
+            .get_or_compile(
+                self.now,
+                &mut self.compiled_regex_count,
+                || match compile_option_regex(pattern, ignore_case) {
+                    Ok(compiled) => CompiledRegex::Compiled(compiled),
+                    Err(e) => CompiledRegex::RegexParsingError(e),
+                },
+            )
+            .is_match(text)
+    }
+
+    /// Returns the compiled pattern of a `$replace` or `$urltransform` option value, using the
+    /// [`RegexManager`]'s managed regex storage, or `None` if it's invalid. Entries are keyed by
+    /// the option value, which also holds the pattern's flags.
+    pub(crate) fn get_replace_regex(
+        &mut self,
+        value: &str,
+        replace: &ReplaceValue,
+    ) -> Option<&BytesRegex> {
+        match source_entry(&mut self.replace_map, value, self.now).get_or_compile(
+            self.now,
+            &mut self.compiled_regex_count,
+            || match replace.compile() {
+                Ok(compiled) => CompiledRegex::Compiled(compiled),
+                Err(e) => CompiledRegex::RegexParsingError(e),
+            },
+        ) {
+            CompiledRegex::Compiled(regex) => Some(regex),
+            _ => None,
         }
     }
 
+    /// Returns the parsed value of an `$ipaddress` option, or `None` if it's invalid. It's shared so
+    /// that it can be matched against an address while the [`RegexManager`] is borrowed again for
+    /// its regex, if any.
+    pub(crate) fn get_ip_address_value(
+        &mut self,
+        value: &str,
+    ) -> Option<Arc<IpAddressValue<'static>>> {
+        get_or_parse(&mut self.ip_address_values, value, |value| {
+            IpAddressValue::parse(value)
+                .ok()
+                .map(|parsed| Arc::new(parsed.into_owned()))
+        })
+        .cloned()
+    }
+
+    /// Returns the record types listed in a `$dnstype` option, or `None` if it's invalid. See
+    /// [`parse_dns_types`].
+    pub(crate) fn get_dns_types(&mut self, value: &str) -> Option<&[(bool, String)]> {
+        get_or_parse(&mut self.dns_type_values, value, |value| {
+            parse_dns_types(value).ok().map(|dns_types| {
+                dns_types
+                    .into_iter()
+                    .map(|(included, name)| (included, name.to_string()))
+                    .collect()
+            })
+        })
+        .map(Vec::as_slice)
+    }
+
+    /// Returns the entries of a `$client` option, or `None` if it's invalid. See
+    /// [`parse_client_value`].
+    pub(crate) fn get_clients(&mut self, value: &str) -> Option<&[(bool, ClientValue<'static>)]> {
+        get_or_parse(&mut self.client_values, value, |value| {
+            parse_client_value(value).ok().map(|clients| {
+                clients
+                    .into_iter()
+                    .map(|(included, client)| (included, client.into_owned()))
+                    .collect()
+            })
+        })
+        .map(Vec::as_slice)
+    }
+
+    fn get_or_compile(
+        &mut self,
+        key: u64,
+        compile: impl FnOnce() -> CompiledRegex,
+    ) -> &CompiledRegex {
+        let now = self.now;
+        self.map
+            .entry(key)
+            .or_insert_with(|| RegexEntry::new(now))
+            .get_or_compile(now, &mut self.compiled_regex_count, compile)
+    }
+
     /// The [`RegexManager`] is just a struct and doesn't manage any worker threads, so this method
     /// must be called periodically to ensure that it can track usage patterns of regexes over
     /// time. This method will handle periodically discarding filters if necessary.
@@ -298,7 +463,12 @@ impl RegexManager {
     #[cfg(not(target_arch = "wasm32"))]
     pub(crate) fn cleanup(&mut self) {
         let now = self.now;
-        for v in self.map.values_mut() {
+        let source_entries = self
+            .option_map
+            .values_mut()
+            .chain(self.option_map_ignore_case.values_mut())
+            .chain(self.replace_map.values_mut());
+        for v in self.map.values_mut().chain(source_entries) {
             if now - v.last_used >= self.discard_policy.discard_unused_time {
                 // Discard the regex to save memory.
                 v.regex = None;
@@ -317,7 +487,16 @@ impl RegexManager {
         self.map
             .iter_mut()
             .filter(|(k, _)| { **k } == regex_id)
-            .for_each(|(_, v)| {
+            .map(|(_, v)| v)
+            .chain(
+                self.option_map
+                    .iter_mut()
+                    .chain(self.option_map_ignore_case.iter_mut())
+                    .chain(self.replace_map.iter_mut())
+                    .filter(|(k, _)| source_id(k) == regex_id)
+                    .map(|(_, v)| v),
+            )
+            .for_each(|v| {
                 v.regex = None;
             });
     }
@@ -327,8 +506,19 @@ impl RegexManager {
         use itertools::Itertools;
         self.map
             .iter()
-            .map(|(k, e)| RegexDebugEntry {
-                id: { *k },
+            .map(|(k, e)| ({ *k }, e))
+            .chain(
+                [
+                    &self.option_map,
+                    &self.option_map_ignore_case,
+                    &self.replace_map,
+                ]
+                .into_iter()
+                .flatten()
+                .map(|(k, e)| (source_id(k), e)),
+            )
+            .map(|(id, e)| RegexDebugEntry {
+                id,
                 regex: e.regex.as_ref().map(|x| x.to_string()),
                 last_used: e.last_used,
                 usage_count: e.usage_count,
@@ -1,5 +1,7 @@
 //! Contains structures needed to describe network requests.
 
+use std::net::IpAddr;
+use std::sync::OnceLock;
 use thiserror::Error;
 
 use crate::url_parser;
@@ -111,9 +113,23 @@ pub struct Request {
     pub is_https: bool,
     pub is_supported: bool,
     pub is_third_party: bool,
+    /// Like `is_third_party`, but compares full hostnames rather than registrable domains, for
+    /// `$strict1p` and `$strict3p` filters.
+    pub is_strict_third_party: bool,
     pub url: String,
     pub hostname: String,
     pub source_hostname_hashes: Option<Vec<utils::Hash>>,
+    /// Hashes of the source hostname's labels before its public suffix, for matching entities like
+    /// `$domain=google.*`. Only resolved once a filter with entities is checked.
+    source_entity_hashes: OnceLock<Vec<utils::Hash>>,
+    /// Like `source_entity_hashes`, but for the request's hostname, to match `$to=google.*`.
+    entity_hashes: OnceLock<Vec<utils::Hash>>,
+    /// Used to match regexes from `$domain=/.../`.
+    pub(crate) source_hostname: String,
+
+    /// The address that the request's hostname resolved to, if known. Filters with an
+    /// `$ipaddress=` option only match requests where this is set.
+    pub ip_address: Option<IpAddr>,
 
     pub(crate) url_lower_cased: String,
     pub(crate) request_tokens: Vec<utils::Hash>,
@@ -135,6 +151,16 @@ impl Request {
         self.source_hostname_hashes.as_ref().into_iter().flatten()
     }
 
+    pub(crate) fn get_source_entity_hashes(&self) -> &[utils::Hash] {
+        self.source_entity_hashes
+            .get_or_init(|| get_entity_hashes(&self.source_hostname))
+    }
+
+    pub(crate) fn get_entity_hashes(&self) -> &[utils::Hash] {
+        self.entity_hashes
+            .get_or_init(|| get_entity_hashes(&self.hostname))
+    }
+
     pub fn get_tokens_for_match(&self) -> impl Iterator<Item = &utils::Hash> {
         self.get_tokens().iter()
     }
@@ -191,6 +217,9 @@ impl Request {
             None
         };
 
+        // Requests with a different domain always have a different hostname as well.
+        let strict_third_party = third_party || hostname != source_hostname;
+
         let url_lower_cased = url.to_ascii_lowercase();
 
         Request {
@@ -201,10 +230,15 @@ impl Request {
             hostname: hostname.to_owned(),
             request_tokens: calculate_tokens(&url_lower_cased),
             source_hostname_hashes,
+            source_entity_hashes: OnceLock::new(),
+            entity_hashes: OnceLock::new(),
+            source_hostname: source_hostname.to_owned(),
             is_third_party: third_party,
+            is_strict_third_party: strict_third_party,
             is_http,
             is_https,
             is_supported,
+            ip_address: None,
             original_url,
         }
     }
@@ -265,6 +299,55 @@ impl Request {
             method.parse::<RequestMethod>().ok(),
         )
     }
+
+    /// Returns a copy of this request with the hostname of its URL replaced by `alias`, e.g. a
+    /// name that it resolves to through DNS CNAME records. Whether the request is third-party is
+    /// recomputed against the original source hostname.
+    pub fn with_hostname_alias(&self, alias: &str) -> Result<Request, RequestError> {
+        let parsed_url =
+            url_parser::parse_url(&self.url).ok_or(RequestError::HostnameParseError)?;
+        let (hostname_start, hostname_end) = parsed_url.hostname_pos;
+        // DNS responses may include the root label, e.g. `tracker.example.`.
+        let alias = alias.strip_suffix('.').unwrap_or(alias);
+        if alias.is_empty() {
+            return Err(RequestError::HostnameParseError);
+        }
+        let aliased_url = format!(
+            "{}{}{}",
+            &parsed_url.url[..hostname_start],
+            alias,
+            &parsed_url.url[hostname_end..]
+        );
+        let parsed_alias =
+            url_parser::parse_url(&aliased_url).ok_or(RequestError::HostnameParseError)?;
+
+        let source_hostname = self.source_hostname.as_str();
+        let (domain_start, domain_end) = url_parser::get_host_domain(source_hostname);
+        let source_domain = &source_hostname[domain_start..domain_end];
+        let third_party = source_hostname.is_empty() || source_domain != parsed_alias.domain();
+
+        let mut request = Request::from_detailed_parameters(
+            "",
+            &parsed_alias.url,
+            parsed_alias.schema(),
+            parsed_alias.hostname(),
+            source_hostname,
+            third_party,
+            aliased_url,
+            self.method,
+        );
+        request.request_type = self.request_type.clone();
+        request.ip_address = self.ip_address;
+        Ok(request)
+    }
+}
+
+fn get_entity_hashes(hostname: &str) -> Vec<utils::Hash> {
+    let (domain_start, domain_end) = url_parser::get_host_domain(hostname);
+    crate::filters::cosmetic::get_entity_hashes_from_labels(
+        hostname,
+        &hostname[domain_start..domain_end],
+    )
 }
 
 fn calculate_tokens(url_lower_cased: &str) -> Vec<utils::Hash> {
@@ -0,0 +1,104 @@
+//! Support for scriptlet injection rules written in AdGuard's `#%#//scriptlet(...)` syntax, which
+//! are translated into the equivalent uBlock Origin `+js(...)` form during filter parsing.
+
+/// AdGuard scriptlet names with an equivalent uBlock Origin scriptlet, sorted by AdGuard name.
+///
+/// Scriptlet arguments are passed through unchanged, so only scriptlets whose arguments are
+/// compatible between both projects are listed here. AdGuard also exposes uBlock Origin
+/// scriptlets directly under a `ubo-` prefix; those are handled separately.
+const ADGUARD_SCRIPTLET_ALIASES: &[(&str, &str)] = &[
+    ("abort-current-inline-script", "abort-current-script"),
+    ("abort-on-property-read", "abort-on-property-read"),
+    ("abort-on-property-write", "abort-on-property-write"),
+    ("abort-on-stack-trace", "abort-on-stack-trace"),
+    ("adjust-setInterval", "nano-setInterval-booster"),
+    ("adjust-setTimeout", "nano-setTimeout-booster"),
+    ("close-window", "window-close-if"),
+    ("disable-newtab-links", "disable-newtab-links"),
+    ("href-sanitizer", "href-sanitizer"),
+    ("json-prune", "json-prune"),
+    ("log-addEventListener", "addEventListener-logger"),
+    ("m3u8-prune", "m3u8-prune"),
+    ("nowebrtc", "nowebrtc"),
+    ("prevent-addEventListener", "addEventListener-defuser"),
+    ("prevent-eval-if", "noeval-if"),
+    ("prevent-fetch", "no-fetch-if"),
+    ("prevent-refresh", "refresh-defuser"),
+    (
+        "prevent-requestAnimationFrame",
+        "no-requestAnimationFrame-if",
+    ),
+    ("prevent-setInterval", "no-setInterval-if"),
+    ("prevent-setTimeout", "no-setTimeout-if"),
+    ("prevent-window-open", "no-window-open-if"),
+    ("prevent-xhr", "no-xhr-if"),
+    ("remove-attr", "remove-attr"),
+    ("remove-class", "remove-class"),
+    ("remove-cookie", "cookie-remover"),
+    ("remove-node-text", "remove-node-text"),
+    ("set-attr", "set-attr"),
+    ("set-constant", "set-constant"),
+    ("set-cookie", "set-cookie"),
+    ("set-local-storage-item", "set-local-storage-item"),
+    ("set-session-storage-item", "set-session-storage-item"),
+    ("spoof-css", "spoof-css"),
+    ("trusted-set-constant", "trusted-set-constant"),
+    ("trusted-set-cookie", "trusted-set-cookie"),
+    (
+        "trusted-set-local-storage-item",
+        "trusted-set-local-storage-item",
+    ),
+    ("xml-prune", "xml-prune"),
+];
+
+/// Returns the name of the uBlock Origin scriptlet equivalent to the given AdGuard scriptlet, or
+/// `None` if there is no known equivalent.
+pub(crate) fn ubo_scriptlet_name(adguard_name: &str) -> Option<&str> {
+    if let Some(name) = adguard_name.strip_prefix("ubo-") {
+        let name = name.strip_suffix(".js").unwrap_or(name);
+        return (!name.is_empty()).then_some(name);
+    }
+    ADGUARD_SCRIPTLET_ALIASES
+        .binary_search_by(|(alias, _)| (*alias).cmp(adguard_name))
+        .ok()
+        .map(|i| ADGUARD_SCRIPTLET_ALIASES[i].1)
+}
+
+/// Parses the inner contents of a `//scriptlet(...)` call from an AdGuard `#%#` rule.
+///
+/// Unlike uBlock Origin's `+js(...)` syntax, every argument must be quoted with `'` or `"`, and a
+/// backslash escapes the following character. Returns `None` if the contents are malformed.
+pub(crate) fn parse_adguard_scriptlet_args(args: &str) -> Option<Vec<String>> {
+    let mut args_vec = vec![];
+    let mut chars = args.trim().chars().peekable();
+    while let Some(quote) = chars.next() {
+        if quote != '\'' && quote != '"' {
+            return None;
+        }
+        let mut arg = String::new();
+        loop {
+            match chars.next()? {
+                '\\' => arg.push(chars.next()?),
+                c if c == quote => break,
+                c => arg.push(c),
+            }
+        }
+        args_vec.push(arg);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                // A trailing comma must be followed by another argument.
+                chars.peek()?;
+            }
+            Some(_) => return None,
+            None => break,
+        }
+    }
+    Some(args_vec)
+}
+
+#[cfg(test)]
+#[path = "../../tests/unit/resources/adguard.rs"]
+mod unit_tests;
@@ -12,13 +12,16 @@
 #[cfg(feature = "resource-assembler")]
 pub mod resource_assembler;
 
+mod adguard;
+pub(crate) use adguard::{parse_adguard_scriptlet_args, ubo_scriptlet_name};
+
 mod resource_storage;
-pub(crate) use resource_storage::parse_scriptlet_args;
 #[doc(inline)]
 pub use resource_storage::{
     AddResourceError, InMemoryResourceStorage, ResourceImpl, ResourceStorage,
     ResourceStorageBackend, ScriptletResourceError,
 };
+pub(crate) use resource_storage::{format_scriptlet_args, parse_scriptlet_args};
 
 use memchr::memrchr as find_char_reverse;
 use serde::{Deserialize, Serialize};
@@ -32,6 +35,10 @@ use serde::{Deserialize, Serialize};
 /// powerful scriptlets like uBlock Origin's `trusted-set-cookie`, which has the ability to set
 /// arbitrary cookies to arbitrary values on visited sites.
 ///
+/// Lists parsed with any permissions at all are also trusted to use `$urltransform` rules, which
+/// can rewrite request URLs arbitrarily. These are rejected from lists with the default
+/// permissions.
+///
 /// ### Example
 ///
 /// ```
@@ -123,7 +130,7 @@ impl PermissionMask {
     }
 
     /// The default value for [`PermissionMask`] is one which provides no additional permissions.
-    fn is_default(&self) -> bool {
+    pub(crate) fn is_default(&self) -> bool {
         self.0 == 0
     }
 }
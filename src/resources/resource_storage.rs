@@ -674,6 +674,29 @@ pub(crate) fn parse_scriptlet_args(mut args: &str) -> Option<Vec<String>> {
     Some(args_vec)
 }
 
+/// Formats `args` as the inner contents of a `+js(...)` operator, such that
+/// [`parse_scriptlet_args`] will return the same arguments.
+///
+/// Returns `None` if an argument cannot be represented.
+pub(crate) fn format_scriptlet_args(args: &[String]) -> Option<String> {
+    const QUOTES: [char; 3] = ['"', '\'', '`'];
+
+    let mut formatted = Vec::with_capacity(args.len());
+    for arg in args {
+        // A trailing backslash would escape the following separator or quote.
+        if arg.ends_with('\\') {
+            return None;
+        }
+        if !arg.is_empty() && arg.trim() == arg && !arg.starts_with(QUOTES) && !arg.contains(',') {
+            formatted.push(arg.to_string());
+        } else {
+            let quote = QUOTES.into_iter().find(|quote| !arg.contains(*quote))?;
+            formatted.push(format!("{quote}{arg}{quote}"));
+        }
+    }
+    Some(formatted.join(", "))
+}
+
 #[cfg(test)]
 #[path = "../../tests/unit/resources/resource_storage.rs"]
 mod unit_tests;
@@ -90,15 +90,36 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.exceptions.insert(".item".into());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.exceptions.insert(".item2".into());
         assert_eq!(out, expected);
     }
@@ -108,15 +129,36 @@ mod cosmetic_cache_tests {
         let cfcache = CosmeticFilterCache::from_rules(vec!["example.com,~sub.example.com##.item"]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.insert(".item".to_owned());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         expected.exceptions.insert(".item".into());
         assert_eq!(out, expected);
@@ -133,18 +175,46 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a1.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a1.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.insert(".element".to_owned());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter::from_css(
@@ -155,7 +225,14 @@ mod cosmetic_cache_tests {
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a2.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a2.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter::from_css(
@@ -166,7 +243,14 @@ mod cosmetic_cache_tests {
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a3.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a3.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter::from_css(
@@ -218,23 +302,58 @@ mod cosmetic_cache_tests {
             ),
         ]);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.injected_script =
             "try {\nset-constant.js, atob, trueFunc\n} catch ( e ) { }\n".to_owned();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "cosmetic.net", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "cosmetic.net",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.injected_script = "try {\nnowebrtc.js\n} catch ( e ) { }\n".to_owned();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "g.cosmetic.net", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "g.cosmetic.net",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.injected_script = "try {\nnowebrtc.js\n} catch ( e ) { }\ntry {\nwindow.open-defuser.js\n} catch ( e ) { }\n".to_owned();
         // order is non-deterministic
         if out != expected {
@@ -242,11 +361,25 @@ mod cosmetic_cache_tests {
             assert_eq!(out, expected);
         }
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "c.g.cosmetic.net", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "c.g.cosmetic.net",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.injected_script = "try {\nwindow.open-defuser.js\n} catch ( e ) { }\n".to_owned();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "d.g.cosmetic.net", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "d.g.cosmetic.net",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.injected_script = "".to_owned();
         assert_eq!(out, expected);
     }
@@ -262,18 +395,46 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a1.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a1.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.insert(".element".to_owned());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
@@ -284,11 +445,25 @@ mod cosmetic_cache_tests {
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a2.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a2.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a3.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a3.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
@@ -308,7 +483,14 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
@@ -319,7 +501,14 @@ mod cosmetic_cache_tests {
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "chip.de", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "chip.de",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
@@ -344,52 +533,94 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a1.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a1.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.insert(".element".to_owned());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveAttr("style".to_string())),
+                action: Some(CosmeticFilterAction::RemoveAttr("style".into())),
             })
             .unwrap(),
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a2.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a2.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveAttr("src".to_string())),
+                action: Some(CosmeticFilterAction::RemoveAttr("src".into())),
             })
             .unwrap(),
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a3.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a3.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveAttr("src".to_string())),
+                action: Some(CosmeticFilterAction::RemoveAttr("src".into())),
             })
             .unwrap(),
         );
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveAttr("style".to_string())),
+                action: Some(CosmeticFilterAction::RemoveAttr("style".into())),
             })
             .unwrap(),
         );
@@ -407,52 +638,124 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "sub.test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "sub.test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a1.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a1.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.insert(".element".to_owned());
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "test.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "test.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.hide_selectors.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveClass("overlay".to_string())),
+                action: Some(CosmeticFilterAction::RemoveClass("overlay".into())),
             })
             .unwrap(),
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a2.sub.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a2.sub.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveClass("banner".to_string())),
+                action: Some(CosmeticFilterAction::RemoveClass("banner".into())),
             })
             .unwrap(),
         );
         assert_eq!(out, expected);
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "a3.example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "a3.example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         expected.procedural_actions.clear();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveClass("banner".to_string())),
+                action: Some(CosmeticFilterAction::RemoveClass("banner".into())),
             })
             .unwrap(),
         );
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
                 selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
-                action: Some(CosmeticFilterAction::RemoveClass("overlay".to_string())),
+                action: Some(CosmeticFilterAction::RemoveClass("overlay".into())),
+            })
+            .unwrap(),
+        );
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn regex_and_quoted_action_args() {
+        let cfcache = CosmeticFilterCache::from_rules(vec![
+            "example.com##.element:remove-attr(/^data-ad/i)",
+            r#"example.com##.element:remove-class("ad)")"#,
+        ]);
+        let resources = ResourceStorage::default();
+
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
+        let mut expected = UrlSpecificResources::empty();
+        expected.procedural_actions.insert(
+            r#"{"selector":[{"type":"css-selector","arg":".element"}],"action":{"type":"remove-attr","arg":{"regex":"^data-ad","flags":"i"}}}"#.to_string(),
+        );
+        expected.procedural_actions.insert(
+            serde_json::to_string(&ProceduralOrActionFilter {
+                selector: vec![CosmeticFilterOperator::CssSelector(".element".to_string())],
+                action: Some(CosmeticFilterAction::RemoveClass("ad)".into())),
             })
             .unwrap(),
         );
@@ -470,7 +773,14 @@ mod cosmetic_cache_tests {
         ]);
         let resources = ResourceStorage::default();
 
-        let out = cfcache.hostname_cosmetic_resources(&resources, "example.com", false);
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/",
+            false,
+            false,
+            &mut RegexManager::default(),
+        );
         let mut expected = UrlSpecificResources::empty();
         expected.procedural_actions.insert(
             serde_json::to_string(&ProceduralOrActionFilter {
@@ -498,7 +808,7 @@ mod cosmetic_cache_tests {
                     CosmeticFilterOperator::HasText("Cookie".to_string()),
                     CosmeticFilterOperator::CssSelector(" > p".to_string()),
                 ],
-                action: Some(CosmeticFilterAction::RemoveClass("overlay".to_string())),
+                action: Some(CosmeticFilterAction::RemoveClass("overlay".into())),
             })
             .unwrap(),
         );
@@ -589,7 +899,14 @@ mod cosmetic_cache_tests {
         let cfcache = CosmeticFilterCache::from_rules(rules);
         let resources = ResourceStorage::default();
         let exceptions = cfcache
-            .hostname_cosmetic_resources(&resources, "example.co.uk", false)
+            .url_cosmetic_resources(
+                &resources,
+                "example.co.uk",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .exceptions;
 
         let out = cfcache.hidden_class_id_selectors(["a-class"], EMPTY, &exceptions);
@@ -610,7 +927,14 @@ mod cosmetic_cache_tests {
         assert_eq!(out, ["#test-element"]);
 
         let exceptions = cfcache
-            .hostname_cosmetic_resources(&resources, "a1.test.com", false)
+            .url_cosmetic_resources(
+                &resources,
+                "a1.test.com",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .exceptions;
 
         let out = cfcache.hidden_class_id_selectors(["a-class"], EMPTY, &exceptions);
@@ -645,7 +969,14 @@ mod cosmetic_cache_tests {
         let resources = ResourceStorage::default();
 
         let hide_selectors = cfcache
-            .hostname_cosmetic_resources(&resources, "test.com", false)
+            .url_cosmetic_resources(
+                &resources,
+                "test.com",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .hide_selectors;
         let mut expected_hides = HashSet::new();
         expected_hides.insert("a[href=\"bad.com\"]".to_owned());
@@ -654,7 +985,14 @@ mod cosmetic_cache_tests {
         assert_eq!(hide_selectors, expected_hides);
 
         let hide_selectors = cfcache
-            .hostname_cosmetic_resources(&resources, "example.com", false)
+            .url_cosmetic_resources(
+                &resources,
+                "example.com",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .hide_selectors;
         let mut expected_hides = HashSet::new();
         expected_hides.insert("a[href=\"bad.com\"]".to_owned());
@@ -682,7 +1020,14 @@ mod cosmetic_cache_tests {
         }]);
 
         let injected_script = cfcache
-            .hostname_cosmetic_resources(&resources, "antonok.toolforge.org", false)
+            .url_cosmetic_resources(
+                &resources,
+                "antonok.toolforge.org",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .injected_script;
         assert_eq!(
             injected_script,
@@ -690,10 +1035,152 @@ mod cosmetic_cache_tests {
         );
 
         let hide_selectors = cfcache
-            .hostname_cosmetic_resources(&resources, "antonok.github.io", false)
+            .url_cosmetic_resources(
+                &resources,
+                "antonok.github.io",
+                "/",
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
             .hide_selectors;
         let mut expected_hides = HashSet::new();
         expected_hides.insert("div.adToBlock".to_owned());
         assert_eq!(hide_selectors, expected_hides);
     }
+
+    #[test]
+    fn scoped_filters() {
+        let cfcache = CosmeticFilterCache::from_rules(vec![
+            "[$path=/watch]example.com##.watch-ad",
+            r"[$path=/\/page\d+/]##.page-ad",
+            r"/^(www\.)?example\.(com|net)$/##.regex-ad",
+            r"/^(www\.)?example\.(com|net)$/,~www.example.net##.regex-ad2",
+            r"example.*,~/\.net$/##.not-regex-ad",
+            "[$path=/watch?v=2]example.com#@#.watch-ad",
+            r"/\.com$/#@#.not-regex-ad",
+            "[$path=/watch]example.com##.watch-ad:style(color: red)",
+        ]);
+        let resources = ResourceStorage::default();
+
+        let query = |hostname: &str, path: &str| {
+            cfcache.url_cosmetic_resources(
+                &resources,
+                hostname,
+                path,
+                false,
+                false,
+                &mut RegexManager::default(),
+            )
+        };
+        let hide_selectors = |hostname: &str, path: &str| {
+            let mut selectors: Vec<_> = query(hostname, path).hide_selectors.into_iter().collect();
+            selectors.sort();
+            selectors
+        };
+
+        assert_eq!(
+            hide_selectors("example.com", "/watch?v=1"),
+            [".regex-ad", ".regex-ad2", ".watch-ad"]
+        );
+        assert_eq!(
+            hide_selectors("example.com", "/watch?v=2"),
+            [".regex-ad", ".regex-ad2"]
+        );
+        assert_eq!(
+            hide_selectors("example.com", "/"),
+            [".regex-ad", ".regex-ad2"]
+        );
+        assert_eq!(hide_selectors("sub.example.com", "/"), Vec::<String>::new());
+        assert_eq!(hide_selectors("www.example.net", "/"), [".regex-ad"]);
+        assert_eq!(
+            hide_selectors("example.org", "/page12"),
+            [".not-regex-ad", ".page-ad"]
+        );
+        assert_eq!(hide_selectors("test.org", "/page"), Vec::<String>::new());
+
+        assert_eq!(
+            query("example.com", "/watch?v=2").exceptions,
+            HashSet::from([".watch-ad".to_string(), ".not-regex-ad".to_string()])
+        );
+        assert_eq!(query("example.com", "/watch").procedural_actions.len(), 1);
+        assert!(query("example.com", "/").procedural_actions.is_empty());
+
+        // Scoped hide rules are hostname-specific, so `specifichide` applies to them
+        let out = cfcache.url_cosmetic_resources(
+            &resources,
+            "example.com",
+            "/watch",
+            false,
+            true,
+            &mut RegexManager::default(),
+        );
+        assert!(out.hide_selectors.is_empty());
+        assert!(out.procedural_actions.is_empty());
+    }
+
+    #[test]
+    fn html_filter_exceptions() {
+        use crate::html_filtering::HtmlFilters;
+
+        let cfcache = CosmeticFilterCache::from_rules(vec![
+            "example.com##^script[src*=\"adblock\"]",
+            "example.com,~sub.example.com##^.ad",
+            "sub.example.com#@#^script[src*=\"adblock\"]",
+            "example.*##^responseheader(refresh)",
+            "test.example.com#@#^responseheader(refresh)",
+            "example.com##.not-an-html-filter",
+            "[$path=/watch]example.com##^.watch-ad",
+            "[$path=/watch?v=2]example.com#@#^.watch-ad",
+            r"/^example\.(com|net)$/##^responseheader(location)",
+        ]);
+        let html_filters = |hostname: &str, path: &str, specifichide: bool| {
+            cfcache.url_html_filters(hostname, path, specifichide, &mut RegexManager::default())
+        };
+
+        let script_filter = vec![CosmeticFilterOperator::CssSelector(
+            r#"script[src*="adblock"]"#.to_string(),
+        )];
+        let ad_filter = vec![CosmeticFilterOperator::CssSelector(".ad".to_string())];
+
+        let watch_filter = vec![CosmeticFilterOperator::CssSelector(".watch-ad".to_string())];
+
+        assert_eq!(
+            html_filters("example.com", "/", false),
+            HtmlFilters {
+                selectors: vec![ad_filter.clone(), script_filter.clone()],
+                response_headers: vec!["location".to_string(), "refresh".to_string()],
+            }
+        );
+        assert_eq!(
+            html_filters("test.example.com", "/", false),
+            HtmlFilters {
+                selectors: vec![ad_filter.clone(), script_filter.clone()],
+                response_headers: vec![],
+            }
+        );
+        assert_eq!(
+            html_filters("sub.example.com", "/", false),
+            HtmlFilters {
+                selectors: vec![],
+                response_headers: vec!["refresh".to_string()],
+            }
+        );
+        assert!(html_filters("test.net", "/", false).is_empty());
+
+        // Path-scoped rules and their exceptions
+        assert_eq!(
+            html_filters("example.com", "/watch?v=1", false).selectors,
+            vec![ad_filter.clone(), watch_filter, script_filter]
+        );
+        assert_eq!(
+            html_filters("example.com", "/watch?v=2", false)
+                .selectors
+                .len(),
+            2
+        );
+
+        // HTML filters are all hostname-specific, so `specifichide` disables them
+        assert!(html_filters("example.com", "/watch", true).is_empty());
+    }
 }
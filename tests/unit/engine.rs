@@ -195,7 +195,7 @@ mod tests {
     fn deserialization_generate_simple() {
         let mut engine = Engine::new_with_list_text("ad-banner");
         let data = engine.serialize().to_vec();
-        const EXPECTED_HASH: u64 = 15226361072329777649;
+        const EXPECTED_HASH: u64 = 8673460311633487165;
         assert_eq!(hash(&data), EXPECTED_HASH, "{HASH_MISMATCH_MSG}");
         engine.deserialize(&data).unwrap();
     }
@@ -206,7 +206,7 @@ mod tests {
         let mut engine = Engine::new_with_list_text("ad-banner$tag=abc");
         engine.use_tags(&["abc"]);
         let data = engine.serialize().to_vec();
-        const EXPECTED_HASH: u64 = 5340450827873480113;
+        const EXPECTED_HASH: u64 = 16518013466956347106;
         assert_eq!(hash(&data), EXPECTED_HASH, "{HASH_MISMATCH_MSG}");
         engine.deserialize(&data).unwrap();
     }
@@ -530,6 +530,557 @@ mod tests {
             });
     }
 
+    #[test]
+    fn path_scoped_cosmetic_resources() {
+        let filters = [
+            "[$path=/watch]example.com##.watch-ad",
+            "[$path=/watch]example.com##+js(set-constant, ad, false)",
+            r"[$path=/^\/shorts\//]/^(www\.)?example\.com$/##.shorts-ad",
+        ]
+        .join("\n");
+        let mut engine = Engine::new_with_list_text(filters);
+        engine.use_resources([Resource::simple(
+            "set-constant.js",
+            MimeType::ApplicationJavascript,
+            "set-constant",
+        )]);
+
+        // (url, hide selectors, has scriptlet)
+        let url_results = [
+            ("https://example.com/watch?v=1", vec![".watch-ad"], true),
+            ("https://sub.example.com/a/watch", vec![".watch-ad"], true),
+            ("https://example.com/", vec![], false),
+            (
+                "https://www.example.com/shorts/1",
+                vec![".shorts-ad"],
+                false,
+            ),
+            ("https://sub.example.com/shorts/1", vec![], false),
+            ("https://example.com/a/shorts/1", vec![], false),
+            ("https://example.net/watch", vec![], false),
+        ];
+        for (url, hide_selectors, has_scriptlet) in url_results {
+            let result = engine.url_cosmetic_resources(url);
+            assert_eq!(
+                result.hide_selectors,
+                hide_selectors
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<HashSet<_>>(),
+                "{url}"
+            );
+            assert_eq!(!result.injected_script.is_empty(), has_scriptlet, "{url}");
+        }
+    }
+
+    #[test]
+    fn generic_procedural_filters() {
+        use crate::cosmetic_filter_cache::ProceduralOrActionFilter;
+        use crate::filters::cosmetic::{CosmeticFilterAction, CosmeticFilterOperator};
+
+        let filters = [
+            "##.ad:style(opacity: 0)",
+            "##.banner:remove()",
+            "example.com#@#.banner:remove()",
+            "@@||generichide.com^$generichide",
+        ];
+
+        let mut filter_set = FilterSet::new(false);
+        filter_set.add_filters(
+            filters,
+            ParseOptions {
+                allow_generic_procedural_filters: true,
+                ..Default::default()
+            },
+        );
+        let engine = Engine::new_with_filter_set(filter_set);
+
+        let style = serde_json::to_string(&ProceduralOrActionFilter {
+            selector: vec![CosmeticFilterOperator::CssSelector(".ad".to_string())],
+            action: Some(CosmeticFilterAction::Style("opacity: 0".to_string())),
+        })
+        .unwrap();
+        let remove = serde_json::to_string(&ProceduralOrActionFilter {
+            selector: vec![CosmeticFilterOperator::CssSelector(".banner".to_string())],
+            action: Some(CosmeticFilterAction::Remove),
+        })
+        .unwrap();
+
+        let result = engine.url_cosmetic_resources("https://example.net");
+        assert_eq!(
+            result.procedural_actions,
+            HashSet::from([style.clone(), remove])
+        );
+        assert!(result.hide_selectors.is_empty());
+
+        let result = engine.url_cosmetic_resources("https://sub.example.com");
+        assert_eq!(result.procedural_actions, HashSet::from([style]));
+
+        let result = engine.url_cosmetic_resources("https://generichide.com");
+        assert!(result.procedural_actions.is_empty());
+
+        // Without opting in, the generic rules are rejected at parse time.
+        let engine = Engine::new_with_list_text(filters.join("\n"));
+        let result = engine.url_cosmetic_resources("https://example.net");
+        assert!(result.procedural_actions.is_empty());
+        assert!(result.hide_selectors.is_empty());
+    }
+
+    #[test]
+    fn check_network_request_with_cname_aliases() {
+        let filters = [
+            "||tracker.net^",
+            "||ads.example.net^$third-party",
+            "||allowed.tracker.net^",
+            "@@||allowed.tracker.net^$cname",
+            "$removeparam=utm_source",
+        ];
+        let engine = Engine::new_with_list_text(filters.join("\n"));
+
+        let check = |url: &str, aliases: &[&str]| {
+            let request = Request::new(url, "https://example.com", "xhr", "").unwrap();
+            engine.check_network_request_with_cname_aliases(&request, aliases)
+        };
+
+        assert!(!check("https://metrics.example.com/collect", &[]).should_block());
+        assert!(
+            check(
+                "https://metrics.example.com/collect",
+                &["metrics.example.com.cdn.net", "example.com.tracker.net"]
+            )
+            .should_block()
+        );
+        // Party is recomputed for the alias.
+        assert!(check("https://ads.example.com/", &["ads.example.net"]).should_block());
+
+        // `$cname` exceptions only prevent uncloaked blocking.
+        assert!(!check("https://metrics.example.com/", &["allowed.tracker.net"]).should_block());
+        assert!(check("https://allowed.tracker.net/", &[]).should_block());
+
+        // URL rewrites only apply to the original URL.
+        let result = check(
+            "https://metrics.example.com/collect?utm_source=a&id=1",
+            &["tracker.net"],
+        );
+        assert!(result.should_block());
+        assert_eq!(
+            result.rewritten_url.as_deref(),
+            Some("https://metrics.example.com/collect?id=1")
+        );
+    }
+
+    #[test]
+    fn check_hostname() {
+        use crate::blocker::{DnsAction, DnsClient};
+        use crate::filters::network::DnsRewrite;
+
+        let mut filter_set = FilterSet::new(true);
+        filter_set.add_filters(
+            [
+                "||ads.example.com^",
+                "||example.com/ads.js",
+                "||tracker.example.com^$script",
+                "||cdn.example.com^$dnstype=AAAA",
+                "||allowed.example.com^",
+                "@@||allowed.example.com^",
+                "||forced.example.com^$important",
+                "@@||forced.example.com^",
+                "||local.example.com^$dnsrewrite=192.168.0.10",
+                "||local.example.com^$dnsrewrite=NOERROR;AAAA;fd00::10",
+                "||local.example.com^",
+                "||refused.example.com^$dnsrewrite=REFUSED",
+                "@@||refused.example.com^$dnsrewrite",
+                "||kids.example.com^$client='Kid\\'s tablet'|192.168.0.0/24",
+            ],
+            Default::default(),
+        );
+        filter_set.add_filter_list(
+            "0.0.0.0 malware.example.net".to_string(),
+            ParseOptions {
+                format: FilterFormat::Hosts,
+                ..Default::default()
+            },
+        );
+        let engine = Engine::new_with_filter_set(filter_set);
+
+        let action = |hostname: &str, query_type: &str| {
+            engine.check_hostname(hostname, query_type).unwrap().action
+        };
+
+        assert_eq!(action("ads.example.com", "A"), DnsAction::Block);
+        assert_eq!(action("sub.ads.example.com.", "A"), DnsAction::Block);
+        assert_eq!(action("malware.example.net", "A"), DnsAction::Block);
+        // Filters that depend on anything other than the hostname are ignored.
+        assert_eq!(action("example.com", "A"), DnsAction::Allow);
+        assert_eq!(action("tracker.example.com", "A"), DnsAction::Allow);
+        assert_eq!(action("cdn.example.com", "A"), DnsAction::Allow);
+        assert_eq!(action("cdn.example.com", "AAAA"), DnsAction::Block);
+        assert_eq!(action("allowed.example.com", "A"), DnsAction::Allow);
+        assert_eq!(action("forced.example.com", "A"), DnsAction::Block);
+        // Rewrites take precedence over blocking filters, unless they're disabled.
+        assert_eq!(
+            action("local.example.com", "A"),
+            DnsAction::Rewrite(vec![
+                DnsRewrite {
+                    rcode: "NOERROR".to_string(),
+                    rrtype: Some("A".to_string()),
+                    value: Some("192.168.0.10".to_string()),
+                },
+                DnsRewrite {
+                    rcode: "NOERROR".to_string(),
+                    rrtype: Some("AAAA".to_string()),
+                    value: Some("fd00::10".to_string()),
+                },
+            ])
+        );
+        assert_eq!(action("refused.example.com", "A"), DnsAction::Allow);
+
+        let result = engine.check_hostname("allowed.example.com", "A").unwrap();
+        assert_eq!(
+            result.filter.unwrap().raw_line.as_deref(),
+            Some("||allowed.example.com^")
+        );
+        assert_eq!(
+            result.exception.unwrap().raw_line.as_deref(),
+            Some("@@||allowed.example.com^")
+        );
+        let result = engine.check_hostname("local.example.com", "A").unwrap();
+        assert_eq!(result.rewrite_filters.len(), 2);
+        assert!(result.filter.is_none());
+
+        // `$client` filters only apply to the given clients.
+        assert_eq!(action("kids.example.com", "A"), DnsAction::Allow);
+        let tablet = DnsClient {
+            name: Some("Kid's tablet".to_string()),
+            ip_address: None,
+        };
+        let desktop = DnsClient {
+            name: Some("desktop".to_string()),
+            ip_address: Some("192.168.0.20".parse().unwrap()),
+        };
+        for (client, expected) in [
+            (&tablet, DnsAction::Block),
+            (&desktop, DnsAction::Block),
+            (&DnsClient::default(), DnsAction::Allow),
+        ] {
+            assert_eq!(
+                engine
+                    .check_hostname_for_client("kids.example.com", "A", client)
+                    .unwrap()
+                    .action,
+                expected,
+                "{client:?}"
+            );
+        }
+
+        assert!(engine.check_hostname("example.com/ads.js", "A").is_err());
+        assert!(engine.check_hostname("", "A").is_err());
+    }
+
+    #[test]
+    fn explain_network_request() {
+        use crate::blocker::{FilterBucket, MatchOutcome};
+
+        let filters = [
+            "||ads.example.com^",
+            "@@||ads.example.com/script.js",
+            "||ads.example.com^$redirect-rule=noop.js",
+            "||ads.example.com^$redirect-rule=missing.js:5",
+            "$removeparam=utm_source",
+            "||ads.example.com^$removeparam=unused",
+            "||ads.example.com/tracker.js$important",
+            "@@||ads.example.com/tracker.js",
+            "/generic-ad.js",
+            "/generic-ad.js$domain=example.org",
+            "@@||example.org^$genericblock",
+            "||example.org^$csp=script-src 'none'",
+            "@@||example.org/page$csp=script-src 'none'",
+        ];
+        let mut filter_set = FilterSet::new(true);
+        filter_set.add_filters(filters, Default::default());
+        let mut engine = Engine::new_with_filter_set(filter_set);
+        engine.use_resources([Resource::simple(
+            "noop.js",
+            MimeType::ApplicationJavascript,
+            "(function() {})()",
+        )]);
+
+        let explain = |url: &str, source_url: &str, request_type: &str| {
+            let request = Request::new(url, source_url, request_type, "").unwrap();
+            let mut matches: Vec<_> = engine
+                .explain_network_request(&request)
+                .matches
+                .into_iter()
+                .map(|m| {
+                    let location = m.filter.source_location.unwrap();
+                    (location.line_number, m.bucket, m.outcome)
+                })
+                .collect();
+            matches.sort_by_key(|(line_number, _, _)| *line_number);
+            matches
+        };
+
+        let missing = MatchOutcome::RedirectResourceMissing {
+            resource: "missing.js".to_string(),
+        };
+        assert_eq!(
+            explain(
+                "https://ads.example.com/script.js?utm_source=a&id=1",
+                "https://example.net",
+                "xhr"
+            ),
+            [
+                (0, FilterBucket::Regular, MatchOutcome::Excepted),
+                (1, FilterBucket::Exception, MatchOutcome::Applied),
+                (2, FilterBucket::Redirect, MatchOutcome::Superseded),
+                (3, FilterBucket::Redirect, missing),
+                (4, FilterBucket::Removeparam, MatchOutcome::Applied),
+                (5, FilterBucket::Removeparam, MatchOutcome::NoEffect),
+            ]
+        );
+
+        let explanation = engine.explain_network_request(
+            &Request::new(
+                "https://ads.example.com/tracker.js?utm_source=a",
+                "https://example.net",
+                "xhr",
+                "",
+            )
+            .unwrap(),
+        );
+        assert!(explanation.result.important);
+        let mut matches: Vec<_> = explanation
+            .matches
+            .into_iter()
+            .filter(|m| m.bucket != FilterBucket::Redirect)
+            .map(|m| (m.filter.raw_line.unwrap(), m.bucket, m.outcome))
+            .collect();
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            matches,
+            [
+                (
+                    "$removeparam=utm_source".to_string(),
+                    FilterBucket::Removeparam,
+                    MatchOutcome::OverriddenByImportant
+                ),
+                (
+                    "@@||ads.example.com/tracker.js".to_string(),
+                    FilterBucket::Exception,
+                    MatchOutcome::OverriddenByImportant
+                ),
+                (
+                    "||ads.example.com/tracker.js$important".to_string(),
+                    FilterBucket::Important,
+                    MatchOutcome::Applied
+                ),
+                (
+                    "||ads.example.com^".to_string(),
+                    FilterBucket::Regular,
+                    MatchOutcome::OverriddenByImportant
+                ),
+                (
+                    "||ads.example.com^$removeparam=unused".to_string(),
+                    FilterBucket::Removeparam,
+                    MatchOutcome::OverriddenByImportant
+                ),
+            ]
+        );
+
+        assert_eq!(
+            explain(
+                "https://cdn.example.com/generic-ad.js",
+                "https://example.org",
+                "script"
+            ),
+            [
+                (8, FilterBucket::Regular, MatchOutcome::GenericBlock),
+                (9, FilterBucket::Regular, MatchOutcome::Applied),
+            ]
+        );
+
+        assert_eq!(
+            explain("https://example.org/page", "", "document"),
+            [
+                (11, FilterBucket::Csp, MatchOutcome::Excepted),
+                (12, FilterBucket::Csp, MatchOutcome::Applied),
+            ]
+        );
+        assert_eq!(
+            explain("https://example.org/script.js", "", "script"),
+            [(11, FilterBucket::Csp, MatchOutcome::NoEffect)]
+        );
+    }
+
+    #[test]
+    fn cosmetic_session() {
+        let filters = [
+            "##.ad",
+            "##.ad > .banner",
+            "###sponsor",
+            "##a[href=\"generic.com\"]",
+            "example.com##.ad",
+            "example.com#@##sponsor",
+            "@@||generichide.com^$generichide",
+        ];
+        let engine = Engine::new_with_list_text(filters.join("\n"));
+
+        let mut session = engine.cosmetic_session("https://example.com");
+        assert_eq!(
+            session.resources().hide_selectors,
+            HashSet::from(["a[href=\"generic.com\"]".to_string(), ".ad".to_string()])
+        );
+
+        // `.ad` is already hidden by the hostname-specific rule, and `#sponsor` is excepted.
+        let mut selectors = session.hidden_class_id_selectors(["ad", "other"], ["sponsor"]);
+        selectors.sort();
+        assert_eq!(selectors, [".ad > .banner"]);
+
+        // Classes and ids that were already seen don't produce any new selectors.
+        assert!(
+            session
+                .hidden_class_id_selectors(["ad"], ["sponsor"])
+                .is_empty()
+        );
+
+        assert_eq!(
+            session.stylesheet(),
+            concat!(
+                ".ad { display: none !important; }\n",
+                ".ad > .banner { display: none !important; }\n",
+                "a[href=\"generic.com\"] { display: none !important; }\n",
+            )
+        );
+
+        let mut session = engine.cosmetic_session("https://example.net");
+        let mut selectors = session.hidden_class_id_selectors(["ad"], ["sponsor"]);
+        selectors.sort();
+        assert_eq!(selectors, ["#sponsor", ".ad", ".ad > .banner"]);
+
+        let mut session = engine.cosmetic_session("https://generichide.com");
+        assert!(session.resources().generichide);
+        assert!(
+            session
+                .hidden_class_id_selectors(["ad"], ["sponsor"])
+                .is_empty()
+        );
+        assert_eq!(session.stylesheet(), "");
+    }
+
+    #[test]
+    fn elemhide_and_specifichide() {
+        let filters = [
+            "##a[href=\"generic.com\"]",
+            "example.com,example2.com,example3.com,example4.com##.specific",
+            "example.com,example2.com,example3.com,example4.com##.specific:style(color: red)",
+            "example.com,example2.com,example3.com,example4.com##+js(set-constant, ad, false)",
+            "example.com,example2.com,example3.com,example4.com##^.html-ad",
+            "example.com,example2.com,example3.com,example4.com##^responseheader(refresh)",
+            "@@||example.com^$elemhide",
+            "@@||example2.com^$specifichide",
+            "@@||example3.com^$generichide,shide",
+        ]
+        .join("\n");
+        let mut engine = Engine::new_with_list_text(filters);
+        engine.use_resources([Resource::simple(
+            "set-constant.js",
+            MimeType::ApplicationJavascript,
+            "set-constant",
+        )]);
+
+        // (url, hide selectors, has procedural actions, generichide, specifichide, elemhide)
+        let url_results = [
+            ("https://example.com", vec![], false, true, true, true),
+            (
+                "https://example2.com",
+                vec!["a[href=\"generic.com\"]"],
+                false,
+                false,
+                true,
+                false,
+            ),
+            ("https://example3.com", vec![], false, true, true, true),
+            (
+                "https://example4.com",
+                vec!["a[href=\"generic.com\"]", ".specific"],
+                true,
+                false,
+                false,
+                false,
+            ),
+        ];
+        for (url, hide_selectors, has_procedural, generichide, specifichide, elemhide) in
+            url_results
+        {
+            let result = engine.url_cosmetic_resources(url);
+            assert_eq!(
+                result.hide_selectors,
+                hide_selectors
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<HashSet<_>>(),
+                "{url}"
+            );
+            assert_eq!(
+                !result.procedural_actions.is_empty(),
+                has_procedural,
+                "{url}"
+            );
+            assert_eq!(result.generichide, generichide, "{url}");
+            assert_eq!(result.specifichide, specifichide, "{url}");
+            assert_eq!(result.elemhide, elemhide, "{url}");
+            // Scriptlets are unaffected
+            assert!(!result.injected_script.is_empty(), "{url}");
+            // HTML filters are all hostname-specific
+            assert_eq!(
+                engine.url_html_filters(url).is_empty(),
+                specifichide,
+                "{url}"
+            );
+            assert_eq!(
+                engine.url_removed_response_headers(url).is_empty(),
+                specifichide,
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn genericblock() {
+        let engine = Engine::new_with_list_text(
+            [
+                "/generic-ad.js",
+                "/specific-ad.js$domain=example.com|example.org",
+                "||ads.example.net^$important",
+                "@@||example.com^$genericblock",
+            ]
+            .join("\n"),
+        );
+        let check = |url, source| {
+            engine
+                .check_network_request(&Request::new(url, source, "script", "").unwrap())
+                .should_block()
+        };
+
+        for source in ["https://example.com", "https://sub.example.com"] {
+            assert!(!check("https://cdn.example.net/generic-ad.js", source));
+            assert!(!check("https://ads.example.net/a.js", source));
+            assert!(check("https://cdn.example.net/specific-ad.js", source));
+        }
+        for source in ["https://example.org", "https://notexample.com"] {
+            assert!(check("https://cdn.example.net/generic-ad.js", source));
+            assert!(check("https://ads.example.net/a.js", source));
+        }
+        assert!(check(
+            "https://cdn.example.net/specific-ad.js",
+            "https://example.org"
+        ));
+        assert!(!check(
+            "https://cdn.example.net/specific-ad.js",
+            "https://notexample.com"
+        ));
+    }
+
     #[test]
     fn important_redirect() {
         let mut filter_set = FilterSet::new(true);
@@ -949,4 +1500,152 @@ trustedSetLocalStorageItem("mol.ads.cmp.tcf.cache", "{\"getTCData\":{\"cmpId\":2
             "POST xhr should still be blocked"
         );
     }
+
+    #[test]
+    fn method_option_other_methods() {
+        let engine = Engine::new_with_list_text(
+            "||api.example.com/v1/events$xhr,method=put|patch\n@@||api.example.com/v1/events$xhr,method=patch,domain=example.org",
+        );
+        let url = "https://api.example.com/v1/events";
+
+        for (method, source, blocked) in [
+            ("put", "https://example.com", true),
+            ("patch", "https://example.com", true),
+            ("delete", "https://example.com", false),
+            ("options", "https://example.com", false),
+            ("put", "https://example.org", true),
+            ("patch", "https://example.org", false),
+        ] {
+            let request = Request::new(url, source, "xhr", method).unwrap();
+            assert_eq!(
+                engine.check_network_request(&request).should_block(),
+                blocked,
+                "{method} xhr from {source}"
+            );
+        }
+    }
+
+    #[test]
+    fn to_option_entity() {
+        let engine = Engine::new_with_list_text("/ads/*$script,to=example.*");
+        let source = "https://news.site.org/page";
+
+        for (url, blocked) in [
+            ("https://example.com/ads/a.js", true),
+            ("https://cdn.example.co.uk/ads/a.js", true),
+            ("https://example.net/ads/a.js", true),
+            ("https://example.com/content/a.js", false),
+            ("https://other.com/ads/a.js", false),
+            ("https://example.other.com/ads/a.js", false),
+        ] {
+            let request = Request::new(url, source, "script", "").unwrap();
+            assert_eq!(
+                engine.check_network_request(&request).should_block(),
+                blocked,
+                "{url}"
+            );
+        }
+
+        let preparsed = Request::preparsed(
+            "https://cdn.example.co.uk/ads/a.js",
+            "cdn.example.co.uk",
+            "news.site.org",
+            "script",
+            true,
+            "",
+        );
+        assert!(engine.check_network_request(&preparsed).should_block());
+    }
+
+    #[test]
+    fn to_option_negated_entity() {
+        let engine = Engine::new_with_list_text("/ads/*$script,to=~example.*");
+        let source = "https://news.site.org/page";
+
+        for (url, blocked) in [
+            ("https://example.com/ads/a.js", false),
+            ("https://cdn.example.co.uk/ads/a.js", false),
+            ("https://other.com/ads/a.js", true),
+            ("https://example.other.com/ads/a.js", true),
+        ] {
+            let request = Request::new(url, source, "script", "").unwrap();
+            assert_eq!(
+                engine.check_network_request(&request).should_block(),
+                blocked,
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn to_option_not_fused_by_optimizer() {
+        let engine =
+            Engine::new_with_list_text(["/banner$image", "/track$image,to=example.com"].join("\n"));
+        let source = "https://news.site.org/page";
+
+        for (url, blocked) in [
+            ("https://other.org/banner.png", true),
+            ("https://example.com/track.png", true),
+            ("https://other.org/track.png", false),
+        ] {
+            let request = Request::new(url, source, "image", "").unwrap();
+            assert_eq!(
+                engine.check_network_request(&request).should_block(),
+                blocked,
+                "{url}"
+            );
+        }
+    }
+
+    #[test]
+    fn preprocessor_directives() {
+        let list = [
+            "||a.example.com^",
+            "!#if env_mobile",
+            "||b.example.com^",
+            "!#include mobile.txt",
+            "!#else",
+            "||c.example.com^",
+            "!#include desktop.txt",
+            "!#endif",
+            "!#include missing.txt",
+            "||d.example.com^",
+        ]
+        .join("\n");
+        let mut loaded = vec![];
+        let loader = |path: &str| {
+            loaded.push(path.to_string());
+            match path {
+                "mobile.txt" => Some("||e.example.com^\n!#include nested.txt".to_string()),
+                "desktop.txt" => Some("||f.example.com^".to_string()),
+                _ => None,
+            }
+        };
+
+        let mut filter_set = FilterSet::new(true);
+        filter_set.add_filter_list_with_includes(
+            list,
+            ParseOptions::default(),
+            &["env_mobile"].into_iter().collect(),
+            loader,
+        );
+        assert_eq!(loaded, ["mobile.txt", "missing.txt"]);
+        let engine = Engine::new_with_filter_set(filter_set);
+
+        let line_number = |hostname: &str| {
+            let url = format!("https://{hostname}/ad.js");
+            let request = Request::new(&url, "https://example.net", "script", "").unwrap();
+            engine
+                .check_network_request(&request)
+                .filter
+                .and_then(|f| f.source_location)
+                .map(|location| location.line_number)
+        };
+        assert_eq!(line_number("a.example.com"), Some(0));
+        assert_eq!(line_number("b.example.com"), Some(2));
+        assert_eq!(line_number("c.example.com"), None);
+        assert_eq!(line_number("d.example.com"), Some(9));
+        assert_eq!(line_number("e.example.com"), Some(3));
+        assert_eq!(line_number("f.example.com"), None);
+    }
 }
@@ -2,7 +2,7 @@ use super::*;
 
 #[test]
 fn parse_pipe_delimited_domains_works() {
-    let values = parse_pipe_delimited_domains("google.*|gstatic.com|~example.it").unwrap();
+    let values = parse_pipe_delimited_domains("google.*|gstatic.com|~example.it", true).unwrap();
     assert_eq!(values.len(), 3);
     assert_eq!(values[0], (true, "google.*"));
     assert_eq!(values[1], (true, "gstatic.com"));
@@ -10,9 +10,60 @@ fn parse_pipe_delimited_domains_works() {
 }
 
 #[test]
-fn parse_pipe_delimited_domains_strips_regex() {
-    assert!(parse_pipe_delimited_domains("/^foo/").is_err());
-    let values = parse_pipe_delimited_domains("/^foo/|bar.com").unwrap();
+#[cfg(feature = "full-regex-handling")]
+fn parse_pipe_delimited_domains_keeps_regex() {
+    let values = parse_pipe_delimited_domains("/^foo/|bar.com|~/a|b/", true).unwrap();
+    assert_eq!(
+        values,
+        [(true, "/^foo/"), (true, "bar.com"), (false, "/a|b/")]
+    );
+    assert!(matches!(
+        parse_pipe_delimited_domains("/(foo/", true),
+        Err(NetworkFilterError::RegexParsingError(_))
+    ));
+}
+
+#[test]
+fn parse_pipe_delimited_domains_strips_regex_if_disallowed() {
+    assert!(parse_pipe_delimited_domains("/^foo/", false).is_err());
+    let values = parse_pipe_delimited_domains("/^foo/|bar.com", false).unwrap();
     assert_eq!(values.len(), 1);
     assert_eq!(values[0], (true, "bar.com"));
 }
+
+#[test]
+fn split_pipe_delimited_domains_works() {
+    let split = |value| split_pipe_delimited_domains(value).collect::<Vec<_>>();
+    assert_eq!(split("a.com|~b.com"), ["a.com", "~b.com"]);
+    assert_eq!(split("/a|b/|c.com"), ["/a|b/", "c.com"]);
+    assert_eq!(split("~/a|b/|/c\\/|d/"), ["~/a|b/", "/c\\/|d/"]);
+    assert_eq!(split("/a/b/|c"), ["/a/b/", "c"]);
+    // An unterminated regex is split like any other entry
+    assert_eq!(split("/a|b"), ["/a", "b"]);
+    assert_eq!(split("a||b|"), ["a", "", "b", ""]);
+}
+
+#[test]
+fn split_unescaped_works() {
+    let parts: Vec<_> = split_unescaped("a,b\\,c,,d\\\\,e", b',').collect();
+    assert_eq!(parts, ["a", "b\\,c", "", "d\\\\", "e"]);
+}
+
+#[test]
+#[cfg(feature = "full-regex-handling")]
+fn options_separator_skips_escaped_dollar() {
+    let line = "||foo.com^$removeparam=/^a\\$/";
+    let parsed = AbstractNetworkFilter::parse(line).unwrap();
+    assert_eq!(&line[parsed.pattern.start..parsed.pattern.end], "foo.com^");
+    assert!(matches!(
+        parsed.options.as_deref(),
+        Some([NetworkFilterOption::Removeparam(Some("/^a\\$/"))])
+    ));
+}
+
+#[test]
+fn unescape_option_value_works() {
+    assert_eq!(unescape_option_value("^a=\\d+$"), "^a=\\d+$");
+    assert_eq!(unescape_option_value("^a=\\d{1\\,3}\\$"), "^a=\\d{1,3}$");
+    assert_eq!(unescape_option_value("\\\\"), "\\\\");
+}
@@ -761,6 +761,97 @@ mod parse_tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "css-validation")]
+    fn ubo_extended_procedural_operators() {
+        fn check_procedural(raw: &str, expected_selectors: Vec<CosmeticFilterOperator>) {
+            check_parse_result(
+                &format!("example.com##{raw}"),
+                CosmeticFilterBreakdown {
+                    selector: SelectorType::Procedural(expected_selectors),
+                    hostnames: sort_hash_domains(vec!["example.com"]),
+                    ..Default::default()
+                },
+            );
+        }
+        check_procedural(
+            ".sidebar:matches-media((max-width: 800px))",
+            vec![
+                CosmeticFilterOperator::CssSelector(".sidebar".to_string()),
+                CosmeticFilterOperator::MatchesMedia("(max-width: 800px)".to_string()),
+            ],
+        );
+        check_procedural(
+            "div:matches-prop(dataset.adType)",
+            vec![
+                CosmeticFilterOperator::CssSelector("div".to_string()),
+                CosmeticFilterOperator::MatchesProp("dataset.adType".to_string()),
+            ],
+        );
+        check_procedural(
+            r#"div:matches-prop(dataset.kind="ad")"#,
+            vec![
+                CosmeticFilterOperator::CssSelector("div".to_string()),
+                CosmeticFilterOperator::MatchesProp(r#"dataset.kind="ad""#.to_string()),
+            ],
+        );
+        check_procedural(
+            ".feed:has-text(Sponsored):watch-attr(class, style)",
+            vec![
+                CosmeticFilterOperator::CssSelector(".feed".to_string()),
+                CosmeticFilterOperator::HasText("Sponsored".to_string()),
+                CosmeticFilterOperator::WatchAttr("class, style".to_string()),
+            ],
+        );
+        check_procedural(
+            ".feed:has-text(Sponsored):watch-attr()",
+            vec![
+                CosmeticFilterOperator::CssSelector(".feed".to_string()),
+                CosmeticFilterOperator::HasText("Sponsored".to_string()),
+                CosmeticFilterOperator::WatchAttr("".to_string()),
+            ],
+        );
+        check_procedural(
+            "#content:others()",
+            vec![
+                CosmeticFilterOperator::CssSelector("#content".to_string()),
+                CosmeticFilterOperator::Others,
+            ],
+        );
+        check_procedural(
+            "#content:others",
+            vec![
+                CosmeticFilterOperator::CssSelector("#content".to_string()),
+                CosmeticFilterOperator::Others,
+            ],
+        );
+
+        assert!(parse_cf("example.com##.sidebar:matches-media()").is_err());
+        assert!(parse_cf("example.com##div:matches-prop()").is_err());
+        assert!(parse_cf("example.com##div:matches-prop(dataset..kind)").is_err());
+        assert!(parse_cf("example.com##div:matches-prop(=ad)").is_err());
+        assert!(parse_cf("example.com##div:matches-prop(kind=/ad/g)").is_err());
+        assert!(parse_cf("example.com##div:matches-prop(kind=/a/d/)").is_err());
+        assert!(parse_cf("example.com##div:matches-prop(kind=//)").is_err());
+        assert!(parse_cf("example.com##div:watch-attr(class,,style)").is_err());
+        assert!(parse_cf("example.com##div:others(.ad)").is_err());
+
+        // Regexes are only compiled by content scripts, so JS-only syntax is accepted
+        assert!(parse_cf("example.com##div:matches-prop(kind=/(?<=x)ad/)").is_ok());
+        assert!(parse_cf(r"example.com##div:matches-prop(kind=/(a)\1d/)").is_ok());
+
+        let filter = parse_cf("example.com##div:matches-prop(dataset.kind=/^ad$/i)").unwrap();
+        assert_eq!(
+            serde_json::to_string(&filter.selector).unwrap(),
+            r#"[{"type":"css-selector","arg":"div"},{"type":"matches-prop","arg":"dataset.kind=/^ad$/i"}]"#,
+        );
+        let filter = parse_cf("example.com###content:others()").unwrap();
+        assert_eq!(
+            serde_json::to_string(&filter.selector).unwrap(),
+            r##"[{"type":"css-selector","arg":"#content"},{"type":"others"}]"##,
+        );
+    }
+
     #[test]
     #[cfg(feature = "css-validation")]
     fn unsupported() {
@@ -785,6 +876,61 @@ mod parse_tests {
         assert!(parse_cf("##.t-rec > .t886:has-text(cookies)").is_err());
     }
 
+    #[test]
+    fn generic_procedural_opt_in() {
+        let opts = ParseOptions {
+            allow_generic_procedural_filters: true,
+            ..Default::default()
+        };
+        let parse_generic = |rule| CosmeticFilter::parse_with_options(rule, false, opts);
+
+        for rule in [
+            "##.ad:style(opacity: 0)",
+            "##.ad:remove()",
+            "##.ad:remove-attr(onclick)",
+            "#$#.ad { opacity: 0; }",
+        ] {
+            assert_eq!(
+                parse_cf(rule).err(),
+                Some(CosmeticFilterError::GenericAction),
+                "{rule}"
+            );
+            let filter = parse_generic(rule).unwrap();
+            assert!(!filter.has_hostname_constraint(), "{rule}");
+            assert!(filter.action.is_some(), "{rule}");
+        }
+
+        #[cfg(feature = "css-validation")]
+        {
+            assert_eq!(
+                parse_cf("##div:has-text(Sponsored)").err(),
+                Some(CosmeticFilterError::GenericAction)
+            );
+            let filter = parse_generic("##div:has-text(Sponsored)").unwrap();
+            assert_eq!(
+                filter.selector,
+                vec![
+                    CosmeticFilterOperator::CssSelector("div".to_string()),
+                    CosmeticFilterOperator::HasText("Sponsored".to_string()),
+                ]
+            );
+        }
+
+        // Other kinds of generic rules are still rejected.
+        assert_eq!(
+            parse_generic("##+js(nowebrtc)").err(),
+            Some(CosmeticFilterError::GenericScriptInject)
+        );
+        assert_eq!(
+            parse_generic("##^script:has-text(ad)").err(),
+            Some(CosmeticFilterError::GenericHtmlFilter)
+        );
+        assert_eq!(
+            parse_generic("#@#.ad:remove()").err(),
+            Some(CosmeticFilterError::GenericUnhide)
+        );
+    }
+
     #[test]
     fn hidden_generic() {
         let rule = parse_cf("##.selector").unwrap();
@@ -855,6 +1001,162 @@ mod parse_tests {
 
         let rule = parse_cf("test.com#@#+js(nowebrtc.js)").unwrap();
         assert!(rule.hidden_generic_rule().is_none());
+
+        #[cfg(feature = "css-validation")]
+        {
+            let rule = parse_cf("~test.com##.selector:has-text(ad)").unwrap();
+            assert!(rule.hidden_generic_rule().is_none());
+        }
+    }
+
+    #[test]
+    fn html_filters() {
+        let rule = parse_cf("example.com##^script:has-text(adblock)").unwrap();
+        assert!(rule.mask.contains(CosmeticFilterMask::HTML_FILTER));
+        #[cfg(feature = "css-validation")]
+        assert_eq!(
+            rule.selector,
+            vec![
+                CosmeticFilterOperator::CssSelector("script".to_string()),
+                CosmeticFilterOperator::HasText("adblock".to_string()),
+            ]
+        );
+        assert!(rule.action.is_none());
+
+        let rule = parse_cf("example.com##^.ad-banner").unwrap();
+        assert!(rule.mask.contains(CosmeticFilterMask::HTML_FILTER));
+        assert_eq!(rule.plain_css_selector(), Some(".ad-banner"));
+
+        let rule = parse_cf("example.com#@#^.ad-banner").unwrap();
+        assert!(rule.mask.contains(CosmeticFilterMask::HTML_FILTER));
+        assert!(rule.mask.contains(CosmeticFilterMask::UNHIDE));
+
+        let rule = parse_cf("example.com##^responseheader(Set-Cookie)").unwrap();
+        assert!(rule.mask.contains(CosmeticFilterMask::HTML_FILTER));
+        assert_eq!(
+            rule.selector,
+            vec![CosmeticFilterOperator::ResponseHeader(
+                "set-cookie".to_string()
+            )]
+        );
+        assert_eq!(rule.plain_css_selector(), None);
+
+        // Negated locations should not produce a hidden generic rule
+        let rule = parse_cf("~example.com##^script:has-text(adblock)").unwrap();
+        assert!(rule.hidden_generic_rule().is_none());
+
+        assert_eq!(
+            parse_cf("##^script:has-text(adblock)").unwrap_err(),
+            CosmeticFilterError::GenericHtmlFilter
+        );
+        assert_eq!(
+            parse_cf("example.com##^.ad:remove()").unwrap_err(),
+            CosmeticFilterError::InvalidActionSpecifier
+        );
+        assert_eq!(
+            parse_cf("example.com##^responseheader()").unwrap_err(),
+            CosmeticFilterError::InvalidResponseHeader
+        );
+        assert_eq!(
+            parse_cf("example.com##^responseheader(x header)").unwrap_err(),
+            CosmeticFilterError::InvalidResponseHeader
+        );
+
+        // Only a small set of headers can be removed by untrusted filters
+        assert_eq!(
+            parse_cf("example.com##^responseheader(content-security-policy)").unwrap_err(),
+            CosmeticFilterError::UntrustedResponseHeader
+        );
+        let rule = CosmeticFilter::parse(
+            "example.com##^responseheader(content-security-policy)",
+            false,
+            PermissionMask::from_bits(0b1),
+        )
+        .unwrap();
+        assert_eq!(
+            rule.selector,
+            vec![CosmeticFilterOperator::ResponseHeader(
+                "content-security-policy".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn scoped_locations() {
+        let rule = parse_cf("[$path=/watch]youtube.com##.ad").unwrap();
+        assert_eq!(rule.path.as_deref(), Some("/watch"));
+        assert_eq!(
+            rule.hostnames,
+            Some(vec![crate::utils::fast_hash("youtube.com")])
+        );
+        assert!(rule.is_scoped());
+        assert!(rule.hidden_generic_rule().is_none());
+
+        // Path-scoped rules don't need a hostname, and can use procedural operators or scriptlets
+        let rule = parse_cf(r"[$path=/\/page\d+\.html/]##.ad").unwrap();
+        assert_eq!(rule.path.as_deref(), Some(r"/\/page\d+\.html/"));
+        assert!(!rule.has_hostname_constraint());
+        assert!(parse_cf("[$path=/watch]##.ad:style(color: red)").is_ok());
+        assert!(parse_cf("[$path=/watch]##+js(nowebrtc)").is_ok());
+        let rule = parse_cf("[$path=/watch]example.com##^.ad").unwrap();
+        assert!(rule.mask.contains(CosmeticFilterMask::HTML_FILTER));
+        assert!(rule.is_scoped());
+        // Escaped commas are part of the value
+        let rule = parse_cf(r"[$path=/a\,b]example.com##.ad").unwrap();
+        assert_eq!(rule.path.as_deref(), Some("/a,b"));
+
+        let rule = parse_cf(r"example.com,/^example\.(com|net)$/,~/^www\./,/a{1,3}/##.ad").unwrap();
+        assert_eq!(
+            rule.hostnames,
+            Some(vec![crate::utils::fast_hash("example.com")])
+        );
+        assert_eq!(
+            rule.hostname_regexes,
+            Some(vec![
+                r"^example\.(com|net)$".to_string(),
+                "a{1,3}".to_string()
+            ])
+        );
+        assert_eq!(rule.not_hostname_regexes, Some(vec![r"^www\.".to_string()]));
+        assert!(rule.path.is_none());
+        assert!(rule.is_scoped());
+
+        let rule = parse_cf(r"~/^www\./##.ad").unwrap();
+        assert!(rule.has_hostname_constraint());
+        assert!(rule.hidden_generic_rule().is_none());
+
+        assert_eq!(
+            parse_cf("[$domain=example.com]##.ad").unwrap_err(),
+            CosmeticFilterError::LocationModifiersUnsupported
+        );
+        assert_eq!(
+            parse_cf("[$path=/a,path=/b]##.ad").unwrap_err(),
+            CosmeticFilterError::LocationModifiersUnsupported
+        );
+        assert_eq!(
+            parse_cf("[$path=]##.ad").unwrap_err(),
+            CosmeticFilterError::LocationModifiersUnsupported
+        );
+        assert_eq!(
+            parse_cf("[$path=/watch##.ad").unwrap_err(),
+            CosmeticFilterError::LocationModifiersUnsupported
+        );
+        assert_eq!(
+            parse_cf("[path=/watch]##.ad").unwrap_err(),
+            CosmeticFilterError::LocationModifiersUnsupported
+        );
+        assert_eq!(
+            parse_cf("[$path=/(/]##.ad").unwrap_err(),
+            CosmeticFilterError::InvalidLocationRegex
+        );
+        assert_eq!(
+            parse_cf("/(/##.ad").unwrap_err(),
+            CosmeticFilterError::InvalidLocationRegex
+        );
+        assert_eq!(
+            parse_cf(r"~/^www\./#@#.ad").unwrap_err(),
+            CosmeticFilterError::DoubleNegation
+        );
     }
 
     #[test]
@@ -959,6 +1261,138 @@ mod parse_tests {
         assert!(parse_cf("example.com##.ad { background: url(https://evil.com) }").is_err());
     }
 
+    #[test]
+    fn adguard_style_injection() {
+        check_parse_result(
+            "nczas.com#$#.adsbygoogle { position: absolute!important; left: -3000px!important; }",
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["nczas.com"]),
+                selector: SelectorType::PlainCss(".adsbygoogle".to_string()),
+                action: Some(CosmeticFilterAction::Style(
+                    "position: absolute!important; left: -3000px!important;".into(),
+                )),
+                ..Default::default()
+            },
+        );
+        check_parse_result(
+            "kurnik.pl#@$#.adsbygoogle { height: 1px !important; width: 1px !important; }",
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["kurnik.pl"]),
+                selector: SelectorType::PlainCss(".adsbygoogle".to_string()),
+                action: Some(CosmeticFilterAction::Style(
+                    "height: 1px !important; width: 1px !important;".into(),
+                )),
+                unhide: true,
+                ..Default::default()
+            },
+        );
+        check_parse_result(
+            "example.com#$#.banner { remove: true; }",
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["example.com"]),
+                selector: SelectorType::PlainCss(".banner".to_string()),
+                action: Some(CosmeticFilterAction::Remove),
+                ..Default::default()
+            },
+        );
+        #[cfg(feature = "css-validation")]
+        check_parse_result(
+            "example.com#$?#div:has-text(Ad) { display: none!important; }",
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["example.com"]),
+                selector: SelectorType::Procedural(vec![
+                    CosmeticFilterOperator::CssSelector("div".to_string()),
+                    CosmeticFilterOperator::HasText("Ad".to_string()),
+                ]),
+                action: Some(CosmeticFilterAction::Style(
+                    "display: none!important;".into(),
+                )),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            parse_cf("#$#.ad { display: none; }").err(),
+            Some(CosmeticFilterError::GenericAction)
+        );
+        assert_eq!(
+            parse_cf("example.com#$#.ad").err(),
+            Some(CosmeticFilterError::AdguardSyntaxUnsupported)
+        );
+        assert_eq!(
+            parse_cf("example.com#$#@media (min-width: 1024px) { .ad { display: none; } }").err(),
+            Some(CosmeticFilterError::AdguardSyntaxUnsupported)
+        );
+        assert_eq!(
+            parse_cf("example.com#$%#.ad { display: none; }").err(),
+            Some(CosmeticFilterError::UnsupportedSyntax)
+        );
+    }
+
+    #[test]
+    fn adguard_scriptlet_injection() {
+        check_parse_result(
+            r#"odkrywamyzakryte.com#%#//scriptlet("abort-on-property-read", "sc_adv_out")"#,
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["odkrywamyzakryte.com"]),
+                selector: SelectorType::PlainCss("abort-on-property-read, sc_adv_out".to_string()),
+                script_inject: true,
+                ..Default::default()
+            },
+        );
+        check_parse_result(
+            r#"example.com#%#//scriptlet('prevent-setTimeout', 'ads, banner', '300')"#,
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["example.com"]),
+                selector: SelectorType::PlainCss(
+                    r#"no-setTimeout-if, "ads, banner", 300"#.to_string(),
+                ),
+                script_inject: true,
+                ..Default::default()
+            },
+        );
+        check_parse_result(
+            r#"example.com#@%#//scriptlet('ubo-aopr.js', 'adblock')"#,
+            CosmeticFilterBreakdown {
+                hostnames: sort_hash_domains(vec!["example.com"]),
+                selector: SelectorType::PlainCss("aopr, adblock".to_string()),
+                script_inject: true,
+                unhide: true,
+                ..Default::default()
+            },
+        );
+
+        // Translated rules must be equivalent to the corresponding uBO rule.
+        let adguard = parse_cf(r#"example.com#%#//scriptlet('set-constant', 'a.b', 'it\'s')"#);
+        let ubo = parse_cf(r#"example.com##+js(set-constant, a.b, it's)"#);
+        assert_eq!(adguard.unwrap().selector, ubo.unwrap().selector,);
+
+        assert_eq!(
+            parse_cf("#%#//scriptlet('nowebrtc')").err(),
+            Some(CosmeticFilterError::GenericScriptInject)
+        );
+        assert_eq!(
+            parse_cf("example.com#%#//scriptlet('google-ima3')").err(),
+            Some(CosmeticFilterError::AdguardSyntaxUnsupported)
+        );
+        assert_eq!(
+            parse_cf("example.com#%#window.adsbygoogle = { loaded: true };").err(),
+            Some(CosmeticFilterError::AdguardSyntaxUnsupported)
+        );
+        assert_eq!(
+            parse_cf("example.com#%#//scriptlet(nowebrtc)").err(),
+            Some(CosmeticFilterError::InvalidScriptletArgs)
+        );
+        assert_eq!(
+            parse_cf("example.com#%#//scriptlet()").err(),
+            Some(CosmeticFilterError::InvalidScriptletArgs)
+        );
+        assert_eq!(
+            parse_cf("example.com#%?#//scriptlet('nowebrtc')").err(),
+            Some(CosmeticFilterError::UnsupportedSyntax)
+        );
+    }
+
     #[test]
     #[cfg(feature = "css-validation")]
     fn abp_style_injection_extended() {
@@ -1295,6 +1729,85 @@ mod matching_tests {
         assert!(parse_cf("example.com###adBanner:remove-class(src)").is_ok());
     }
 
+    #[test]
+    fn action_args() {
+        fn action(rule: &str) -> Option<CosmeticFilterAction> {
+            parse_cf(rule).unwrap().action
+        }
+        assert_eq!(
+            action("example.com##.ad:remove-attr(onclick)"),
+            Some(CosmeticFilterAction::RemoveAttr("onclick".into()))
+        );
+        assert_eq!(
+            action(r#"example.com##.ad:remove-attr("data-(x)")"#),
+            Some(CosmeticFilterAction::RemoveAttr("data-(x)".into()))
+        );
+        assert_eq!(
+            action(r"example.com##.ad:remove-class('it\'s')"),
+            Some(CosmeticFilterAction::RemoveClass("it's".into()))
+        );
+        assert_eq!(
+            action("example.com##.ad:remove-attr(/^data-ad/)"),
+            Some(CosmeticFilterAction::RemoveAttr(
+                CosmeticFilterActionArg::Regex {
+                    regex: "^data-ad".to_string(),
+                    flags: "".to_string(),
+                }
+            ))
+        );
+        assert_eq!(
+            action("example.com##.ad:remove-class(/^ad-\\d+$/i)"),
+            Some(CosmeticFilterAction::RemoveClass(
+                CosmeticFilterActionArg::Regex {
+                    regex: "^ad-\\d+$".to_string(),
+                    flags: "i".to_string(),
+                }
+            ))
+        );
+
+        assert_eq!(
+            action(r"example.com##.ad:remove-attr(/^data-[/]\/(?<n>x)/mu)"),
+            Some(CosmeticFilterAction::RemoveAttr(
+                CosmeticFilterActionArg::Regex {
+                    regex: r"^data-[/]\/(?<n>x)".to_string(),
+                    flags: "mu".to_string(),
+                }
+            ))
+        );
+        // Patterns are compiled by content scripts, so they're passed through unchecked
+        assert_eq!(
+            action("example.com##.ad:remove-attr(/(data/)"),
+            Some(CosmeticFilterAction::RemoveAttr(
+                CosmeticFilterActionArg::Regex {
+                    regex: "(data".to_string(),
+                    flags: "".to_string(),
+                }
+            ))
+        );
+
+        for rule in [
+            "example.com##.ad:remove-class(/ad/g)",
+            "example.com##.ad:remove-class(/ad/ii)",
+            "example.com##.ad:remove-class(//)",
+            "example.com##.ad:remove-class(/a/b/)",
+            r"example.com##.ad:remove-class(/ad\/)",
+        ] {
+            assert_eq!(
+                parse_cf(rule).err(),
+                Some(CosmeticFilterError::InvalidActionRegex),
+                "{rule}"
+            );
+        }
+        assert_eq!(
+            parse_cf(r#"example.com##.ad:remove-attr("onclick)"#).err(),
+            Some(CosmeticFilterError::InvalidActionSpecifier)
+        );
+        assert_eq!(
+            parse_cf(r#"example.com##.ad:remove-attr("on"click")"#).err(),
+            Some(CosmeticFilterError::InvalidActionSpecifier)
+        );
+    }
+
     #[test]
     #[allow(clippy::invisible_characters)]
     fn zero_width_space() {
@@ -1303,8 +1816,11 @@ mod matching_tests {
 
     #[test]
     fn adg_regex() {
-        assert!(parse_cf(r"/^dizipal\d+\.com$/##.web").is_err());
-        // Filter is still salvageable if at least one location is supported
+        let rule = parse_cf(r"/^dizipal\d+\.com$/##.web").unwrap();
+        assert_eq!(
+            rule.hostname_regexes,
+            Some(vec![r"^dizipal\d+\.com$".to_string()])
+        );
         assert!(parse_cf(r"/^dizipal\d+\.com,test.net$/##.web").is_ok());
     }
 
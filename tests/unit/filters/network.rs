@@ -35,9 +35,7 @@ mod parse_tests {
         from_websocket: bool,
         from_xml_http_request: bool,
         from_document: bool,
-        from_get: bool,
-        from_head: bool,
-        from_post: bool,
+        methods: NetworkFilterMethodMask,
         match_case: bool,
         third_party: bool,
     }
@@ -79,9 +77,7 @@ mod parse_tests {
                 from_websocket: filter.mask.contains(NetworkFilterMask::FROM_WEBSOCKET),
                 from_xml_http_request: filter.mask.contains(NetworkFilterMask::FROM_XMLHTTPREQUEST),
                 from_document: filter.mask.contains(NetworkFilterMask::FROM_DOCUMENT),
-                from_get: filter.mask.contains(NetworkFilterMask::FROM_GET),
-                from_head: filter.mask.contains(NetworkFilterMask::FROM_HEAD),
-                from_post: filter.mask.contains(NetworkFilterMask::FROM_POST),
+                methods: filter.methods,
                 match_case: filter.match_case(),
                 third_party: filter.third_party(),
             }
@@ -121,22 +117,18 @@ mod parse_tests {
             from_websocket: true,
             from_xml_http_request: true,
             from_document: false,
-            from_get: false,
-            from_head: false,
-            from_post: false,
+            methods: NetworkFilterMethodMask::empty(),
             match_case: false,
             third_party: true,
         }
     }
 
-    fn foo_method_breakdown(get: bool, head: bool, post: bool) -> NetworkFilterBreakdown {
+    fn foo_method_breakdown(methods: NetworkFilterMethodMask) -> NetworkFilterBreakdown {
         let mut expected = default_network_filter_breakdown();
         expected.hostname = Some(String::from("foo"));
         expected.is_hostname_anchor = true;
         expected.is_plain = true;
-        expected.from_get = get;
-        expected.from_head = head;
-        expected.from_post = post;
+        expected.methods = methods;
         expected
     }
 
@@ -720,13 +712,94 @@ mod parse_tests {
             assert_eq!(filter.opt_domains, Some(vec![utils::fast_hash("bar.com")]));
             assert_eq!(filter.opt_not_domains, None);
         }
+        // parses entities
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com$domain=google.*|~google.com|~yahoo.*|bar.com",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert_eq!(filter.opt_domains, Some(vec![utils::fast_hash("bar.com")]));
+            assert_eq!(
+                filter.opt_not_domains,
+                Some(vec![utils::fast_hash("google.com")])
+            );
+            assert_eq!(filter.opt_entities, Some(vec![utils::fast_hash("google")]));
+            assert_eq!(
+                filter.opt_not_entities,
+                Some(vec![utils::fast_hash("yahoo")])
+            );
+        }
+        {
+            // Entities don't share an id with hostnames that have the same hash
+            let entity =
+                NetworkFilter::parse("||foo.com$domain=google.*", true, Default::default())
+                    .unwrap();
+            let not_entity =
+                NetworkFilter::parse("||foo.com$domain=~google.*", true, Default::default())
+                    .unwrap();
+            let hostname =
+                NetworkFilter::parse("||foo.com$domain=google", true, Default::default()).unwrap();
+            assert_eq!(entity.opt_domains, None);
+            assert_ne!(entity.get_id(), not_entity.get_id());
+            assert_ne!(entity.get_id(), hostname.get_id());
+        }
+        // parses regexes
+        #[cfg(feature = "full-regex-handling")]
         {
             let filter = NetworkFilter::parse(
                 r"||video.twimg.com/ext_tw_video/*/*.m3u8$domain=/^i[a-z]*\.strmrdr[a-z]+\..*/",
                 true,
                 Default::default(),
+            )
+            .unwrap();
+            assert_eq!(filter.opt_domains, None);
+            assert_eq!(
+                filter.opt_domain_regexes,
+                Some(vec![r"^i[a-z]*\.strmrdr[a-z]+\..*".into()])
             );
-            assert_eq!(filter.err(), Some(NetworkFilterError::NoSupportedDomains));
+            assert_eq!(filter.opt_not_domain_regexes, None);
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            // `|` doesn't end a regex, and `,` and `$` can be escaped
+            let filter = NetworkFilter::parse(
+                r"||foo.com$domain=bar.com|/^(a|b)\.com\$/|~/^c{1\,3}\.org\$/|~/baz/,image",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert_eq!(filter.opt_domains, Some(vec![utils::fast_hash("bar.com")]));
+            assert_eq!(
+                filter.opt_domain_regexes,
+                Some(vec![r"^(a|b)\.com$".into()])
+            );
+            assert_eq!(
+                filter.opt_not_domain_regexes,
+                Some(vec![r"^c{1,3}\.org$".into(), "baz".into()])
+            );
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_IMAGE));
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter = NetworkFilter::parse("||foo.com$domain=/(abc/", true, Default::default());
+            assert!(matches!(
+                filter.err(),
+                Some(NetworkFilterError::RegexParsingError(_))
+            ));
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            // Regexes don't share an id with their negations, or with hostnames
+            let regex =
+                NetworkFilter::parse("||foo.com$domain=/bar/", true, Default::default()).unwrap();
+            let not_regex =
+                NetworkFilter::parse("||foo.com$domain=~/bar/", true, Default::default()).unwrap();
+            let hostname =
+                NetworkFilter::parse("||foo.com$domain=bar", true, Default::default()).unwrap();
+            assert_ne!(regex.get_id(), not_regex.get_id());
+            assert_ne!(regex.get_id(), hostname.get_id());
         }
     }
 
@@ -751,7 +824,25 @@ mod parse_tests {
                 Default::default(),
             )
             .unwrap();
-            assert_eq!(filter.opt_to_domains.as_ref().map(|d| d.len()), Some(2));
+            assert_eq!(
+                filter.opt_to_domains,
+                Some(vec![utils::fast_hash("gstatic.com")])
+            );
+            assert_eq!(
+                filter.opt_to_entities,
+                Some(vec![utils::fast_hash("google")])
+            );
+            assert_eq!(filter.opt_not_to_entities, None);
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com$to=~google.*", true, Default::default()).unwrap();
+            assert_eq!(filter.opt_not_to_domains, None);
+            assert_eq!(
+                filter.opt_not_to_entities,
+                Some(vec![utils::fast_hash("google")])
+            );
+            assert!(filter.has_to_option());
         }
         {
             let filter =
@@ -801,6 +892,257 @@ mod parse_tests {
         }
     }
 
+    #[test]
+    fn parses_denyallow() {
+        {
+            let filter = NetworkFilter::parse(
+                "*$script,3p,denyallow=x.com|y.com|x.com,domain=a.com",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            let mut expected = vec![utils::fast_hash("x.com"), utils::fast_hash("y.com")];
+            expected.sort_unstable();
+            assert_eq!(filter.opt_denyallow_domains, Some(expected));
+            assert_eq!(filter.opt_domains, Some(vec![utils::fast_hash("a.com")]));
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$script", true, Default::default()).unwrap();
+            assert_eq!(filter.opt_denyallow_domains, None);
+        }
+        assert_ne!(
+            NetworkFilter::parse("*$denyallow=x.com,domain=a.com", true, Default::default())
+                .unwrap()
+                .get_id(),
+            NetworkFilter::parse("*$domain=a.com|~x.com", true, Default::default())
+                .unwrap()
+                .get_id()
+        );
+        for (line, error) in [
+            (
+                "*$script,~denyallow=x.com,domain=a.com",
+                NetworkFilterError::NegatedDenyallow,
+            ),
+            (
+                "*$script,denyallow=,domain=a.com",
+                NetworkFilterError::DenyallowInvalidValue,
+            ),
+            (
+                "*$script,denyallow=~x.com,domain=a.com",
+                NetworkFilterError::DenyallowInvalidValue,
+            ),
+            (
+                "*$script,denyallow=x.*,domain=a.com",
+                NetworkFilterError::DenyallowInvalidValue,
+            ),
+            (
+                "*$script,denyallow=/^x/,domain=a.com",
+                NetworkFilterError::DenyallowInvalidValue,
+            ),
+        ] {
+            let filter = NetworkFilter::parse(line, true, Default::default());
+            assert_eq!(filter.err(), Some(error), "{line}");
+        }
+    }
+
+    #[test]
+    fn parses_ipaddress() {
+        for (line, value) in [
+            ("*$ipaddress=lan", "lan"),
+            ("*$ipaddress=10.0.0.0/8", "10.0.0.0/8"),
+            ("||example.com^$ipaddress=2001:db8::1", "2001:db8::1"),
+        ] {
+            let filter = NetworkFilter::parse(line, true, Default::default()).unwrap();
+            assert_eq!(filter.opt_ip_address, Some(value), "{line}");
+        }
+        assert_ne!(
+            NetworkFilter::parse("*$ipaddress=lan", true, Default::default())
+                .unwrap()
+                .get_id(),
+            NetworkFilter::parse("*$ipaddress=loopback", true, Default::default())
+                .unwrap()
+                .get_id()
+        );
+
+        for (line, error) in [
+            ("*$ipaddress=", NetworkFilterError::EmptyIpAddress),
+            ("*$~ipaddress=lan", NetworkFilterError::NegatedIpAddress),
+            ("*$ipaddress=wan", NetworkFilterError::IpAddressInvalidValue),
+            (
+                "*$ipaddress=10.0.0",
+                NetworkFilterError::IpAddressInvalidValue,
+            ),
+            (
+                "*$ipaddress=10.0.0.0/33",
+                NetworkFilterError::IpAddressInvalidValue,
+            ),
+            (
+                "*$ipaddress=::/129",
+                NetworkFilterError::IpAddressInvalidValue,
+            ),
+            (
+                "*$ipaddress=/^10\\./g",
+                NetworkFilterError::IpAddressInvalidValue,
+            ),
+        ] {
+            let filter = NetworkFilter::parse(line, true, Default::default());
+            assert_eq!(filter.err(), Some(error), "{line}");
+        }
+        #[cfg(feature = "full-regex-handling")]
+        assert!(matches!(
+            NetworkFilter::parse("*$ipaddress=/(/", true, Default::default()),
+            Err(NetworkFilterError::RegexParsingError(_))
+        ));
+    }
+
+    #[test]
+    fn parses_dns_options() {
+        let filter =
+            NetworkFilter::parse("||example.com^$dnstype=A|~AAAA", true, Default::default())
+                .unwrap();
+        assert_eq!(filter.opt_dns_type, Some("A|~AAAA"));
+        assert!(filter.is_dns_only());
+        let filter = NetworkFilter::parse(
+            "||example.com^$client='Frank\\'s laptop'|192.168.0.0/24,important",
+            true,
+            Default::default(),
+        )
+        .unwrap();
+        assert_eq!(filter.opt_client, Some("'Frank\\'s laptop'|192.168.0.0/24"));
+        assert!(filter.is_important());
+        let filter = NetworkFilter::parse(
+            "||example.com^$dnsrewrite=1.2.3.4",
+            true,
+            Default::default(),
+        )
+        .unwrap();
+        assert!(filter.is_dnsrewrite());
+        assert_eq!(filter.modifier_option, Some("1.2.3.4"));
+        let filter =
+            NetworkFilter::parse("@@||example.com^$dnsrewrite", true, Default::default()).unwrap();
+        assert!(filter.is_dnsrewrite());
+        assert_eq!(filter.modifier_option, None);
+        assert!(
+            !NetworkFilter::parse("||example.com^", true, Default::default())
+                .unwrap()
+                .is_dns_only()
+        );
+        assert_ne!(
+            NetworkFilter::parse("||example.com^$dnstype=A", true, Default::default())
+                .unwrap()
+                .get_id(),
+            NetworkFilter::parse("||example.com^$client=A", true, Default::default())
+                .unwrap()
+                .get_id()
+        );
+
+        for (line, error) in [
+            (
+                "||example.com^$~dnstype=A",
+                NetworkFilterError::NegatedDnsType,
+            ),
+            (
+                "||example.com^$dnstype=",
+                NetworkFilterError::DnsTypeInvalidValue,
+            ),
+            (
+                "||example.com^$dnstype=A|",
+                NetworkFilterError::DnsTypeInvalidValue,
+            ),
+            (
+                "||example.com^$~client=tv",
+                NetworkFilterError::NegatedClient,
+            ),
+            (
+                "||example.com^$client=",
+                NetworkFilterError::ClientInvalidValue,
+            ),
+            (
+                "||example.com^$client='tv",
+                NetworkFilterError::ClientInvalidValue,
+            ),
+            (
+                "||example.com^$client='tv'x",
+                NetworkFilterError::ClientInvalidValue,
+            ),
+            (
+                "||example.com^$dnsrewrite",
+                NetworkFilterError::EmptyDnsRewrite,
+            ),
+            (
+                "||example.com^$~dnsrewrite=1.2.3.4",
+                NetworkFilterError::NegatedDnsRewrite,
+            ),
+            (
+                "||example.com^$dnsrewrite=NOERROR;A;::1",
+                NetworkFilterError::DnsRewriteInvalidValue,
+            ),
+            (
+                "||example.com^$dnsrewrite=BADCODE;A;1.2.3.4",
+                NetworkFilterError::DnsRewriteInvalidValue,
+            ),
+            (
+                "||example.com^$dnsrewrite=NOERROR;;1.2.3.4",
+                NetworkFilterError::DnsRewriteInvalidValue,
+            ),
+            (
+                "||example.com^$dnsrewrite=example/path",
+                NetworkFilterError::DnsRewriteInvalidValue,
+            ),
+            (
+                "||example.com^$dnstype=A,third-party",
+                NetworkFilterError::DnsOptionWithUnsupportedOption,
+            ),
+            (
+                "||example.com^$dnsrewrite=1.2.3.4,csp=default-src 'none'",
+                NetworkFilterError::MultipleModifierOptions,
+            ),
+            (
+                "||example.com/ads$dnstype=A",
+                NetworkFilterError::DnsOptionWithoutHostname,
+            ),
+        ] {
+            let filter = NetworkFilter::parse(line, true, Default::default());
+            assert_eq!(filter.err(), Some(error), "{line}");
+        }
+        #[cfg(feature = "full-regex-handling")]
+        assert_eq!(
+            NetworkFilter::parse("/ads/$dnstype=A", true, Default::default()).err(),
+            Some(NetworkFilterError::DnsOptionWithoutHostname)
+        );
+    }
+
+    #[test]
+    fn parses_dnsrewrite_values() {
+        let rewrite = |rcode: &str, rrtype: Option<&str>, value: Option<&str>| DnsRewrite {
+            rcode: rcode.to_string(),
+            rrtype: rrtype.map(str::to_string),
+            value: value.map(str::to_string),
+        };
+        for (value, expected) in [
+            ("1.2.3.4", rewrite("NOERROR", Some("A"), Some("1.2.3.4"))),
+            ("::1", rewrite("NOERROR", Some("AAAA"), Some("::1"))),
+            (
+                "example.net",
+                rewrite("NOERROR", Some("CNAME"), Some("example.net")),
+            ),
+            ("REFUSED", rewrite("REFUSED", None, None)),
+            (
+                "NOERROR;A;1.2.3.4",
+                rewrite("NOERROR", Some("A"), Some("1.2.3.4")),
+            ),
+            (
+                "NOERROR;txt;hello world",
+                rewrite("NOERROR", Some("TXT"), Some("hello world")),
+            ),
+            ("NOERROR;AAAA;", rewrite("NOERROR", Some("AAAA"), None)),
+            ("NXDOMAIN;;", rewrite("NXDOMAIN", None, None)),
+        ] {
+            assert_eq!(DnsRewrite::parse(value), Ok(expected), "{value}");
+        }
+    }
+
     #[test]
     fn parses_redirects() {
         // parses redirect
@@ -879,8 +1221,10 @@ mod parse_tests {
     #[test]
     fn parses_removeparam() {
         {
-            let filter = NetworkFilter::parse("||foo.com^$removeparam", true, Default::default());
-            assert!(filter.is_err());
+            let filter =
+                NetworkFilter::parse("||foo.com^$removeparam", true, Default::default()).unwrap();
+            assert!(filter.is_removeparam());
+            assert_eq!(filter.modifier_option, None);
         }
         {
             let filter = NetworkFilter::parse("$~removeparam=test", true, Default::default());
@@ -888,12 +1232,26 @@ mod parse_tests {
         }
         {
             let filter =
-                NetworkFilter::parse("@@||foo.com^$removeparam=test", true, Default::default());
-            assert!(filter.is_err());
+                NetworkFilter::parse("@@||foo.com^$removeparam=test", true, Default::default())
+                    .unwrap();
+            assert!(filter.is_removeparam());
+            assert!(filter.is_exception());
+            assert_eq!(filter.modifier_option, Some("test"));
         }
         {
-            let filter = NetworkFilter::parse("||foo.com^$removeparam=", true, Default::default());
-            assert!(filter.is_err());
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$removeparam", true, Default::default()).unwrap();
+            assert!(filter.is_exception());
+            assert_eq!(filter.modifier_option, None);
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$removeparam=", true, Default::default()).unwrap();
+            assert_eq!(filter.modifier_option, None);
+        }
+        {
+            let filter = NetworkFilter::parse("||foo.com^$removeparam=~", true, Default::default());
+            assert_eq!(filter, Err(NetworkFilterError::EmptyRemoveparam));
         }
         {
             let filter = NetworkFilter::parse(
@@ -916,10 +1274,57 @@ mod parse_tests {
                 NetworkFilter::parse("||foo.com^$removeparam=𝐔𝐍𝐈𝐂𝐎𝐃𝐄🧋", true, Default::default());
             assert!(filter.is_err());
         }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$removeparam=/abc.*/", true, Default::default())
+                    .unwrap();
+            assert_eq!(filter.modifier_option, Some("/abc.*/"));
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$removeparam=~/^utm_/i", true, Default::default())
+                    .unwrap();
+            assert_eq!(filter.modifier_option, Some("~/^utm_/i"));
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com^$removeparam=/^(a|b)=\\d{1\\,3}\\$/,xhr",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert_eq!(filter.modifier_option, Some("/^(a|b)=\\d{1\\,3}\\$/"));
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_XMLHTTPREQUEST));
+            assert!(!filter.mask.contains(NetworkFilterMask::FROM_DOCUMENT));
+        }
         {
             let filter =
-                NetworkFilter::parse("||foo.com^$removeparam=/abc.*/", true, Default::default());
-            assert_eq!(filter, Err(NetworkFilterError::RemoveparamRegexUnsupported));
+                NetworkFilter::parse("||foo.com^$removeparam=/abc/g", true, Default::default());
+            assert_eq!(filter, Err(NetworkFilterError::RemoveparamInvalidValue));
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$removeparam=/(abc/", true, Default::default());
+            assert!(matches!(
+                filter,
+                Err(NetworkFilterError::RegexParsingError(_))
+            ));
+        }
+        #[cfg(not(feature = "full-regex-handling"))]
+        assert_eq!(
+            NetworkFilter::parse("||foo.com^$removeparam=/abc.*/", true, Default::default()),
+            Err(NetworkFilterError::RemoveparamRegexUnsupported)
+        );
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$queryprune=test", true, Default::default())
+                    .unwrap();
+            assert!(filter.is_removeparam());
+            assert_eq!(filter.modifier_option, Some("test"));
         }
         {
             let filter =
@@ -930,6 +1335,353 @@ mod parse_tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "full-regex-handling")]
+    fn parses_replace() {
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com/player.js$replace=/autoplay:!0/autoplay:!1/g",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.is_replace());
+            assert!(!filter.is_exception());
+            assert_eq!(filter.modifier_option, Some("/autoplay:!0/autoplay:!1/g"));
+        }
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com^$xhr,replace=/\\/ads\\/(\\d+)/\\/\\$1\\,x/i",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_XMLHTTPREQUEST));
+            let value = ReplaceValue::parse(filter.modifier_option.unwrap()).unwrap();
+            assert_eq!(value.pattern, "/ads/(\\d+)");
+            assert_eq!(value.replacement, "/$1,x");
+            assert!(value.ignore_case);
+            assert!(!value.global);
+        }
+        {
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$replace", true, Default::default()).unwrap();
+            assert!(filter.is_replace());
+            assert!(filter.is_exception());
+            assert_eq!(filter.modifier_option, None);
+        }
+        {
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$replace=/a/b/", true, Default::default())
+                    .unwrap();
+            assert!(filter.is_exception());
+            assert_eq!(filter.modifier_option, Some("/a/b/"));
+        }
+        {
+            let filter = NetworkFilter::parse("||foo.com^$replace", true, Default::default());
+            assert_eq!(filter, Err(NetworkFilterError::EmptyReplace));
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$~replace=/a/b/", true, Default::default());
+            assert_eq!(filter, Err(NetworkFilterError::NegatedReplace));
+        }
+        {
+            for value in ["a/b/", "/a/b", "//b/", "/a/b/c/", "/a/b/x"] {
+                let line = format!("||foo.com^$replace={value}");
+                let filter = NetworkFilter::parse(&line, true, Default::default());
+                assert_eq!(
+                    filter,
+                    Err(NetworkFilterError::ReplaceInvalidValue),
+                    "{value}"
+                );
+            }
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$replace=/(a/b/", true, Default::default());
+            assert!(matches!(
+                filter,
+                Err(NetworkFilterError::RegexParsingError(_))
+            ));
+        }
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com^$replace=/a/b/,removeparam=test",
+                true,
+                Default::default(),
+            );
+            assert_eq!(filter, Err(NetworkFilterError::MultipleModifierOptions));
+        }
+    }
+
+    #[test]
+    fn parses_header() {
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$header=via:1.1 google", true, Default::default())
+                    .unwrap();
+            assert!(filter.is_header());
+            assert_eq!(filter.modifier_option, Some("via:1.1 google"));
+            let value = HeaderValue::parse(filter.modifier_option.unwrap()).unwrap();
+            assert_eq!(value.name, "via");
+            assert!(!value.negated);
+            assert_eq!(value.matcher, HeaderMatcher::Literal("1.1 google".into()));
+        }
+        {
+            let filter = NetworkFilter::parse(
+                "@@||foo.com^$header=X-Frame-Options",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.is_header());
+            assert!(filter.is_exception());
+            let value = HeaderValue::parse(filter.modifier_option.unwrap()).unwrap();
+            assert_eq!(value.name, "X-Frame-Options");
+            assert_eq!(value.matcher, HeaderMatcher::Present);
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com^$header=server:~/^nginx\\,?/i",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            let value = HeaderValue::parse(filter.modifier_option.unwrap()).unwrap();
+            assert!(value.negated);
+            assert_eq!(
+                value.matcher,
+                HeaderMatcher::Regex {
+                    pattern: "^nginx,?".into(),
+                    ignore_case: true
+                }
+            );
+        }
+        {
+            for (line, error) in [
+                ("||foo.com^$header", NetworkFilterError::EmptyHeader),
+                ("||foo.com^$header=:value", NetworkFilterError::EmptyHeader),
+                ("||foo.com^$~header=via", NetworkFilterError::NegatedHeader),
+                (
+                    "||foo.com^$header=via:",
+                    NetworkFilterError::HeaderInvalidValue,
+                ),
+                (
+                    "||foo.com^$header=via:~",
+                    NetworkFilterError::HeaderInvalidValue,
+                ),
+                (
+                    "||foo.com^$header=a b:c",
+                    NetworkFilterError::HeaderInvalidValue,
+                ),
+                (
+                    "||foo.com^$header=via:/a/g",
+                    NetworkFilterError::HeaderInvalidValue,
+                ),
+                (
+                    "||foo.com^$header=via,csp=script-src 'none'",
+                    NetworkFilterError::MultipleModifierOptions,
+                ),
+            ] {
+                assert_eq!(
+                    NetworkFilter::parse(line, true, Default::default()),
+                    Err(error),
+                    "{line}"
+                );
+            }
+        }
+        #[cfg(feature = "full-regex-handling")]
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$header=via:/(a/", true, Default::default());
+            assert!(matches!(
+                filter,
+                Err(NetworkFilterError::RegexParsingError(_))
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "full-regex-handling")]
+    fn parses_urltransform() {
+        let trusted = ParseOptions {
+            permissions: crate::resources::PermissionMask::from_bits(0b1),
+            ..Default::default()
+        };
+        {
+            let filter = NetworkFilter::parse(
+                r"||example.com^$urltransform=/utm_([a-z]+)/ref_\$1/g",
+                true,
+                trusted,
+            )
+            .unwrap();
+            assert!(filter.is_urltransform());
+            assert_eq!(filter.modifier_option, Some(r"/utm_([a-z]+)/ref_\$1/g"));
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_DOCUMENT));
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||example.com^$uritransform=/a/b/", true, trusted).unwrap();
+            assert!(filter.is_urltransform());
+        }
+        {
+            // Exceptions are accepted from untrusted lists
+            let filter = NetworkFilter::parse(
+                "@@||example.com^$urltransform=/a/b/",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.is_urltransform());
+            let filter =
+                NetworkFilter::parse("@@||example.com^$urltransform", true, Default::default())
+                    .unwrap();
+            assert_eq!(filter.modifier_option, None);
+        }
+        for (line, opts, error) in [
+            (
+                "||example.com^$urltransform=/a/b/",
+                Default::default(),
+                NetworkFilterError::UntrustedUrlTransform,
+            ),
+            (
+                "||example.com^$urltransform",
+                trusted,
+                NetworkFilterError::EmptyUrlTransform,
+            ),
+            (
+                "||example.com^$~urltransform=/a/b/",
+                trusted,
+                NetworkFilterError::NegatedUrlTransform,
+            ),
+            (
+                "||example.com^$urltransform=a/b",
+                trusted,
+                NetworkFilterError::UrlTransformInvalidValue,
+            ),
+            (
+                "||example.com^$urltransform=/a/b/,removeparam=c",
+                trusted,
+                NetworkFilterError::MultipleModifierOptions,
+            ),
+        ] {
+            let filter = NetworkFilter::parse(line, true, opts);
+            assert_eq!(filter.err(), Some(error), "{line}");
+        }
+    }
+
+    #[test]
+    fn parses_popup() {
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com^$popup", true, Default::default()).unwrap();
+            assert!(filter.is_popup());
+            assert!(!filter.is_popunder());
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_DOCUMENT));
+        }
+        {
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$popunder,3p", true, Default::default()).unwrap();
+            assert!(filter.is_popunder());
+            assert!(filter.is_exception());
+        }
+        for (line, error) in [
+            ("||foo.com^$~popup", NetworkFilterError::NegatedPopup),
+            ("||foo.com^$~popunder", NetworkFilterError::NegatedPopup),
+            (
+                "||foo.com^$popup,script",
+                NetworkFilterError::PopupWithContentType,
+            ),
+            (
+                "||foo.com^$popup,popunder",
+                NetworkFilterError::MultipleModifierOptions,
+            ),
+            (
+                "||foo.com^$popup,redirect=noop.js",
+                NetworkFilterError::MultipleModifierOptions,
+            ),
+        ] {
+            let filter = NetworkFilter::parse(line, true, Default::default());
+            assert_eq!(filter.err(), Some(error), "{line}");
+        }
+    }
+
+    #[test]
+    fn parses_permissions() {
+        {
+            let filter = NetworkFilter::parse(
+                "||foo.com^$permissions=autoplay=()|geolocation=(self \"https://foo.com\")",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.is_permissions());
+            assert!(filter.mask.contains(NetworkFilterMask::FROM_DOCUMENT));
+            assert_eq!(
+                parse_permissions_directives(filter.modifier_option.unwrap()),
+                Ok(vec![
+                    "autoplay=()".to_string(),
+                    "geolocation=(self \"https://foo.com\")".to_string()
+                ])
+            );
+        }
+        {
+            let filter = NetworkFilter::parse(
+                "$domain=foo.com,permissions=camera=()\\, microphone=*",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert_eq!(
+                parse_permissions_directives(filter.modifier_option.unwrap()),
+                Ok(vec!["camera=()".to_string(), "microphone=*".to_string()])
+            );
+        }
+        {
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$permissions", true, Default::default()).unwrap();
+            assert!(filter.is_permissions());
+            assert!(filter.is_exception());
+            assert_eq!(filter.modifier_option, None);
+        }
+        {
+            for (line, error) in [
+                (
+                    "||foo.com^$~permissions=camera=()",
+                    NetworkFilterError::NegatedPermissions,
+                ),
+                (
+                    "||foo.com^$permissions=camera",
+                    NetworkFilterError::PermissionsInvalidValue,
+                ),
+                (
+                    "||foo.com^$permissions=camera=()|",
+                    NetworkFilterError::PermissionsInvalidValue,
+                ),
+                (
+                    "||foo.com^$permissions=camera=self",
+                    NetworkFilterError::PermissionsInvalidValue,
+                ),
+                (
+                    "||foo.com^$script,permissions=camera=()",
+                    NetworkFilterError::PermissionsWithContentType,
+                ),
+                (
+                    "||foo.com^$permissions=camera=(),csp=script-src 'none'",
+                    NetworkFilterError::MultipleModifierOptions,
+                ),
+            ] {
+                assert_eq!(
+                    NetworkFilter::parse(line, true, Default::default()),
+                    Err(error),
+                    "{line}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn parses_match_case() {
         // match-case on non-regex rules is invalid
@@ -1069,6 +1821,40 @@ mod parse_tests {
         );
     }
 
+    #[test]
+    fn parses_strict_party() {
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com$strict1p", true, Default::default()).unwrap();
+            assert!(filter.strict_first_party());
+            assert!(!filter.strict_third_party());
+            assert!(filter.first_party());
+            assert!(filter.third_party());
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com$strict3p", true, Default::default()).unwrap();
+            assert!(!filter.strict_first_party());
+            assert!(filter.strict_third_party());
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com$~strict1p", true, Default::default()).unwrap();
+            assert!(filter.strict_third_party());
+        }
+        {
+            let filter =
+                NetworkFilter::parse("||foo.com$~strict3p", true, Default::default()).unwrap();
+            assert!(filter.strict_first_party());
+        }
+        // defaults to false
+        {
+            let filter = NetworkFilter::parse("||foo.com", true, Default::default()).unwrap();
+            assert!(!filter.strict_first_party());
+            assert!(!filter.strict_third_party());
+        }
+    }
+
     #[test]
     fn parses_generic_hide() {
         {
@@ -1107,6 +1893,74 @@ mod parse_tests {
         }
     }
 
+    #[test]
+    fn parses_page_exceptions() {
+        for (option, features) in [
+            ("elemhide", NetworkFilterFeaturesMask::ELEM_HIDE),
+            ("ehide", NetworkFilterFeaturesMask::ELEM_HIDE),
+            ("specifichide", NetworkFilterFeaturesMask::SPECIFIC_HIDE),
+            ("shide", NetworkFilterFeaturesMask::SPECIFIC_HIDE),
+            ("genericblock", NetworkFilterFeaturesMask::GENERIC_BLOCK),
+        ] {
+            let line = format!("@@||foo.com^${option}");
+            let filter = NetworkFilter::parse(&line, true, Default::default()).unwrap();
+            assert!(filter.is_exception());
+            assert_eq!(filter.features_mask, features, "{option}");
+        }
+        {
+            let filter = NetworkFilter::parse(
+                "@@||foo.com^$generichide,specifichide,genericblock",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            assert!(filter.is_generic_hide());
+            assert!(!filter.is_elem_hide());
+            assert!(filter.is_specific_hide());
+            assert!(filter.is_generic_block());
+        }
+        {
+            let filter =
+                NetworkFilter::parse("@@||foo.com^$cname", true, Default::default()).unwrap();
+            assert!(filter.is_cname());
+            assert!(!filter.is_generic_block());
+        }
+        for (filter, error) in [
+            (
+                "||foo.com^$elemhide",
+                NetworkFilterError::ElemHideWithoutException,
+            ),
+            (
+                "||foo.com^$shide",
+                NetworkFilterError::SpecificHideWithoutException,
+            ),
+            (
+                "||foo.com^$genericblock",
+                NetworkFilterError::GenericBlockWithoutException,
+            ),
+            ("@@||foo.com^$~ehide", NetworkFilterError::NegatedElemHide),
+            (
+                "@@||foo.com^$~specifichide",
+                NetworkFilterError::NegatedSpecificHide,
+            ),
+            (
+                "@@||foo.com^$~genericblock",
+                NetworkFilterError::NegatedGenericBlock,
+            ),
+            (
+                "||foo.com^$cname",
+                NetworkFilterError::CnameWithoutException,
+            ),
+            ("@@||foo.com^$~cname", NetworkFilterError::NegatedCname),
+        ] {
+            assert_eq!(
+                NetworkFilter::parse(filter, true, Default::default()).err(),
+                Some(error),
+                "{filter}"
+            );
+        }
+    }
+
     #[test]
     fn parses_hosts_style() {
         {
@@ -1212,7 +2066,7 @@ mod parse_tests {
 
     #[test]
     fn handles_unsupported_options() {
-        let options = vec!["genericblock", "inline-script", "popunder", "popup", "woot"];
+        let options = vec!["genericblock", "inline-script", "woot"];
 
         for option in options {
             let rule = format!("||foo.com${option}");
@@ -1388,19 +2242,28 @@ mod parse_tests {
 
     #[test]
     fn handles_method_options() {
-        const CASES: &[(&str, bool, bool, bool)] = &[
-            ("||foo$method=post", false, false, true),
-            ("||foo$method=post|get", true, false, true),
-            ("||foo$method=head|get", true, true, false),
-            ("||foo$method=~get", false, true, true),
-            ("||foo$method=POST", false, false, true),
-            ("||foo$method=post|put", false, false, true),
+        type M = NetworkFilterMethodMask;
+        const CASES: &[(&str, NetworkFilterMethodMask)] = &[
+            ("||foo$method=post", M::POST),
+            ("||foo$method=post|get", M::POST.union(M::GET)),
+            ("||foo$method=head|get", M::HEAD.union(M::GET)),
+            ("||foo$method=~get", M::all().difference(M::GET)),
+            ("||foo$method=POST", M::POST),
+            ("||foo$method=post|put", M::POST.union(M::PUT)),
+            ("||foo$method=put", M::PUT),
+            ("||foo$method=connect|delete", M::CONNECT.union(M::DELETE)),
+            ("||foo$method=options|patch", M::OPTIONS.union(M::PATCH)),
+            (
+                "||foo$method=~put|~delete",
+                M::all().difference(M::PUT.union(M::DELETE)),
+            ),
+            ("||foo$method=get|post|~post", M::GET),
         ];
 
-        for (rule, get, head, post) in CASES {
+        for (rule, methods) in CASES {
             let filter = NetworkFilter::parse(rule, true, Default::default()).unwrap();
             assert_eq!(
-                foo_method_breakdown(*get, *head, *post),
+                foo_method_breakdown(*methods),
                 NetworkFilterBreakdown::from(&filter),
                 "rule: {rule}",
             );
@@ -1429,7 +2292,7 @@ mod parse_tests {
                 Default::default(),
             )
             .unwrap();
-            assert!(filter.mask.contains(NetworkFilterMask::FROM_POST));
+            assert_eq!(filter.methods, NetworkFilterMethodMask::POST);
             assert!(filter.mask.contains(NetworkFilterMask::FROM_XMLHTTPREQUEST));
             assert!(!filter.mask.contains(NetworkFilterMask::FROM_NETWORK_TYPES));
             assert!(filter.first_party());
@@ -1444,17 +2307,13 @@ mod parse_tests {
             )
             .unwrap();
             assert!(filter.is_exception());
-            assert!(filter.mask.contains(NetworkFilterMask::FROM_HEAD));
-            assert!(filter.mask.contains(NetworkFilterMask::FROM_GET));
-            assert!(!filter.mask.contains(NetworkFilterMask::FROM_POST));
+            assert_eq!(
+                filter.methods,
+                NetworkFilterMethodMask::HEAD | NetworkFilterMethodMask::GET
+            );
         }
 
-        const INVALID_RULES: &[&str] = &[
-            "||foo$method=put",
-            "||foo$method=connect|delete",
-            "||foo$method=",
-            "||foo$~method=post",
-        ];
+        const INVALID_RULES: &[&str] = &["||foo$method=", "||foo$~method=post"];
 
         for filter_text in INVALID_RULES {
             assert!(
@@ -1463,6 +2322,27 @@ mod parse_tests {
             );
         }
 
+        for filter_text in [
+            "||foo$method=trace",
+            "||foo$method=get|trace",
+            "||foo$method=~",
+            "||foo$method=get|",
+        ] {
+            assert_eq!(
+                NetworkFilter::parse(filter_text, true, Default::default()).err(),
+                Some(NetworkFilterError::MethodInvalidValue),
+                "expected MethodInvalidValue: {filter_text}",
+            );
+        }
+
+        for filter_text in ["||foo$method=get|~get", "||foo$method=put|~put|~post"] {
+            assert_eq!(
+                NetworkFilter::parse(filter_text, true, Default::default()).err(),
+                Some(NetworkFilterError::EmptyMethod),
+                "expected EmptyMethod: {filter_text}",
+            );
+        }
+
         assert!(NetworkFilter::parse("@@||foo^$generichide", true, Default::default()).is_ok());
 
         for filter_text in [
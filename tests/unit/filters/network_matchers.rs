@@ -2,6 +2,7 @@
 mod match_tests {
     use super::super::*;
     use crate::filters::network::*;
+    use std::borrow::Cow;
 
     #[test]
     fn is_anchored_by_hostname_works() {
@@ -385,38 +386,47 @@ mod match_tests {
         );
     }
 
-    fn check_options(filter: &NetworkFilter, request: &request::Request) -> bool {
+    fn check_options<'a>(filter: &'a NetworkFilter, request: &request::Request) -> bool {
         let mut mapping = HashMap::new();
-        let opt_domains = filter.opt_domains.clone().map(|domains| {
-            domains
-                .iter()
-                .map(|domain| {
-                    mapping.insert(*domain, *domain as u32);
-                    *domain as u32
-                })
-                .collect::<Vec<u32>>()
-        });
-
-        let opt_not_domains = filter.opt_not_domains.clone().map(|domains| {
-            domains
-                .iter()
-                .map(|domain| {
-                    mapping.insert(*domain, *domain as u32);
-                    *domain as u32
-                })
-                .collect::<Vec<u32>>()
-        });
-
-        super::super::check_options(filter.mask, request)
+        let mut map_domains = |domains: &Option<Vec<Hash>>| {
+            domains.as_ref().map(|domains| {
+                domains
+                    .iter()
+                    .map(|domain| {
+                        mapping.insert(*domain, *domain as u32);
+                        *domain as u32
+                    })
+                    .collect::<Vec<u32>>()
+            })
+        };
+
+        let opt_domains = map_domains(&filter.opt_domains);
+        let opt_not_domains = map_domains(&filter.opt_not_domains);
+        let opt_entities = map_domains(&filter.opt_entities);
+        let opt_not_entities = map_domains(&filter.opt_not_entities);
+        let regexes = |regexes: &'a Option<Vec<Cow<'_, str>>>| {
+            regexes
+                .as_ref()
+                .map(|regexes| regexes.iter().map(AsRef::as_ref))
+        };
+
+        let mut regex_manager = RegexManager::default();
+        super::super::check_options(filter.mask, filter.methods, request)
             && super::super::check_included_domains_mapped(
                 opt_domains.as_deref(),
+                opt_entities.as_deref(),
+                regexes(&filter.opt_domain_regexes),
                 request,
                 &mapping,
+                &mut regex_manager,
             )
             && super::super::check_excluded_domains_mapped(
                 opt_not_domains.as_deref(),
+                opt_not_entities.as_deref(),
+                regexes(&filter.opt_not_domain_regexes),
                 request,
                 &mapping,
+                &mut regex_manager,
             )
     }
 
@@ -475,6 +485,28 @@ mod match_tests {
             assert!(!check_options(&network_filter, &request));
         }
 
+        // strict1p / strict3p
+        for (filter, source, expected) in [
+            ("||foo$strict1p", "http://foo.com", true),
+            ("||foo$strict1p", "http://baz.foo.com", false),
+            ("||foo$strict1p", "http://baz.bar.com", false),
+            ("||foo$strict3p", "http://foo.com", false),
+            ("||foo$strict3p", "http://baz.foo.com", true),
+            ("||foo$strict3p", "http://baz.bar.com", true),
+            ("||foo$~strict3p", "http://foo.com", true),
+            ("||foo$~strict1p", "http://baz.foo.com", true),
+            ("||foo$strict3p,1p", "http://baz.foo.com", true),
+            ("||foo$strict3p,1p", "http://baz.bar.com", false),
+        ] {
+            let network_filter = NetworkFilter::parse(filter, true, Default::default()).unwrap();
+            let request = request::Request::new("https://foo.com/bar", source, "", "").unwrap();
+            assert_eq!(
+                check_options(&network_filter, &request),
+                expected,
+                "Expected {expected} for {filter} from {source}"
+            );
+        }
+
         // opt-domain
         {
             let network_filter =
@@ -507,22 +539,139 @@ mod match_tests {
             assert!(!check_options(&network_filter, &request));
         }
 
-        // $to is parsed but not applied at match time
-        {
-            let network_filter =
-                NetworkFilter::parse("foo$to=foo.com", true, Default::default()).unwrap();
+        // entities
+        for (filter, source, expected) in [
+            ("||foo$domain=google.*", "https://google.com", true),
+            ("||foo$domain=google.*", "https://www.google.co.uk", true),
+            // `blogspot.com` is a public suffix
+            ("||foo$domain=google.*", "https://google.blogspot.com", true),
+            ("||foo$domain=google.*", "https://notgoogle.com", false),
+            ("||foo$domain=google.*", "https://google.example.com", false),
+            ("||foo$domain=google.*", "", false),
+            ("||foo$domain=google.*|bar.com", "https://bar.com", true),
+            ("||foo$domain=google.*|bar.com", "https://google.de", true),
+            ("||foo$domain=~google.*", "https://google.de", false),
+            ("||foo$domain=~google.*", "https://mail.google.de", false),
+            ("||foo$domain=~google.*", "https://bar.com", true),
+            ("||foo$domain=~google.*", "", true),
+            (
+                "||foo$domain=google.*|~mail.google.de",
+                "https://google.de",
+                true,
+            ),
+            (
+                "||foo$domain=google.*|~mail.google.de",
+                "https://mail.google.de",
+                false,
+            ),
+        ] {
+            let network_filter = NetworkFilter::parse(filter, true, Default::default()).unwrap();
+            let request = request::Request::new("https://foo.com/bar", source, "", "").unwrap();
+            assert_eq!(
+                check_options(&network_filter, &request),
+                expected,
+                "{filter} from {source}"
+            );
+            assert_eq!(
+                network_filter.matches_test(&request),
+                expected,
+                "{filter} from {source}"
+            );
+        }
+
+        // regexes are matched against the full source hostname
+        #[cfg(feature = "full-regex-handling")]
+        for (filter, source, expected) in [
+            (
+                r"||foo$domain=/^img[a-z]{2\,}\.com\$/",
+                "https://imgxyz.com",
+                true,
+            ),
+            (
+                r"||foo$domain=/^img[a-z]{2\,}\.com\$/",
+                "https://img1.com",
+                false,
+            ),
+            (
+                r"||foo$domain=/^img[a-z]{2\,}\.com\$/",
+                "https://www.imgxyz.com",
+                false,
+            ),
+            (r"||foo$domain=/^img[a-z]{2\,}\.com\$/", "", false),
+            (r"||foo$domain=/img/", "https://www.imgxyz.com", true),
+            (
+                r"||foo$domain=bar.com|/^(a|b)\.com\$/",
+                "https://b.com",
+                true,
+            ),
+            (
+                r"||foo$domain=bar.com|/^(a|b)\.com\$/",
+                "https://sub.bar.com",
+                true,
+            ),
+            (
+                r"||foo$domain=bar.com|/^(a|b)\.com\$/",
+                "https://c.com",
+                false,
+            ),
+            (r"||foo$domain=~/^ad/", "https://adserver.com", false),
+            (r"||foo$domain=~/^ad/", "https://bar.com", true),
+            (r"||foo$domain=~/^ad/", "", true),
+            (
+                r"||foo$domain=/\.com\$/|~/^ad/",
+                "https://adserver.com",
+                false,
+            ),
+            (r"||foo$domain=/\.com\$/|~/^ad/", "https://server.com", true),
+            (
+                r"||foo$domain=bar.com|~/^mail\./",
+                "https://mail.bar.com",
+                false,
+            ),
+        ] {
+            let network_filter = NetworkFilter::parse(filter, true, Default::default()).unwrap();
+            let request = request::Request::new("https://foo.com/bar", source, "", "").unwrap();
+            assert_eq!(
+                check_options(&network_filter, &request),
+                expected,
+                "{filter} from {source}"
+            );
+            assert_eq!(
+                network_filter.matches_test(&request),
+                expected,
+                "{filter} from {source}"
+            );
+        }
+
+        // $to
+        for (filter, url, expected) in [
+            ("foo$to=foo.com", "https://foo.com/foo", true),
+            ("foo$to=foo.com", "https://sub.foo.com/foo", true),
+            ("foo$to=foo.com", "https://example.com/foo", false),
+            ("foo$to=~foo.com", "https://foo.com/foo", false),
+            ("foo$to=~foo.com", "https://example.com/foo", true),
+            ("foo$to=google.*", "https://www.google.co.uk/foo", true),
+            ("foo$to=google.*", "https://google.com/foo", true),
+            ("foo$to=google.*", "https://example.com/foo", false),
+            ("foo$to=~google.*", "https://google.de/foo", false),
+            ("foo$to=~google.*", "https://example.com/foo", true),
+            (
+                "foo$to=google.*|~mail.google.com",
+                "https://mail.google.com/foo",
+                false,
+            ),
+        ] {
+            let network_filter = NetworkFilter::parse(filter, true, Default::default()).unwrap();
             assert!(network_filter.has_to_option());
-            let request =
-                request::Request::new("https://foo.com/foo", "https://example.com", "", "")
-                    .unwrap();
-            assert!(!network_filter.matches_test(&request));
-            let request =
-                request::Request::new("https://example.com/foo", "https://foo.com", "", "")
-                    .unwrap();
-            assert!(!network_filter.matches_test(&request));
+            let request = request::Request::new(url, "https://example.org", "", "").unwrap();
+            assert_eq!(
+                network_filter.matches_test(&request),
+                expected,
+                "{filter} to {url}"
+            );
         }
 
-        // $from + $to: ignored
+        // $from + $to
         {
             let network_filter = NetworkFilter::parse(
                 "*$script,3p,from=ovagames.com,to=~facebook.net|~fbcdn.net",
@@ -538,9 +687,9 @@ mod match_tests {
                 "",
             )
             .unwrap();
-            assert!(!network_filter.matches_test(&request));
+            assert!(network_filter.matches_test(&request));
             let request = request::Request::new(
-                "https://facebook.net/script.js",
+                "https://static.fbcdn.net/script.js",
                 "https://ovagames.com",
                 "script",
                 "",
@@ -556,6 +705,135 @@ mod match_tests {
             .unwrap();
             assert!(!network_filter.matches_test(&request));
         }
+
+        // $denyallow
+        {
+            let network_filter = NetworkFilter::parse(
+                "*$script,3p,denyallow=x.com|y.com,domain=a.com",
+                true,
+                Default::default(),
+            )
+            .unwrap();
+            for (url, expected) in [
+                ("https://other.com/script.js", true),
+                ("https://x.com/script.js", false),
+                ("https://sub.x.com/script.js", false),
+                ("https://y.com/script.js", false),
+                ("https://notx.com/script.js", true),
+                ("https://x.com.other.com/script.js", true),
+            ] {
+                let request = request::Request::new(url, "https://a.com", "script", "").unwrap();
+                assert_eq!(
+                    network_filter.matches_test(&request),
+                    expected,
+                    "Expected {expected} for {url}"
+                );
+            }
+            let request =
+                request::Request::new("https://other.com/script.js", "https://b.com", "script", "")
+                    .unwrap();
+            assert!(!network_filter.matches_test(&request));
+        }
+    }
+
+    #[test]
+    fn check_ip_address_works() {
+        let request = |address: Option<&str>| {
+            let mut request =
+                request::Request::new("https://example.com/ad.js", "https://a.com", "script", "")
+                    .unwrap();
+            request.ip_address = address.map(|address| address.parse().unwrap());
+            request
+        };
+
+        for (filter, address, expected) in [
+            ("*$ipaddress=192.0.2.1", Some("192.0.2.1"), true),
+            ("*$ipaddress=192.0.2.1", Some("192.0.2.10"), false),
+            ("*$ipaddress=192.0.2.1", None, false),
+            ("*$ipaddress=10.0.0.0/8", Some("10.1.2.3"), true),
+            ("*$ipaddress=10.0.0.0/8", Some("11.0.0.1"), false),
+            ("*$ipaddress=10.0.0.0/8", Some("::ffff:10.0.0.1"), true),
+            ("*$ipaddress=0.0.0.0/0", Some("203.0.113.1"), true),
+            ("*$ipaddress=0.0.0.0/0", Some("2001:db8::1"), false),
+            ("*$ipaddress=2001:db8::/32", Some("2001:db8:1::1"), true),
+            ("*$ipaddress=2001:db8::/32", Some("2001:db9::1"), false),
+            ("*$ipaddress=::/0", Some("2001:db8::1"), true),
+            ("*$ipaddress=lan", Some("192.168.1.1"), true),
+            ("*$ipaddress=lan", Some("172.20.0.1"), true),
+            ("*$ipaddress=lan", Some("169.254.0.1"), true),
+            ("*$ipaddress=lan", Some("fd00::1"), true),
+            ("*$ipaddress=lan", Some("fe80::1"), true),
+            ("*$ipaddress=lan", Some("8.8.8.8"), false),
+            ("*$ipaddress=lan", Some("2001:db8::1"), false),
+            ("*$ipaddress=loopback", Some("127.0.0.1"), true),
+            ("*$ipaddress=loopback", Some("::1"), true),
+            ("*$ipaddress=loopback", Some("192.168.1.1"), false),
+            #[cfg(feature = "full-regex-handling")]
+            (r"*$ipaddress=/^192\.0\.2\./", Some("192.0.2.55"), true),
+            #[cfg(feature = "full-regex-handling")]
+            (r"*$ipaddress=/^192\.0\.2\./", Some("192.0.20.1"), false),
+            #[cfg(feature = "full-regex-handling")]
+            ("*$ipaddress=/^2001:DB8:/i", Some("2001:db8::1"), true),
+            ("||example.com^$ipaddress=lan", Some("10.0.0.1"), true),
+            ("||example.net^$ipaddress=lan", Some("10.0.0.1"), false),
+        ] {
+            let network_filter = NetworkFilter::parse(filter, true, Default::default()).unwrap();
+            assert_eq!(
+                network_filter.matches_test(&request(address)),
+                expected,
+                "Expected {expected} for {filter} with {address:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_dns_type_works() {
+        for (value, query_type, expected) in [
+            (None, "A", true),
+            (Some("A|AAAA"), "A", true),
+            (Some("A|AAAA"), "aaaa", true),
+            (Some("A|AAAA"), "HTTPS", false),
+            (Some("~CNAME"), "A", true),
+            (Some("~CNAME"), "CNAME", false),
+            (Some("A|~AAAA"), "AAAA", false),
+            (Some("A|~AAAA"), "MX", false),
+        ] {
+            assert_eq!(
+                check_dns_type(value, query_type, &mut RegexManager::default()),
+                expected,
+                "Expected {expected} for {value:?} with {query_type}"
+            );
+        }
+    }
+
+    #[test]
+    fn check_client_works() {
+        let laptop = Some("Frank's laptop");
+        let address =
+            |address: &str| -> Option<std::net::IpAddr> { Some(address.parse().unwrap()) };
+
+        for (value, name, ip, expected) in [
+            (None, None, None, true),
+            (Some("'Frank\\'s laptop'"), laptop, None, true),
+            (Some("\"Frank's laptop\""), laptop, None, true),
+            (Some("'Frank\\'s laptop'"), Some("tv"), None, false),
+            (Some("'Frank\\'s laptop'"), None, None, false),
+            (Some("tv|'Frank\\'s laptop'"), laptop, None, true),
+            (Some("192.168.0.0/24"), None, address("192.168.0.7"), true),
+            (Some("192.168.0.0/24"), None, address("192.168.1.7"), false),
+            (Some("192.168.0.7"), laptop, address("192.168.0.7"), true),
+            (Some("2001:db8::/32"), None, address("2001:db8::1"), true),
+            (Some("~tv"), None, None, true),
+            (Some("~tv"), Some("tv"), None, false),
+            (Some("~192.168.0.1"), None, address("192.168.0.1"), false),
+            (Some("~192.168.0.1"), None, address("192.168.0.2"), true),
+        ] {
+            assert_eq!(
+                check_client(value, name, ip, &mut RegexManager::default()),
+                expected,
+                "Expected {expected} for {value:?} with {name:?} at {ip:?}"
+            );
+        }
     }
 
     #[test]
@@ -630,6 +908,32 @@ mod match_tests {
             let request = request::Request::new(url, "", "xhr", "head").unwrap();
             assert!(check_options(&filter, &request));
         }
+
+        {
+            let filter =
+                NetworkFilter::parse("||foo$method=put|delete", true, Default::default()).unwrap();
+            for method in ["put", "delete", "PUT"] {
+                let request = request::Request::new(url, "", "xhr", method).unwrap();
+                assert!(check_options(&filter, &request), "{method}");
+            }
+            for method in ["get", "post", "connect", "options", "patch", "trace", ""] {
+                let request = request::Request::new(url, "", "xhr", method).unwrap();
+                assert!(!check_options(&filter, &request), "{method}");
+            }
+        }
+
+        {
+            let filter =
+                NetworkFilter::parse("||foo$method=~options", true, Default::default()).unwrap();
+            for method in ["connect", "delete", "get", "head", "patch", "post", "put"] {
+                let request = request::Request::new(url, "", "xhr", method).unwrap();
+                assert!(check_options(&filter, &request), "{method}");
+            }
+            for method in ["options", "trace", ""] {
+                let request = request::Request::new(url, "", "xhr", method).unwrap();
+                assert!(!check_options(&filter, &request), "{method}");
+            }
+        }
     }
 
     #[test]
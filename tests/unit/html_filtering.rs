@@ -0,0 +1,73 @@
+#[cfg(all(test, feature = "html-filtering"))]
+mod rewriter_tests {
+    use super::super::*;
+
+    fn filters(rules: &[&str]) -> HtmlFilters {
+        let list: Vec<_> = rules
+            .iter()
+            .map(|rule| format!("example.com##^{rule}"))
+            .collect();
+        let engine = crate::Engine::new_with_list_text(list.join("\n"));
+        engine.url_html_filters("https://example.com")
+    }
+
+    /// Rewrites `html` in chunks of `chunk_size` bytes.
+    fn rewrite(filters: &HtmlFilters, html: &str, chunk_size: usize) -> String {
+        let mut output = vec![];
+        let mut rewriter = filters.rewriter(|chunk: &[u8]| output.extend_from_slice(chunk));
+        for chunk in html.as_bytes().chunks(chunk_size) {
+            rewriter.write(chunk).unwrap();
+        }
+        rewriter.end().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn removes_css_matches() {
+        let filters = filters(&["div.ad", "#banner"]);
+        let html = r#"<html><body><div class="ad">ad <b>text</b></div><div id="banner"></div><div class="content">content</div></body></html>"#;
+        for chunk_size in [1, 7, html.len()] {
+            assert_eq!(
+                rewrite(&filters, html, chunk_size),
+                r#"<html><body><div class="content">content</div></body></html>"#
+            );
+        }
+    }
+
+    #[test]
+    fn removes_raw_text_matches() {
+        let filters = filters(&[
+            "script:has-text(adblock)",
+            "script:has-text(/detect(ed)?Block/i)",
+            "style:min-text-length(20)",
+        ]);
+        let html = r#"<head><script src="a.js" data-x="&quot;">var adblock = 1;</script><script>ok();</script><script>window.DETECTBLOCK()</script><style>.a{color:red}</style><style>.ad{display:none !important}</style></head>"#;
+        for chunk_size in [1, 5, html.len()] {
+            assert_eq!(
+                rewrite(&filters, html, chunk_size),
+                "<head><script>ok();</script><style>.a{color:red}</style></head>"
+            );
+        }
+    }
+
+    #[test]
+    fn overlapping_filters() {
+        let filters = filters(&[
+            "script:has-text(foo)",
+            "script[async]:has-text(bar)",
+            "script[defer]",
+        ]);
+        let html = "<script async>bar()</script><script async>foo()</script><script defer>baz()</script><script async>baz()</script>";
+        assert_eq!(
+            rewrite(&filters, html, 3),
+            r#"<script async="">baz()</script>"#
+        );
+    }
+
+    #[test]
+    fn unsupported_filters_are_ignored() {
+        let filters = filters(&["div:has-text(ad)", "div.ad:upward(1)"]);
+        let html = r#"<div class="ad">ad</div>"#;
+        assert_eq!(rewrite(&filters, html, html.len()), html);
+    }
+}
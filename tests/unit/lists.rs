@@ -416,12 +416,7 @@ mod tests {
             let input =
                 r#"odkrywamyzakryte.com#%#//scriptlet("abort-on-property-read", "sc_adv_out")"#;
             let result = parse_filter(input, true, Default::default());
-            assert!(matches!(
-                result,
-                Err(FilterParseError::Cosmetic(
-                    CosmeticFilterError::UnsupportedSyntax
-                ))
-            ));
+            assert!(matches!(result, Ok(ParsedLine::Cosmetic(..))));
         }
         {
             let input = "bikeradar.com,spiegel.de#@%#!function(){function b(){}function a(a){return{get:function(){return a},set:b}}function c(a)";
@@ -429,30 +424,20 @@ mod tests {
             assert!(matches!(
                 result,
                 Err(FilterParseError::Cosmetic(
-                    CosmeticFilterError::UnsupportedSyntax
+                    CosmeticFilterError::AdguardSyntaxUnsupported
                 ))
             ));
         }
         {
             let input = "nczas.com#$#.adsbygoogle { position: absolute!important; left: -3000px!important; }";
             let result = parse_filter(input, true, Default::default());
-            assert!(matches!(
-                result,
-                Err(FilterParseError::Cosmetic(
-                    CosmeticFilterError::UnsupportedSyntax
-                ))
-            ));
+            assert!(matches!(result, Ok(ParsedLine::Cosmetic(..))));
         }
         {
             let input =
                 "kurnik.pl#@$#.adsbygoogle { height: 1px !important; width: 1px !important; }";
             let result = parse_filter(input, true, Default::default());
-            assert!(matches!(
-                result,
-                Err(FilterParseError::Cosmetic(
-                    CosmeticFilterError::UnsupportedSyntax
-                ))
-            ));
+            assert!(matches!(result, Ok(ParsedLine::Cosmetic(..))));
         }
     }
 }
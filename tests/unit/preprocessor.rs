@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    fn preprocess<'a>(list: &[&'a str], env: &[&str]) -> Vec<(usize, PreprocessedLine<'a>)> {
+        let env = env.iter().copied().collect();
+        Preprocessor::new(list.iter().copied(), &env).collect()
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        let env: PreprocessorEnv = ["env_mobile", "cap_html_filtering"].into_iter().collect();
+        let conditions = [
+            ("env_mobile", true),
+            ("env_safari", false),
+            ("!env_mobile", false),
+            ("!env_safari", true),
+            ("!!env_mobile", true),
+            ("env_mobile && cap_html_filtering", true),
+            ("env_mobile && env_safari", false),
+            ("env_safari || env_mobile", true),
+            ("env_safari || env_firefox", false),
+            ("env_safari || env_mobile && !cap_html_filtering", false),
+            ("(env_safari || env_mobile) && cap_html_filtering", true),
+            ("!(env_safari || env_firefox)", true),
+            ("  env_mobile  &&cap_html_filtering ", true),
+            // Malformed conditions are always false
+            ("", false),
+            ("env_mobile &&", false),
+            ("(env_mobile", false),
+            ("env_mobile)", false),
+            ("env_mobile env_mobile", false),
+            ("!env-safari", false),
+            ("env_mobile & cap_html_filtering", false),
+        ];
+        for (condition, expected) in conditions {
+            assert_eq!(evaluate(condition, &env), expected, "{condition}");
+        }
+    }
+
+    #[test]
+    fn skips_inactive_blocks() {
+        let list = [
+            "a",
+            "!#if env_mobile",
+            "b",
+            "!#if !env_safari",
+            "c",
+            "!#else",
+            "d",
+            "!#endif",
+            "!#else",
+            "e",
+            "!#endif",
+            "f",
+        ];
+        let lines = |env| {
+            preprocess(&list, env)
+                .into_iter()
+                .map(|(n, line)| match line {
+                    PreprocessedLine::Filter(line) => (n, line),
+                    PreprocessedLine::Include(_) => unreachable!(),
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lines(&[]), [(0, "a"), (9, "e"), (11, "f")]);
+        assert_eq!(
+            lines(&["env_mobile"]),
+            [(0, "a"), (2, "b"), (4, "c"), (11, "f")]
+        );
+        assert_eq!(
+            lines(&["env_mobile", "env_safari"]),
+            [(0, "a"), (2, "b"), (6, "d"), (11, "f")]
+        );
+    }
+
+    #[test]
+    fn handles_unbalanced_directives() {
+        // Unmatched `!#else` and `!#endif` are ignored, and an unterminated `!#if` continues to the
+        // end of the list.
+        let list = [
+            "!#endif",
+            "!#else",
+            "a",
+            "!#if env_mobile",
+            "b",
+            "!#else",
+            "c",
+        ];
+        assert_eq!(
+            preprocess(&list, &[]),
+            [
+                (2, PreprocessedLine::Filter("a")),
+                (6, PreprocessedLine::Filter("c")),
+            ]
+        );
+    }
+
+    #[test]
+    fn yields_active_includes() {
+        let list = [
+            "!#include common.txt",
+            "!#if env_mobile",
+            "!#include  mobile.txt ",
+            "!#else",
+            "!#include desktop.txt",
+            "!#endif",
+            "!#include",
+            "! #include comment.txt",
+            "!#safari_cb_affinity(general)",
+            "!#safari_cb_affinity",
+        ];
+        assert_eq!(
+            preprocess(&list, &["env_mobile"]),
+            [
+                (0, PreprocessedLine::Include("common.txt")),
+                (2, PreprocessedLine::Include("mobile.txt")),
+                (7, PreprocessedLine::Filter("! #include comment.txt")),
+            ]
+        );
+    }
+}
@@ -84,4 +84,62 @@ mod tests {
         assert_eq!(regex_manager.get_compiled_regex_count(), 2);
         assert_eq!(get_active_regex_count(&regex_manager), 1);
     }
+
+    #[test]
+    #[cfg(feature = "full-regex-handling")]
+    fn urltransform_regex_is_reused() {
+        let mut filter_set = crate::FilterSet::new(false);
+        filter_set.add_filters(
+            ["||example.com^$urltransform=/utm/ref/"],
+            crate::lists::ParseOptions {
+                permissions: crate::resources::PermissionMask::from_bits(0b1),
+                ..Default::default()
+            },
+        );
+        let engine = Engine::new_with_filter_set(filter_set);
+
+        for _ in 0..2 {
+            assert_eq!(
+                engine
+                    .check_network_request(&make_request("https://example.com/?utm=1"))
+                    .rewritten_url,
+                Some("https://example.com/?ref=1".to_string())
+            );
+        }
+
+        let regex_manager = engine.borrow_regex_manager();
+        assert_eq!(regex_manager.get_compiled_regex_count(), 1);
+        assert_eq!(get_active_regex_count(&regex_manager), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "full-regex-handling")]
+    fn option_regexes_are_keyed_by_source_and_case() {
+        let mut regex_manager = RegexManager::default();
+
+        assert!(regex_manager.matches_option_regex("ab", false, "ab"));
+        assert!(!regex_manager.matches_option_regex("ab", false, "AB"));
+        assert!(regex_manager.matches_option_regex("ab", true, "AB"));
+        assert!(regex_manager.matches_option_regex("ab", true, "ab"));
+
+        assert_eq!(regex_manager.get_compiled_regex_count(), 2);
+        assert_eq!(regex_manager.get_debug_regex_data().len(), 2);
+    }
+
+    #[test]
+    fn option_values_are_parsed_once() {
+        let mut regex_manager = RegexManager::default();
+
+        let first = regex_manager.get_ip_address_value("10.0.0.0/8").unwrap();
+        let second = regex_manager.get_ip_address_value("10.0.0.0/8").unwrap();
+        assert!(std::sync::Arc::ptr_eq(&first, &second));
+        assert!(regex_manager.get_ip_address_value("10.0.0.0/33").is_none());
+
+        assert_eq!(
+            regex_manager.get_dns_types("A|~AAAA"),
+            Some(&[(true, "A".to_string()), (false, "AAAA".to_string())][..])
+        );
+        assert_eq!(regex_manager.get_dns_types("A|"), None);
+        assert_eq!(regex_manager.get_clients("~tv").map(<[_]>::len), Some(1));
+    }
 }
@@ -39,6 +39,7 @@ mod tests {
         assert!(simple_example.is_https);
         assert!(simple_example.is_supported);
         assert!(!simple_example.is_third_party);
+        assert!(!simple_example.is_strict_third_party);
         assert_eq!(simple_example.request_type, RequestType::Document);
         assert_eq!(
             simple_example.source_hostname_hashes,
@@ -73,6 +74,7 @@ mod tests {
         assert!(first_party.is_https);
         assert!(first_party.is_supported);
         assert!(!first_party.is_third_party);
+        assert!(first_party.is_strict_third_party);
 
         let third_party = build_request(
             "document",
@@ -86,6 +88,7 @@ mod tests {
         assert!(third_party.is_https);
         assert!(third_party.is_supported);
         assert!(third_party.is_third_party);
+        assert!(third_party.is_strict_third_party);
 
         let websocket = build_request(
             "document",
@@ -184,6 +187,74 @@ mod tests {
         assert_eq!(bad_url.err(), Some(RequestError::HostnameParseError));
     }
 
+    #[test]
+    fn preparsed_entity_hashes() {
+        let request = Request::new(
+            "https://cdn.example.co.uk/ad.js",
+            "https://www.google.co.uk/",
+            "script",
+            "",
+        )
+        .unwrap();
+        let preparsed = Request::preparsed(
+            "https://cdn.example.co.uk/ad.js",
+            "cdn.example.co.uk",
+            "www.google.co.uk",
+            "script",
+            true,
+            "",
+        );
+        assert!(
+            preparsed
+                .get_source_entity_hashes()
+                .contains(&utils::fast_hash("google"))
+        );
+        assert_eq!(
+            preparsed.get_source_entity_hashes(),
+            request.get_source_entity_hashes()
+        );
+        assert!(
+            preparsed
+                .get_entity_hashes()
+                .contains(&utils::fast_hash("example"))
+        );
+        assert_eq!(preparsed.get_entity_hashes(), request.get_entity_hashes());
+    }
+
+    #[test]
+    fn with_hostname_alias() {
+        let request = Request::new(
+            "https://metrics.example.com/collect?id=1",
+            "https://www.example.com/",
+            "xhr",
+            "post",
+        )
+        .unwrap();
+        assert!(!request.is_third_party);
+
+        let aliased = request
+            .with_hostname_alias("example.com.tracker.net.")
+            .unwrap();
+        assert_eq!(aliased.url, "https://example.com.tracker.net/collect?id=1");
+        assert_eq!(aliased.hostname, "example.com.tracker.net");
+        assert!(aliased.is_third_party);
+        assert_eq!(aliased.request_type, RequestType::Xmlhttprequest);
+        assert_eq!(aliased.method, Some(RequestMethod::Post));
+        assert_eq!(
+            aliased.source_hostname_hashes,
+            request.source_hostname_hashes
+        );
+
+        let aliased = request.with_hostname_alias("cdn.example.com").unwrap();
+        assert!(!aliased.is_third_party);
+        assert!(aliased.is_strict_third_party);
+
+        assert_eq!(
+            request.with_hostname_alias("").err(),
+            Some(RequestError::HostnameParseError)
+        );
+    }
+
     #[test]
     fn parse_method_works() {
         assert_eq!(
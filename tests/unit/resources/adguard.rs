@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+
+    #[test]
+    fn aliases_are_sorted() {
+        assert!(
+            ADGUARD_SCRIPTLET_ALIASES
+                .windows(2)
+                .all(|pair| pair[0].0 < pair[1].0)
+        );
+    }
+
+    #[test]
+    fn scriptlet_names() {
+        assert_eq!(
+            ubo_scriptlet_name("abort-current-inline-script"),
+            Some("abort-current-script")
+        );
+        assert_eq!(ubo_scriptlet_name("set-constant"), Some("set-constant"));
+        assert_eq!(
+            ubo_scriptlet_name("prevent-setTimeout"),
+            Some("no-setTimeout-if")
+        );
+        assert_eq!(ubo_scriptlet_name("ubo-aopr.js"), Some("aopr"));
+        assert_eq!(ubo_scriptlet_name("ubo-nobab"), Some("nobab"));
+        assert_eq!(ubo_scriptlet_name("ubo-.js"), None);
+        assert_eq!(ubo_scriptlet_name("google-ima3"), None);
+        assert_eq!(ubo_scriptlet_name("Set-Constant"), None);
+    }
+
+    #[test]
+    fn scriptlet_args() {
+        assert_eq!(
+            parse_adguard_scriptlet_args(r#"'set-constant', 'ads.enabled', "false""#),
+            Some(vec![
+                "set-constant".to_string(),
+                "ads.enabled".to_string(),
+                "false".to_string()
+            ])
+        );
+        assert_eq!(
+            parse_adguard_scriptlet_args(r#"'prevent-fetch','a, b','it\'s'"#),
+            Some(vec![
+                "prevent-fetch".to_string(),
+                "a, b".to_string(),
+                "it's".to_string()
+            ])
+        );
+        assert_eq!(
+            parse_adguard_scriptlet_args("'nowebrtc'"),
+            Some(vec!["nowebrtc".to_string()])
+        );
+        assert_eq!(parse_adguard_scriptlet_args(""), Some(vec![]));
+
+        assert_eq!(parse_adguard_scriptlet_args("set-constant"), None);
+        assert_eq!(parse_adguard_scriptlet_args("'set-constant"), None);
+        assert_eq!(parse_adguard_scriptlet_args("'set-constant',"), None);
+        assert_eq!(parse_adguard_scriptlet_args("'a' 'b'"), None);
+        assert_eq!(parse_adguard_scriptlet_args(r#"'a', "b"#), None);
+    }
+}
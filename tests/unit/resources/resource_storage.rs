@@ -247,6 +247,29 @@ mod scriptlet_storage_tests {
         assert_eq!(args, vec!["remove-node-text", "script", ",mr=function(r,"]);
     }
 
+    #[test]
+    fn format_argslist_roundtrip() {
+        fn roundtrip(args: &[&str]) {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            let formatted = format_scriptlet_args(&args).unwrap();
+            assert_eq!(parse_scriptlet_args(&formatted), Some(args), "{formatted}");
+        }
+        roundtrip(&["set-constant", "ads.enabled", "false"]);
+        roundtrip(&["no-fetch-if", "a, b", "", " padded "]);
+        roundtrip(&["remove-node-text", "script", r#"'quoted' "both" ways"#]);
+        roundtrip(&["json-prune", r"a\b", "`tick`"]);
+        roundtrip(&["set-constant", r#"quote"s' `all"#]);
+
+        assert_eq!(
+            format_scriptlet_args(&["set-constant".to_string(), r"trailing\".to_string()]),
+            None
+        );
+        assert_eq!(
+            format_scriptlet_args(&["a".to_string(), r#"'", `"#.to_string()]),
+            None
+        );
+    }
+
     #[test]
     fn get_patched_scriptlets() {
         let resources = ResourceStorage::in_memory_from_resources([